@@ -0,0 +1,36 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use fluent_templates::{fluent_args, ArcLoader, DynLoader};
+use unic_langid::langid;
+
+fluent_templates::static_loader! {
+    static LOCALES = {
+        locales: "./tests/locales",
+        fallback_language: "en-US",
+        core_locales: "./tests/locales/core.ftl",
+    };
+}
+
+fn lookup(c: &mut Criterion) {
+    let arc = ArcLoader::builder("./tests/locales", langid!("en-US"))
+        .shared_resources(Some(&["./tests/locales/core.ftl".into()]))
+        .build()
+        .unwrap();
+    let loaders: [(&str, &dyn DynLoader); 2] = [("static", &*LOCALES), ("arc", &arc)];
+    let french = langid!("fr");
+    let args = fluent_args!["name" => "Alice"];
+
+    for (name, loader) in loaders {
+        c.bench_function(&format!("{name}/lookup"), |b| {
+            b.iter(|| loader.lookup(&french, "hello-world"))
+        });
+        c.bench_function(&format!("{name}/lookup_fallback"), |b| {
+            b.iter(|| loader.lookup(&french, "fallback"))
+        });
+        c.bench_function(&format!("{name}/lookup_with_args"), |b| {
+            b.iter(|| loader.lookup_complete(&french, "greeting", Some(&args)))
+        });
+    }
+}
+
+criterion_group!(benches, lookup);
+criterion_main!(benches);
@@ -1,23 +1,202 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet, HashMap},
     path::{Path, PathBuf},
 };
 
-use proc_macro2::TokenStream;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use proc_macro2::{Span, TokenStream};
 use quote::quote;
 use syn::{
-    braced,
+    braced, bracketed,
     parse::{Parse, ParseStream},
-    parse_macro_input, token, Ident, Result,
+    parse_macro_input,
+    punctuated::Punctuated,
+    token, Ident, Result,
 };
 
 struct StaticLoader {
     vis: Option<syn::Visibility>,
     name: Ident,
-    locales_directory: PathBuf,
+    /// Whether to create a function returning the loader, rather than a
+    /// static.
+    function: bool,
+    locales_directories: Vec<PathBuf>,
+    locales_span: Span,
     fallback_language: syn::LitStr,
-    core_locales: Option<PathBuf>,
+    core_locales: Vec<(PathBuf, Span)>,
     customise: Option<syn::ExprClosure>,
+    on_missing: Option<syn::Expr>,
+    prefix: Option<syn::LitStr>,
+    compress: bool,
+    fallback_overrides: Vec<(syn::LitStr, Vec<syn::LitStr>)>,
+    language_aliases: Vec<(syn::LitStr, syn::LitStr)>,
+    /// The `NegotiationStrategy` variant to match languages with, if it
+    /// isn't the default.
+    negotiation_strategy: Option<Ident>,
+    check_coverage: Option<Check>,
+    check_placeholders: Option<Check>,
+    check_orphans: Option<Check>,
+    /// How messages that are defined more than once in the same locales
+    /// directory are reported, or `None` if they silently override each
+    /// other.
+    duplicates: Option<Check>,
+    keys_module: Option<Ident>,
+    template_loader: Option<Ident>,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    env_vars: Vec<String>,
+    only_languages: Option<Vec<(unic_langid::LanguageIdentifier, Span)>>,
+    extensions: Vec<String>,
+    /// The languages of locale directories whose names aren't language
+    /// identifiers.
+    aliases: HashMap<String, unic_langid::LanguageIdentifier>,
+}
+
+/// Parses either a string literal or an array of them, returning the span of
+/// the whole value.
+fn parse_strings(input: ParseStream) -> Result<(Vec<syn::LitStr>, Span)> {
+    if input.peek(token::Bracket) {
+        let strings;
+        let bracket = bracketed!(strings in input);
+        let strings = Punctuated::<syn::LitStr, token::Comma>::parse_terminated(&strings)?;
+        Ok((strings.into_iter().collect(), bracket.span.join()))
+    } else {
+        let string = input.parse::<syn::LitStr>()?;
+        let span = string.span();
+        Ok((vec![string], span))
+    }
+}
+
+/// Evaluates a path given as a string literal, or as `env!` or `concat!` of
+/// them, adding the names of the environment variables it reads to
+/// `env_vars`.
+fn eval_path(expr: &syn::Expr, env_vars: &mut Vec<String>) -> Result<String> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(path),
+            ..
+        }) => Ok(path.value()),
+        syn::Expr::Macro(expr) if expr.mac.path.is_ident("env") => {
+            let name = expr.mac.parse_body::<syn::LitStr>()?;
+            let value = std::env::var(name.value()).map_err(|_| {
+                syn::Error::new(
+                    name.span(),
+                    format!("Environment variable `{}` isn't set", name.value()),
+                )
+            })?;
+            env_vars.push(name.value());
+            Ok(value)
+        }
+        syn::Expr::Macro(expr) if expr.mac.path.is_ident("concat") => expr
+            .mac
+            .parse_body_with(Punctuated::<syn::Expr, token::Comma>::parse_terminated)?
+            .iter()
+            .map(|part| eval_path(part, env_vars))
+            .collect(),
+        _ => Err(syn::Error::new_spanned(
+            expr,
+            "Expected a string literal, `env!` or `concat!`",
+        )),
+    }
+}
+
+/// Parses a path, see [`eval_path`], returning its value and span.
+fn parse_path(input: ParseStream, env_vars: &mut Vec<String>) -> Result<(String, Span)> {
+    let expr = input.parse::<syn::Expr>()?;
+    Ok((
+        eval_path(&expr, env_vars)?,
+        syn::spanned::Spanned::span(&expr),
+    ))
+}
+
+/// Parses either a path or an array of them, returning the span of the
+/// whole value.
+fn parse_paths(
+    input: ParseStream,
+    env_vars: &mut Vec<String>,
+) -> Result<(Vec<(String, Span)>, Span)> {
+    if input.peek(token::Bracket) {
+        let paths;
+        let bracket = bracketed!(paths in input);
+        let paths = Punctuated::<syn::Expr, token::Comma>::parse_terminated(&paths)?
+            .iter()
+            .map(|expr| {
+                Ok((
+                    eval_path(expr, env_vars)?,
+                    syn::spanned::Spanned::span(expr),
+                ))
+            })
+            .collect::<Result<_>>()?;
+        Ok((paths, bracket.span.join()))
+    } else {
+        let path = parse_path(input, env_vars)?;
+        let span = path.1;
+        Ok((vec![path], span))
+    }
+}
+
+/// Parses either an array of language identifiers, or a comma separated list
+/// of them given as a path would be, see [`eval_path`], e.g. with `env!`.
+fn parse_languages(
+    input: ParseStream,
+    env_vars: &mut Vec<String>,
+) -> Result<Vec<(unic_langid::LanguageIdentifier, Span)>> {
+    let languages = if input.peek(token::Bracket) {
+        let languages;
+        bracketed!(languages in input);
+        Punctuated::<_, token::Comma>::parse_terminated_with(&languages, parse_language)?
+            .into_iter()
+            .map(|lang| (lang.value(), lang.span()))
+            .collect()
+    } else {
+        let (languages, span) = parse_path(input, env_vars)?;
+        languages
+            .split(',')
+            .map(str::trim)
+            .filter(|lang| !lang.is_empty())
+            .map(|lang| (lang.to_owned(), span))
+            .collect::<Vec<_>>()
+    };
+
+    languages
+        .into_iter()
+        .map(|(lang, span)| match lang.parse() {
+            Ok(lang) => Ok((lang, span)),
+            Err(_) => Err(syn::Error::new(
+                span,
+                format!("Invalid language identifier \"{lang}\""),
+            )),
+        })
+        .collect()
+}
+
+/// Parses either a glob pattern or an array of them.
+fn parse_globs(input: ParseStream) -> Result<GlobSet> {
+    let mut globs = GlobSetBuilder::new();
+    for pattern in parse_strings(input)?.0 {
+        let glob =
+            Glob::new(&pattern.value()).map_err(|error| syn::Error::new(pattern.span(), error))?;
+        globs.add(glob);
+    }
+    globs
+        .build()
+        .map_err(|error| syn::Error::new(input.span(), error))
+}
+
+/// Parses a string literal containing a valid language identifier.
+fn parse_language(input: ParseStream) -> Result<syn::LitStr> {
+    let lang = input.parse::<syn::LitStr>()?;
+    if lang
+        .value()
+        .parse::<unic_langid::LanguageIdentifier>()
+        .is_err()
+    {
+        return Err(syn::Error::new(
+            lang.span(),
+            format!("Invalid language identifier \"{}\"", lang.value()),
+        ));
+    }
+    Ok(lang)
 }
 
 impl Parse for StaticLoader {
@@ -26,15 +205,39 @@ impl Parse for StaticLoader {
             std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| String::from("./")),
         );
         let vis = input.parse::<syn::Visibility>().ok();
-        input.parse::<token::Static>()?;
+        let function = if input.peek(token::Fn) {
+            input.parse::<token::Fn>()?;
+            true
+        } else {
+            input.parse::<token::Static>()?;
+            false
+        };
         let name = input.parse::<Ident>()?;
         input.parse::<token::Eq>()?;
         let fields;
         braced!(fields in input);
-        let mut core_locales: Option<syn::LitStr> = None;
+        let mut core_locales = Vec::new();
         let mut customise = None;
         let mut fallback_language = None;
-        let mut locales_directory: Option<syn::LitStr> = None;
+        let mut locales_directories: Option<(Vec<(String, Span)>, Span)> = None;
+        let mut env_vars = Vec::new();
+        let mut only_languages = None;
+        let mut extensions = vec!["ftl".to_owned()];
+        let mut aliases = HashMap::new();
+        let mut on_missing = None;
+        let mut prefix = None;
+        let mut compress = false;
+        let mut fallback_overrides = Vec::new();
+        let mut language_aliases = Vec::new();
+        let mut negotiation_strategy = None;
+        let mut check_coverage = None;
+        let mut check_placeholders = None;
+        let mut check_orphans = None;
+        let mut duplicates = None;
+        let mut keys_module = None;
+        let mut template_loader = None;
+        let mut include = None;
+        let mut exclude = None;
 
         while !fields.is_empty() {
             let k = fields.parse::<Ident>()?;
@@ -43,11 +246,128 @@ impl Parse for StaticLoader {
             if k == "customise" {
                 customise = Some(fields.parse()?);
             } else if k == "core_locales" {
-                core_locales = Some(fields.parse()?);
+                core_locales = parse_paths(&fields, &mut env_vars)?.0;
             } else if k == "fallback_language" {
                 fallback_language = Some(fields.parse()?);
             } else if k == "locales" {
-                locales_directory = Some(fields.parse()?);
+                locales_directories = Some(parse_paths(&fields, &mut env_vars)?);
+            } else if k == "only_languages" {
+                only_languages = Some(parse_languages(&fields, &mut env_vars)?);
+            } else if k == "extensions" {
+                extensions = parse_strings(&fields)?
+                    .0
+                    .iter()
+                    .map(syn::LitStr::value)
+                    .collect();
+            } else if k == "include" {
+                include = Some(parse_globs(&fields)?);
+            } else if k == "exclude" {
+                exclude = Some(parse_globs(&fields)?);
+            } else if k == "on_missing" {
+                on_missing = Some(fields.parse()?);
+            } else if k == "prefix" {
+                prefix = Some(fields.parse()?);
+            } else if k == "compress" {
+                compress = fields.parse::<syn::LitBool>()?.value;
+                if compress && !cfg!(feature = "compress") {
+                    return Err(syn::Error::new(
+                        k.span(),
+                        "`compress` requires the `compress` feature of `fluent-templates`",
+                    ));
+                }
+            } else if k == "check_coverage" {
+                check_coverage = Some(fields.parse()?);
+            } else if k == "check_placeholders" {
+                check_placeholders = Some(fields.parse()?);
+            } else if k == "check_orphans" {
+                check_orphans = Some(fields.parse()?);
+            } else if k == "duplicates" {
+                let level = fields.parse::<syn::LitStr>()?;
+                let span = level.span();
+                duplicates = match &*level.value() {
+                    "override" => Some(None),
+                    "warn" => Some(Some(Check { deny: false, span })),
+                    "deny" => Some(Some(Check { deny: true, span })),
+                    _ => {
+                        return Err(syn::Error::new(
+                            span,
+                            "Expected either \"override\", \"warn\" or \"deny\"",
+                        ))
+                    }
+                };
+            } else if k == "negotiation_strategy" {
+                let strategy = fields.parse::<syn::LitStr>()?;
+                let variant = match &*strategy.value() {
+                    "specificity" => "Specificity",
+                    "matching" => "Matching",
+                    "lookup" => "Lookup",
+                    "filtering" => "Filtering",
+                    _ => {
+                        return Err(syn::Error::new(
+                            strategy.span(),
+                            "Expected either \"specificity\", \"matching\", \"lookup\" or \"filtering\"",
+                        ))
+                    }
+                };
+                negotiation_strategy = Some(Ident::new(variant, strategy.span()));
+            } else if k == "keys_module" {
+                keys_module = Some(fields.parse()?);
+            } else if k == "template_loader" {
+                let loader = fields.parse::<Ident>()?;
+                if function {
+                    return Err(syn::Error::new(
+                        loader.span(),
+                        "`template_loader` can only be used with a `static` loader",
+                    ));
+                }
+                template_loader = Some(loader);
+            } else if k == "aliases" {
+                let entries;
+                braced!(entries in fields);
+                while !entries.is_empty() {
+                    let name = entries.parse::<syn::LitStr>()?;
+                    entries.parse::<syn::Token![:]>()?;
+                    let lang = parse_language(&entries)?;
+                    aliases.insert(name.value(), lang.value().parse().unwrap());
+
+                    if entries.is_empty() {
+                        break;
+                    }
+                    entries.parse::<token::Comma>()?;
+                }
+            } else if k == "fallback_overrides" {
+                let overrides;
+                braced!(overrides in fields);
+                while !overrides.is_empty() {
+                    let lang = parse_language(&overrides)?;
+                    overrides.parse::<syn::Token![:]>()?;
+                    let fallbacks;
+                    bracketed!(fallbacks in overrides);
+                    let fallbacks = Punctuated::<_, token::Comma>::parse_terminated_with(
+                        &fallbacks,
+                        parse_language,
+                    )?;
+                    fallback_overrides.push((lang, fallbacks.into_iter().collect()));
+
+                    if overrides.is_empty() {
+                        break;
+                    }
+                    overrides.parse::<token::Comma>()?;
+                }
+            } else if k == "language_aliases" {
+                let entries;
+                braced!(entries in fields);
+                while !entries.is_empty() {
+                    let from = parse_language(&entries)?;
+                    entries.parse::<syn::Token![:]>()?;
+                    let to = parse_language(&entries)?;
+                    language_aliases.push((from, to));
+
+                    if entries.is_empty() {
+                        break;
+                    }
+                    entries.parse::<token::Comma>()?;
+                }
             } else {
                 return Err(syn::Error::new(k.span(), "Not a valid parameter"));
             }
@@ -59,24 +379,43 @@ impl Parse for StaticLoader {
         }
         input.parse::<token::Semi>()?;
 
-        let locales_directory = locales_directory
+        let (locales_directories, locales_span) = locales_directories
             .ok_or_else(|| syn::Error::new(name.span(), "Missing `locales` field"))?;
+        let duplicates = duplicates.unwrap_or(Some(Check {
+            deny: true,
+            span: locales_span,
+        }));
 
-        let locales_directory_path = workspace_path.join(locales_directory.value());
+        let mut locales_directory_paths = Vec::new();
+        for (locales_directory, span) in locales_directories {
+            let locales_directory_path = workspace_path.join(locales_directory);
 
-        if std::fs::metadata(&locales_directory_path).is_err() {
-            return Err(syn::Error::new(locales_directory.span(), format!("Couldn't read locales directory, this path should be relative to your crate's `Cargo.toml`. Looking for: {:?}", locales_directory_path)));
+            if std::fs::metadata(&locales_directory_path).is_err() {
+                return Err(syn::Error::new(span, format!("Couldn't read locales directory, this path should be relative to your crate's `Cargo.toml`. Looking for: {:?}", locales_directory_path)));
+            }
+            locales_directory_paths.push(locales_directory_path);
+        }
+        if locales_directory_paths.is_empty() {
+            return Err(syn::Error::new(
+                locales_span,
+                "Expected at least one locales directory",
+            ));
         }
 
-        let core_locales = if let Some(core_locales) = &core_locales {
-            let core_locales_path = workspace_path.join(core_locales.value());
-            if std::fs::metadata(&core_locales_path).is_err() {
-                return Err(syn::Error::new(core_locales.span(), format!("Couldn't read core fluent resource, this path should be relative to your crate's `Cargo.toml`. Looking for: {:?}", core_locales_path)));
+        // Each core path is either a resource or a directory of them.
+        let mut core_locale_paths = Vec::new();
+        for (core_locales, span) in core_locales {
+            let core_locales_path = workspace_path.join(core_locales);
+            match std::fs::metadata(&core_locales_path) {
+                Ok(metadata) if metadata.is_dir() => {
+                    let mut paths = read_from_dir(&core_locales_path, &extensions);
+                    paths.sort();
+                    core_locale_paths.extend(paths.into_iter().map(|path| (path.into(), span)));
+                }
+                Ok(_) => core_locale_paths.push((core_locales_path, span)),
+                Err(_) => return Err(syn::Error::new(span, format!("Couldn't read core fluent resource, this path should be relative to your crate's `Cargo.toml`. Looking for: {:?}", core_locales_path))),
             }
-            Some(core_locales_path)
-        } else {
-            None
-        };
+        }
 
         let fallback_language = fallback_language
             .ok_or_else(|| syn::Error::new(name.span(), "Missing `fallback_language` field"))?;
@@ -84,18 +423,57 @@ impl Parse for StaticLoader {
         Ok(Self {
             vis,
             name,
-            locales_directory: locales_directory_path,
+            function,
+            locales_directories: locales_directory_paths,
+            locales_span,
             fallback_language,
-            core_locales,
+            core_locales: core_locale_paths,
             customise,
+            on_missing,
+            prefix,
+            compress,
+            fallback_overrides,
+            language_aliases,
+            negotiation_strategy,
+            check_coverage,
+            check_placeholders,
+            check_orphans,
+            duplicates,
+            keys_module,
+            template_loader,
+            include,
+            exclude,
+            env_vars,
+            only_languages,
+            extensions,
+            aliases,
         })
     }
 }
 
+/// Returns the canonical language of the locale directory `name`, either
+/// from `aliases` or by parsing it, which accepts `_` separators and any
+/// casing, e.g. `zh-Hans-CN` for `zh_hans_cn`.
+fn directory_language(
+    name: &str,
+    aliases: &HashMap<String, unic_langid::LanguageIdentifier>,
+) -> Option<String> {
+    aliases
+        .get(name)
+        .cloned()
+        .or_else(|| name.parse().ok())
+        .map(|lang| lang.to_string())
+}
+
 /// Copied from `fluent_templates::loader` to avoid needing a seperate crate to
-/// share the function.
-fn build_resources(dir: impl AsRef<std::path::Path>) -> HashMap<String, Vec<String>> {
-    let mut all_resources = HashMap::new();
+/// share the function. Returns the path, language and resources of each
+/// locale directory, sorted by path.
+fn build_resources(
+    dir: impl AsRef<std::path::Path>,
+    extensions: &[String],
+    aliases: &HashMap<String, unic_langid::LanguageIdentifier>,
+) -> Vec<(PathBuf, String, Vec<String>)> {
+    let mut all_resources = Vec::new();
     for entry in std::fs::read_dir(dir)
         .unwrap()
         .filter_map(|rs| rs.ok())
@@ -103,22 +481,412 @@ fn build_resources(dir: impl AsRef<std::path::Path>) -> HashMap<String, Vec<Stri
     {
         if let Some(lang) = entry
             .file_name()
-            .into_string()
-            .ok()
-            .filter(|l| l.parse::<unic_langid::LanguageIdentifier>().is_ok())
+            .to_str()
+            .and_then(|name| directory_language(name, aliases))
         {
-            let resources = read_from_dir(entry.path());
-            all_resources.insert(lang, resources);
+            let resources = read_from_dir(entry.path(), extensions);
+            all_resources.push((entry.path(), lang, resources));
         }
     }
+    all_resources.sort();
     all_resources
 }
 
+type Resource = fluent_syntax::ast::Resource<String>;
+
+/// Combines `errors` into a single error, if there are any.
+fn combine_errors(errors: impl IntoIterator<Item = syn::Error>) -> Option<syn::Error> {
+    errors.into_iter().reduce(|mut errors, error| {
+        errors.combine(error);
+        errors
+    })
+}
+
+/// Parses the resource at `path`, returning an error at `span` for each
+/// syntax error with the file, line and column that it was found at, and the
+/// first line of the entry that the parser had to skip as junk.
+fn parse_resource(path: &str, span: Span) -> Result<Resource> {
+    let source = std::fs::read_to_string(path)
+        .map_err(|error| syn::Error::new(span, format!("Couldn't read {path}: {error}")))?;
+    let errors = match fluent_syntax::parser::parse(source.clone()) {
+        Ok(resource) => return Ok(resource),
+        Err((_, errors)) => errors,
+    };
+
+    let errors = errors.into_iter().map(|error| {
+        let before = source.get(..error.pos.start).unwrap_or(&source);
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+        let junk = error
+            .slice
+            .and_then(|slice| source.get(slice))
+            .and_then(|junk| junk.lines().find(|line| !line.trim().is_empty()))
+            .map(|junk| format!(" in `{}`", junk.trim()))
+            .unwrap_or_default();
+        syn::Error::new(
+            span,
+            format!("{path}:{line}:{column}: {}{junk}", error.kind),
+        )
+    });
+    Err(combine_errors(errors).expect("parsing failed without errors"))
+}
+
+/// How problems found by one of the optional checks of `static_loader!` are
+/// reported, either `"warn"` or `"deny"`.
+struct Check {
+    deny: bool,
+    span: Span,
+}
+
+impl Parse for Check {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let level = input.parse::<syn::LitStr>()?;
+        let deny = match &*level.value() {
+            "warn" => false,
+            "deny" => true,
+            _ => {
+                return Err(syn::Error::new(
+                    level.span(),
+                    "Expected either \"warn\" or \"deny\"",
+                ))
+            }
+        };
+        Ok(Self {
+            deny,
+            span: level.span(),
+        })
+    }
+}
+
+impl Check {
+    /// Reports `problems` as errors, or as a warning from using the
+    /// deprecated constant `name`, as proc macros can't emit warnings
+    /// directly on stable.
+    fn report(&self, name: &str, problems: &[String]) -> Result<TokenStream> {
+        if problems.is_empty() {
+            return Ok(TokenStream::new());
+        }
+        if self.deny {
+            let errors = problems
+                .iter()
+                .map(|problem| syn::Error::new(self.span, problem));
+            return Err(combine_errors(errors).unwrap());
+        }
+
+        let name = Ident::new(name, self.span);
+        let note = format!("\n{}", problems.join("\n"));
+        Ok(quote::quote_spanned!(self.span=>
+            const _: () = {
+                #[deprecated(note = #note)]
+                #[allow(non_upper_case_globals)]
+                const #name: () = ();
+                #name
+            };
+        ))
+    }
+}
+
+/// Returns the IDs of the messages defined in `resources`.
+fn message_ids(resources: &[Resource]) -> BTreeSet<&str> {
+    resources
+        .iter()
+        .flat_map(|resource| &resource.body)
+        .filter_map(|entry| match entry {
+            fluent_syntax::ast::Entry::Message(message) => Some(&*message.id.name),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Describes the messages and terms of `resources` that are already in
+/// `defined`, which maps their IDs, with a leading `-` for terms, to the
+/// path of the resource that defines them.
+fn duplicate_problems<'a>(
+    resources: impl IntoIterator<Item = (&'a str, &'a Resource)>,
+    defined: &mut HashMap<String, &'a str>,
+) -> Vec<String> {
+    use fluent_syntax::ast::Entry;
+
+    let mut problems = Vec::new();
+    for (path, resource) in resources {
+        for entry in &resource.body {
+            let id = match entry {
+                Entry::Message(message) => message.id.name.clone(),
+                Entry::Term(term) => format!("-{}", term.id.name),
+                _ => continue,
+            };
+            if let Some(other) = defined.get(&id) {
+                problems.push(format!("`{id}` is defined in both {other} and {path}"));
+            } else {
+                defined.insert(id, path);
+            }
+        }
+    }
+    problems
+}
+
+/// Returns the variables referenced by the value and attributes of each
+/// message defined in `resources`, keyed by message ID.
+fn message_variables(resources: &[Resource]) -> BTreeMap<&str, BTreeSet<&str>> {
+    use fluent_syntax::ast;
+
+    fn pattern_vars<'a>(pattern: &'a ast::Pattern<String>, vars: &mut BTreeSet<&'a str>) {
+        for element in &pattern.elements {
+            if let ast::PatternElement::Placeable { expression } = element {
+                expression_vars(expression, vars);
+            }
+        }
+    }
+
+    fn expression_vars<'a>(expression: &'a ast::Expression<String>, vars: &mut BTreeSet<&'a str>) {
+        match expression {
+            ast::Expression::Select { selector, variants } => {
+                inline_expression_vars(selector, vars);
+                for variant in variants {
+                    pattern_vars(&variant.value, vars);
+                }
+            }
+            ast::Expression::Inline(inline) => inline_expression_vars(inline, vars),
+        }
+    }
+
+    fn inline_expression_vars<'a>(
+        expression: &'a ast::InlineExpression<String>,
+        vars: &mut BTreeSet<&'a str>,
+    ) {
+        match expression {
+            ast::InlineExpression::VariableReference { id } => {
+                vars.insert(&id.name);
+            }
+            ast::InlineExpression::FunctionReference { arguments, .. } => {
+                call_argument_vars(arguments, vars);
+            }
+            ast::InlineExpression::TermReference {
+                arguments: Some(arguments),
+                ..
+            } => call_argument_vars(arguments, vars),
+            ast::InlineExpression::Placeable { expression } => expression_vars(expression, vars),
+            _ => {}
+        }
+    }
+
+    fn call_argument_vars<'a>(
+        arguments: &'a ast::CallArguments<String>,
+        vars: &mut BTreeSet<&'a str>,
+    ) {
+        for argument in &arguments.positional {
+            inline_expression_vars(argument, vars);
+        }
+        for argument in &arguments.named {
+            inline_expression_vars(&argument.value, vars);
+        }
+    }
+
+    resources
+        .iter()
+        .flat_map(|resource| &resource.body)
+        .filter_map(|entry| match entry {
+            ast::Entry::Message(message) => {
+                let mut vars = BTreeSet::new();
+                let patterns = message
+                    .value
+                    .iter()
+                    .chain(message.attributes.iter().map(|attribute| &attribute.value));
+                for value in patterns {
+                    pattern_vars(value, &mut vars);
+                }
+                Some((&*message.id.name, vars))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Describes the messages whose variables differ between a locale and the
+/// `fallback` locale.
+fn placeholder_problems(parsed: &HashMap<String, Vec<Resource>>, fallback: &str) -> Vec<String> {
+    let expected = message_variables(parsed.get(fallback).map_or(&[], Vec::as_slice));
+    let mut locales = parsed
+        .keys()
+        .filter(|locale| *locale != fallback)
+        .collect::<Vec<_>>();
+    locales.sort();
+
+    let list = |vars: Vec<&&str>| {
+        vars.iter()
+            .map(|var| format!("${var}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut problems = Vec::new();
+    for locale in locales {
+        for (id, vars) in message_variables(&parsed[locale]) {
+            let Some(expected) = expected.get(id) else {
+                continue;
+            };
+            let missing = expected.difference(&vars).collect::<Vec<_>>();
+            let extra = vars.difference(expected).collect::<Vec<_>>();
+            let mut differences = Vec::new();
+            if !missing.is_empty() {
+                differences.push(format!("is missing {}", list(missing)));
+            }
+            if !extra.is_empty() {
+                differences.push(format!("has unexpected {}", list(extra)));
+            }
+            if !differences.is_empty() {
+                problems.push(format!(
+                    "Message `{id}` in locale `{locale}` {} compared to `{fallback}`",
+                    differences.join(" and ")
+                ));
+            }
+        }
+    }
+    problems
+}
+
+/// Creates a `&str` constant for each message ID in `ids`, named after the
+/// ID in upper snake case, e.g. `HELLO_WORLD` for `hello-world`.
+fn key_constants(module: &Ident, ids: &BTreeSet<&str>, prefix: &str) -> Result<TokenStream> {
+    let mut names = BTreeMap::new();
+    for id in ids {
+        let name = id.replace('-', "_").to_uppercase();
+        if let Some(other) = names.insert(name.clone(), *id) {
+            return Err(syn::Error::new(
+                module.span(),
+                format!("The messages `{other}` and `{id}` would both be named `{name}`"),
+            ));
+        }
+    }
+
+    Ok(names
+        .into_iter()
+        .map(|(name, id)| {
+            let name = Ident::new(&name, module.span());
+            let id = format!("{prefix}{id}");
+            let doc = format!("The ID of the `{id}` message.");
+            quote!(
+                #[doc = #doc]
+                pub const #name: &str = #id;
+            )
+        })
+        .collect())
+}
+
+/// Describes the messages that each locale is missing or has in addition
+/// to the `fallback` locale.
+fn coverage_problems(parsed: &HashMap<String, Vec<Resource>>, fallback: &str) -> Vec<String> {
+    let mut problems = Vec::new();
+    for (locale, missing, extra) in message_differences(parsed, fallback) {
+        if !missing.is_empty() {
+            problems.push(format!(
+                "Locale `{locale}` is missing messages: {}",
+                missing.join(", ")
+            ));
+        }
+        if !extra.is_empty() {
+            problems.push(format!(
+                "Locale `{locale}` has messages not in `{fallback}`: {}",
+                extra.join(", ")
+            ));
+        }
+    }
+    problems
+}
+
+/// Describes the messages that locales have in addition to the `fallback`
+/// locale, which can't be reached by falling back and are often left behind
+/// when a message is renamed.
+fn orphan_problems(parsed: &HashMap<String, Vec<Resource>>, fallback: &str) -> Vec<String> {
+    message_differences(parsed, fallback)
+        .into_iter()
+        .filter(|(_, _, extra)| !extra.is_empty())
+        .map(|(locale, _, extra)| {
+            format!(
+                "Locale `{locale}` has messages not in `{fallback}`, which are unreachable \
+                 from other locales: {}",
+                extra.join(", ")
+            )
+        })
+        .collect()
+}
+
+/// Returns the messages that each locale, in order, is missing and has in
+/// addition to the `fallback` locale.
+fn message_differences<'a>(
+    parsed: &'a HashMap<String, Vec<Resource>>,
+    fallback: &str,
+) -> Vec<(&'a str, Vec<&'a str>, Vec<&'a str>)> {
+    let expected = message_ids(parsed.get(fallback).map_or(&[], Vec::as_slice));
+    let mut locales = parsed
+        .keys()
+        .filter(|locale| *locale != fallback)
+        .collect::<Vec<_>>();
+    locales.sort();
+
+    locales
+        .into_iter()
+        .map(|locale| {
+            let ids = message_ids(&parsed[locale]);
+            let missing = expected.difference(&ids).copied().collect();
+            let extra = ids.difference(&expected).copied().collect();
+            (locale.as_str(), missing, extra)
+        })
+        .collect()
+}
+
+/// Reads the resource at `path` and compresses it into a byte string literal.
+#[cfg(feature = "compress")]
+fn compress_resource(path: &str) -> proc_macro2::Literal {
+    let source = std::fs::read_to_string(path).unwrap();
+    proc_macro2::Literal::byte_string(&miniz_oxide::deflate::compress_to_vec(
+        source.as_bytes(),
+        10,
+    ))
+}
+
+#[cfg(not(feature = "compress"))]
+fn compress_resource(_path: &str) -> proc_macro2::Literal {
+    unreachable!("`compress` is rejected without the `compress` feature")
+}
+
 /// Copied from `fluent_templates::fs` to avoid needing a seperate crate to
 /// share the function.
-pub(crate) fn read_from_dir<P: AsRef<Path>>(path: P) -> Vec<String> {
-    #[cfg(not(any(feature = "ignore", feature = "walkdir",)))]
-    compile_error!("one of the features `ignore` or `walkdir` must be enabled.");
+fn has_extension(path: &Path, extensions: &[String]) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    extensions.iter().any(|extension| {
+        name.strip_suffix(extension.trim_start_matches('.'))
+            .and_then(|stem| stem.strip_suffix('.'))
+            .is_some_and(|stem| !stem.is_empty())
+    })
+}
+
+/// Copied from `fluent_templates::fs` to avoid needing a seperate crate to
+/// share the function.
+pub(crate) fn read_from_dir<P: AsRef<Path>>(path: P, extensions: &[String]) -> Vec<String> {
+    // Without either walker, only `std::fs` is used, and symbolic links to
+    // directories aren't followed.
+    #[cfg(not(any(feature = "ignore", feature = "walkdir")))]
+    {
+        let mut files = Vec::new();
+        let mut directories = vec![path.as_ref().to_owned()];
+        while let Some(directory) = directories.pop() {
+            let Ok(entries) = std::fs::read_dir(&directory) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                    directories.push(path);
+                } else if path.is_file() && has_extension(&path, extensions) {
+                    files.push(path.display().to_string());
+                }
+            }
+        }
+        #[allow(clippy::needless_return)]
+        return files;
+    }
 
     #[cfg(feature = "ignore")]
     {
@@ -131,8 +899,8 @@ pub(crate) fn read_from_dir<P: AsRef<Path>>(path: P) -> Vec<String> {
                 let tx = tx.clone();
                 Box::new(move |result| {
                     if let Ok(entry) = result {
-                        if entry.file_type().as_ref().map_or(false, |e| e.is_file())
-                            && entry.path().extension().map_or(false, |e| e == "ftl")
+                        if entry.file_type().as_ref().is_some_and(|e| e.is_file())
+                            && has_extension(entry.path(), extensions)
                         {
                             tx.send(entry.path().display().to_string()).unwrap();
                         }
@@ -142,6 +910,7 @@ pub(crate) fn read_from_dir<P: AsRef<Path>>(path: P) -> Vec<String> {
                 })
             });
 
+        #[allow(clippy::needless_return)]
         return rx.drain().collect();
     }
 
@@ -151,7 +920,7 @@ pub(crate) fn read_from_dir<P: AsRef<Path>>(path: P) -> Vec<String> {
         .into_iter()
         .filter_map(|e| e.ok())
         .filter(|e| e.file_type().is_file())
-        .filter(|e| e.path().extension().map_or(false, |e| e == "ftl"))
+        .filter(|e| has_extension(e.path(), extensions))
         .map(|e| e.path().display().to_string())
         .collect()
 }
@@ -159,23 +928,115 @@ pub(crate) fn read_from_dir<P: AsRef<Path>>(path: P) -> Vec<String> {
 /// Loads all of your fluent resources at compile time as `&'static str`s and
 /// and creates a new `StaticLoader` static variable that you can use in your
 /// program. This allows you to easily ship your localisations as part of a
-/// single binary.
+/// single binary. Syntax errors in any of the resources are reported when
+/// compiling, with the file, line and column they're found at.
+///
+/// The static is a `LazyStaticLoader`, which dereferences to the
+/// `StaticLoader` and parses the resources on first use. Call its `try_init`
+/// method at startup to handle a resource failing to load as an error rather
+/// than a panic.
 ///
 /// ### Example
 /// ```no_compile
 /// fluent_templates::static_loader! {
 ///     // Declare our `StaticLoader` named `LOCALES`.
 ///     static LOCALES = {
-///         // The directory of localisations and fluent resources.
+///         // The directory of localisations and fluent resources, or an array
+///         // of directories whose resources are merged, with messages in
+///         // later directories overriding earlier ones. Paths can also be
+///         // given with `env!` and `concat!`, e.g.
+///         // `concat!(env!("WORKSPACE_ROOT"), "/locales")`.
 ///         locales: "./tests/locales",
+///         // Optional: The file extensions of the resources, `"ftl"` by
+///         // default, either a string or an array of strings.
+///         extensions: ["ftl", "ftl.txt"],
+///         // Optional: Glob patterns of the files to use, relative to each
+///         // locale's directory, either a string or an array of strings.
+///         include: "*.ftl",
+///         // Optional: Glob patterns of the files to skip, e.g. drafts.
+///         exclude: ["*.wip.ftl", "drafts/**"],
+///         // Optional: The languages of locale directories whose names aren't
+///         // language identifiers. Names like `en_US` or `zh_hans_cn` are
+///         // recognised without an alias.
+///         aliases: { "english": "en-US", "brazilian": "pt-BR" },
+///         // Optional: Only embed these languages, either an array or a comma
+///         // separated list, e.g. `env!("APP_LANGUAGES")`.
+///         only_languages: ["en-US", "de"],
 ///         // The language to falback on if something is not present.
 ///         fallback_language: "en-US",
-///         // Optional: A shared fluent resource
+///         // Optional: Fluent resources shared by every locale, either a
+///         // resource, a directory of resources, or an array of them.
 ///         core_locales: "./tests/locales/core.ftl",
-///         // Optional: A function that is run over each fluent bundle.
+///         // Optional: A function that is run over each fluent bundle,
+///         // optionally also taking the bundle's language, e.g.
+///         // `|bundle, lang| {}`.
 ///         customise: |bundle| {},
+///         // Optional: What to return when a message can't be found.
+///         on_missing: fluent_templates::OnMissing::DebugPanic,
+///         // Optional: A prefix added to the ID of every message, e.g. so a
+///         // library's messages don't collide with the application's in a
+///         // `MultiLoader`. `hello` is then looked up as `myapp-hello`.
+///         prefix: "myapp-",
+///         // Optional: Languages to search after the given language, before
+///         // the fallback language.
+///         fallback_overrides: { "pt-PT": ["pt-BR"], "nn": ["nb"] },
+///         // Optional: Languages that are looked up as another language, e.g.
+///         // legacy codes. An alias of a language also applies to its regions,
+///         // so `no-NO` is looked up as `nb-NO`.
+///         language_aliases: { "no": "nb", "iw": "he", "in": "id" },
+///         // Optional: How requested languages are matched against the
+///         // locales, either `"specificity"`, only using a language and the
+///         // more general languages it falls back to, `"matching"`,
+///         // `"lookup"` or `"filtering"`. `"specificity"` by default.
+///         negotiation_strategy: "matching",
+///         // Optional: Whether a message or term that's defined more than once
+///         // for a locale is overridden, `"override"`, overridden with a
+///         // warning naming both files, `"warn"`, or is an error, `"deny"`.
+///         // When overriding, files override those whose paths sort before
+///         // them, and locales override `core_locales`. Later locales
+///         // directories always override earlier ones. `"deny"` by default,
+///         // like `Duplicates::Error` for an `ArcLoader`.
+///         duplicates: "warn",
+///         // Optional: Check that every locale has the same messages as the
+///         // fallback language, either `"warn"` or `"deny"`.
+///         check_coverage: "warn",
+///         // Optional: Check that every locale's messages use the same
+///         // variables as the fallback language, either `"warn"` or `"deny"`.
+///         check_placeholders: "deny",
+///         // Optional: Check that no locale has messages that aren't in the
+///         // fallback language, e.g. after a message is renamed, either
+///         // `"warn"` or `"deny"`. `check_coverage` also reports these.
+///         check_orphans: "deny",
+///         // Optional: Create a module of `&str` constants for the ID of each
+///         // message in the fallback language, e.g. `locale_keys::HELLO_WORLD`.
+///         keys_module: locale_keys,
+///         // Optional: Also create a `FluentLoader` static for template
+///         // engines, using the fallback language as its default language,
+///         // e.g. `tera.register_function("fluent", LOCALES_TPL.clone())`.
+///         template_loader: LOCALES_TPL,
+///         // Optional: Embed the resources compressed, which requires the
+///         // `compress` feature. They are decompressed on first use.
+///         compress: true,
+///     };
+/// }
+/// ```
+///
+/// ### Constructor functions
+/// Using `fn` instead of `static` creates a function that returns a new
+/// `StaticLoader` each time it's called. The resources are only embedded and
+/// loaded once, but each loader builds its own bundles, so they can be
+/// customised independently, e.g. with `StaticLoader::rebuilt_with`. The
+/// function panics if a resource fails to load.
+///
+/// ```no_compile
+/// fluent_templates::static_loader! {
+///     pub fn build_locales = {
+///         locales: "./tests/locales",
+///         fallback_language: "en-US",
 ///     };
 /// }
+///
+/// let strict = build_locales().with_on_missing(fluent_templates::OnMissing::Panic);
 /// ```
 #[proc_macro]
 #[allow(non_snake_case)]
@@ -184,47 +1045,138 @@ pub fn static_loader(input: proc_macro::TokenStream) -> proc_macro::TokenStream
         core_locales,
         customise,
         fallback_language,
-        locales_directory,
+        locales_directories,
+        locales_span,
         name,
+        function,
+        on_missing,
+        prefix,
+        compress,
+        fallback_overrides,
+        language_aliases,
+        negotiation_strategy,
+        check_coverage,
+        check_placeholders,
+        check_orphans,
+        duplicates,
+        keys_module,
+        template_loader,
+        include,
+        exclude,
+        env_vars,
+        only_languages,
+        extensions,
+        aliases,
         vis,
         ..
     } = parse_macro_input!(input as StaticLoader);
     let CRATE_NAME: TokenStream = quote!(fluent_templates);
-    let LAZY: TokenStream = quote!(std::sync::LazyLock);
     let LANGUAGE_IDENTIFIER: TokenStream = quote!(#CRATE_NAME::loader::LanguageIdentifier);
-    let FLUENT_BUNDLE: TokenStream = quote!(#CRATE_NAME::FluentBundle);
     let FLUENT_RESOURCE: TokenStream = quote!(#CRATE_NAME::fluent_bundle::FluentResource);
     let HASHMAP: TokenStream = quote!(std::collections::HashMap);
 
-    let core_resource = if let Some(core_locales) = &core_locales {
-        let core_locales = core_locales.display().to_string();
-        quote!(
-            Some(
-                #CRATE_NAME::fs::resource_from_str(include_str!(#core_locales))
-                    .expect("Couldn't load core resources")
-            )
-        )
-    } else {
-        quote!(None)
+    // Embeds the resource at `path`, either as is or compressed.
+    let embed_resource = |path: &str| {
+        if compress {
+            let compressed = compress_resource(path);
+            quote!({
+                // Rebuilds the loader when the resource changes.
+                const _: &[u8] = include_bytes!(#path);
+                #CRATE_NAME::fs::resource_from_compressed(#compressed)
+            })
+        } else {
+            quote!(#CRATE_NAME::fs::resource_from_str(include_str!(#path)))
+        }
     };
 
-    let fallback_language_value = fallback_language.value();
-    if !fallback_language_value
+    let core_resources = core_locales
+        .iter()
+        .map(|(path, _)| embed_resource(&path.display().to_string()));
+
+    let fallback_language_value = match fallback_language
+        .value()
         .parse::<unic_langid::LanguageIdentifier>()
-        .is_ok()
     {
-        return syn::Error::new(
-            fallback_language.span(),
-            format!(
-                "Invalid language identifier \"{}\" for fallback language",
-                &fallback_language_value
-            ),
-        )
-        .to_compile_error()
-        .into();
+        Ok(lang) => lang.to_string(),
+        Err(_) => {
+            return syn::Error::new(
+                fallback_language.span(),
+                format!(
+                    "Invalid language identifier \"{}\" for fallback language",
+                    fallback_language.value()
+                ),
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
+
+    // Later directories come after earlier ones, so that their messages
+    // override those of earlier directories.
+    let mut insert_resources = HashMap::<String, Vec<String>>::new();
+    let mut resource_directories = HashMap::<String, usize>::new();
+    for (index, directory) in locales_directories.iter().enumerate() {
+        for (locale_directory, locale, mut resources) in
+            build_resources(directory, &extensions, &aliases)
+        {
+            resources.retain(|path| {
+                let path = Path::new(path);
+                let path = path.strip_prefix(&locale_directory).unwrap_or(path);
+                let included = match &include {
+                    Some(include) => include.is_match(path),
+                    None => true,
+                };
+                included
+                    && !exclude
+                        .as_ref()
+                        .is_some_and(|exclude| exclude.is_match(path))
+            });
+            resources.sort();
+            resource_directories.extend(resources.iter().map(|path| (path.clone(), index)));
+            insert_resources
+                .entry(locale)
+                .or_default()
+                .extend(resources);
+        }
     }
+    if let Some(only_languages) = &only_languages {
+        let fallback = fallback_language_value.parse().ok();
+        if !only_languages
+            .iter()
+            .any(|(lang, _)| Some(lang) == fallback.as_ref())
+        {
+            return syn::Error::new(
+                fallback_language.span(),
+                "The fallback language must be one of `only_languages`",
+            )
+            .to_compile_error()
+            .into();
+        }
 
-    let mut insert_resources: Vec<_> = build_resources(locales_directory).into_iter().collect();
+        let available = insert_resources
+            .keys()
+            .filter_map(|locale| locale.parse().ok())
+            .collect::<Vec<unic_langid::LanguageIdentifier>>();
+        let missing = only_languages
+            .iter()
+            .filter(|(lang, _)| !available.contains(lang))
+            .map(|(lang, span)| {
+                syn::Error::new(
+                    *span,
+                    format!("Language `{lang}` not found in locales directory"),
+                )
+            });
+        if let Some(errors) = combine_errors(missing) {
+            return errors.to_compile_error().into();
+        }
+
+        insert_resources.retain(|locale, _| {
+            locale
+                .parse::<unic_langid::LanguageIdentifier>()
+                .is_ok_and(|locale| only_languages.iter().any(|(lang, _)| *lang == locale))
+        });
+    }
+    let mut insert_resources: Vec<_> = insert_resources.into_iter().collect();
 
     if !insert_resources
         .iter()
@@ -238,6 +1190,87 @@ pub fn static_loader(input: proc_macro::TokenStream) -> proc_macro::TokenStream
         .into();
     }
 
+    // Report syntax errors in any resource when building, rather than when
+    // the loader is first used.
+    let mut parsed = HashMap::<String, Vec<Resource>>::new();
+    let mut errors = Vec::new();
+    for (locale, resources) in &insert_resources {
+        for path in resources {
+            match parse_resource(path, locales_span) {
+                Ok(resource) => parsed.entry(locale.clone()).or_default().push(resource),
+                Err(error) => errors.push(error),
+            }
+        }
+    }
+    let mut core_parsed = Vec::new();
+    for (path, span) in &core_locales {
+        match parse_resource(&path.display().to_string(), *span) {
+            Ok(resource) => core_parsed.push(resource),
+            Err(error) => errors.push(error),
+        }
+    }
+    if let Some(errors) = combine_errors(errors) {
+        return errors.to_compile_error().into();
+    }
+
+    let mut warnings = TokenStream::new();
+    if let Some(check) = duplicates {
+        let core_paths = core_locales
+            .iter()
+            .map(|(path, _)| path.display().to_string())
+            .collect::<Vec<_>>();
+        let mut core_defined = HashMap::new();
+        let mut problems = duplicate_problems(
+            core_paths.iter().map(String::as_str).zip(&core_parsed),
+            &mut core_defined,
+        );
+        // Later locales directories override earlier ones, so only the
+        // resources of the same directory are compared with each other.
+        for (locale, paths) in &insert_resources {
+            let parsed = parsed.get(locale).map_or(&[][..], Vec::as_slice);
+            let resources = paths
+                .iter()
+                .map(String::as_str)
+                .zip(parsed)
+                .collect::<Vec<_>>();
+            for directory in resources.chunk_by(|(a, _), (b, _)| {
+                resource_directories.get(*a) == resource_directories.get(*b)
+            }) {
+                problems.extend(duplicate_problems(
+                    directory.iter().copied(),
+                    &mut core_defined.clone(),
+                ));
+            }
+        }
+        match check.report("duplicate_messages", &problems) {
+            Ok(warning) => warnings.extend(warning),
+            Err(error) => return error.to_compile_error().into(),
+        }
+    }
+
+    let checks = [
+        (
+            check_coverage,
+            "incomplete_translations",
+            coverage_problems as fn(_, _) -> _,
+        ),
+        (
+            check_placeholders,
+            "mismatched_placeholders",
+            placeholder_problems,
+        ),
+        (check_orphans, "unreachable_messages", orphan_problems),
+    ];
+    for (check, name, problems) in checks {
+        let Some(check) = check else {
+            continue;
+        };
+        match check.report(name, &problems(&parsed, &fallback_language_value)) {
+            Ok(warning) => warnings.extend(warning),
+            Err(error) => return error.to_compile_error().into(),
+        }
+    }
+
     // Make the output `TokenStream` only depend on the filenames and the file contents,
     // not hashmap/filesystem iteration order.
     insert_resources.sort();
@@ -245,60 +1278,133 @@ pub fn static_loader(input: proc_macro::TokenStream) -> proc_macro::TokenStream
     let insert_resources = insert_resources
         .into_iter()
         .map(|(locale, resources)| {
+            let resources = resources.iter().map(|path| embed_resource(path));
             quote!(
                 resources.insert(
                     #locale.parse().unwrap(),
-                    vec![#(#CRATE_NAME::fs::resource_from_str(include_str!(#resources)).unwrap(),)*]
+                    vec![#(#resources?,)*]
                 );
             )
         })
         .collect::<TokenStream>();
 
+    // A `customise` closure taking two arguments is also given the language.
+    let lazy_bundles = match &customise {
+        Some(customise) if customise.inputs.len() == 2 => {
+            quote!(#CRATE_NAME::loader::LazyBundles::with_lang)
+        }
+        _ => quote!(#CRATE_NAME::loader::LazyBundles::new),
+    };
     let customise = customise.map_or(quote!(|_| ()), |c| quote!(#c));
 
+    let on_missing = on_missing.map(|on_missing| quote!(.with_on_missing(#on_missing)));
+    let with_prefix = prefix.as_ref().map(|prefix| quote!(.with_prefix(#prefix)));
+
+    let fallback_overrides = (!fallback_overrides.is_empty()).then(|| {
+        let overrides = fallback_overrides.iter().map(|(lang, fallbacks)| {
+            quote!((
+                #CRATE_NAME::langid!(#lang),
+                vec![#(#CRATE_NAME::langid!(#fallbacks),)*]
+            ))
+        });
+        quote!(.with_fallback_overrides([#(#overrides,)*]))
+    });
+    let language_aliases = (!language_aliases.is_empty()).then(|| {
+        let aliases = language_aliases
+            .iter()
+            .map(|(from, to)| quote!((#CRATE_NAME::langid!(#from), #CRATE_NAME::langid!(#to))));
+        quote!(.with_language_aliases([#(#aliases,)*]))
+    });
+    let negotiation_strategy = negotiation_strategy.map(
+        |strategy| quote!(.with_negotiation_strategy(#CRATE_NAME::NegotiationStrategy::#strategy)),
+    );
+
     let resource_map = quote! {
         let mut resources = #HASHMAP::new();
         #insert_resources
         resources
     };
 
-    let quote = quote! {
-        #vis static #name : #LAZY<#CRATE_NAME::StaticLoader> = #LAZY::new(|| {
-            static CORE_RESOURCE:
-                #LAZY<Option<#FLUENT_RESOURCE>> =
-                #LAZY::new(|| { #core_resource });
-
-            static RESOURCES:
-                #LAZY<#HASHMAP<#LANGUAGE_IDENTIFIER, Vec<#FLUENT_RESOURCE>>> =
-                #LAZY::new(|| { #resource_map });
-
-            static BUNDLES:
-                #LAZY<
-                    #HASHMAP<
-                        #LANGUAGE_IDENTIFIER,
-                        #FLUENT_BUNDLE<&'static #FLUENT_RESOURCE>
-                    >
-                > =
-                #LAZY::new(||  {
-                    #CRATE_NAME::loader::build_bundles(
-                        &*RESOURCES,
-                        CORE_RESOURCE.as_ref(),
-                        #customise
-                    )
-                });
-
-            static FALLBACKS:
-                #LAZY<#HASHMAP<#LANGUAGE_IDENTIFIER, Vec<#LANGUAGE_IDENTIFIER>>> =
-                #LAZY::new(|| #CRATE_NAME::loader::build_fallbacks(
-                    &RESOURCES.keys().cloned().collect::<Vec<#LANGUAGE_IDENTIFIER>>()
-                ));
+    let keys_module = match keys_module {
+        Some(module) => {
+            let fallback_parsed = parsed.get(&fallback_language_value);
+            let ids = message_ids(fallback_parsed.map_or(&[], Vec::as_slice))
+                .into_iter()
+                .chain(message_ids(&core_parsed))
+                .collect::<BTreeSet<_>>();
+            let prefix = prefix.as_ref().map(syn::LitStr::value).unwrap_or_default();
+            match key_constants(&module, &ids, &prefix) {
+                Ok(constants) => quote!(#vis mod #module { #constants }),
+                Err(error) => return error.to_compile_error().into(),
+            }
+        }
+        None => TokenStream::new(),
+    };
 
-            #CRATE_NAME::StaticLoader::new(
-                &BUNDLES,
-                &FALLBACKS,
-                #CRATE_NAME::langid!(#fallback_language_value)
-            )
-        });
+    let init = quote! {
+            #CRATE_NAME::loader::LazyStaticLoader::new(|| {
+                let core_resources: Vec<#FLUENT_RESOURCE> = vec![#(#core_resources?),*];
+                let resources: #HASHMAP<#LANGUAGE_IDENTIFIER, Vec<#FLUENT_RESOURCE>> = { #resource_map };
+
+                // Only leaked once all resources have loaded, as
+                // `LazyStaticLoader` keeps the first loader that's created.
+                let core_resources: &'static [#FLUENT_RESOURCE] =
+                    Box::leak(core_resources.into_boxed_slice());
+                let resources: &'static #HASHMAP<#LANGUAGE_IDENTIFIER, Vec<#FLUENT_RESOURCE>> =
+                    Box::leak(Box::new(resources));
+
+                let fallbacks: &'static #HASHMAP<#LANGUAGE_IDENTIFIER, Vec<#LANGUAGE_IDENTIFIER>> =
+                    Box::leak(Box::new(#CRATE_NAME::loader::build_fallbacks(
+                        &resources.keys().cloned().collect::<Vec<#LANGUAGE_IDENTIFIER>>()
+                    )));
+
+                Ok(#CRATE_NAME::StaticLoader::new(
+                    resources,
+                    core_resources,
+                    #lazy_bundles(resources, core_resources, #customise),
+                    fallbacks,
+                    #CRATE_NAME::langid!(#fallback_language_value)
+                )
+                #on_missing
+                #fallback_overrides
+                #language_aliases
+                #negotiation_strategy
+                #with_prefix)
+            })
+    };
+
+    let loader = if function {
+        quote! {
+            #vis fn #name() -> #CRATE_NAME::StaticLoader {
+                static LOADER: #CRATE_NAME::loader::LazyStaticLoader = #init;
+                LOADER.force().clone()
+            }
+        }
+    } else {
+        quote! {
+            #vis static #name : #CRATE_NAME::loader::LazyStaticLoader = #init;
+        }
+    };
+
+    let template_loader = template_loader.map(|template_loader| {
+        quote! {
+            #vis static #template_loader: std::sync::LazyLock<
+                #CRATE_NAME::FluentLoader<&'static #CRATE_NAME::StaticLoader>,
+            > = std::sync::LazyLock::new(|| {
+                #CRATE_NAME::FluentLoader::new(&*#name)
+                    .with_default_lang(#CRATE_NAME::langid!(#fallback_language_value))
+            });
+        }
+    });
+
+    let quote = quote! {
+        // Rebuilds the loader when the environment variables in its paths
+        // change.
+        #(const _: &str = env!(#env_vars);)*
+        #warnings
+        #keys_module
+        #loader
+        #template_loader
     };
 
     // println!("{}", quote);
@@ -0,0 +1,177 @@
+//! Conveniences for passing arguments to Fluent messages.
+//!
+//! The `*_with_args` methods on [`LoaderExt`] accept anything that can be
+//! iterated as `(name, value)` pairs, such as an array of tuples, a
+//! `HashMap`, or the [`FluentArgs`] produced by [`fluent_args!`]. The
+//! methods on [`DynLoader`] take a `FluentArgs` directly.
+//!
+//! ```
+//! use fluent_templates::{fluent_args, static_loader, DynLoader, LoaderExt};
+//! use unic_langid::langid;
+//!
+//! static_loader! {
+//!     static LOCALES = {
+//!         locales: "./tests/locales",
+//!         fallback_language: "en-US",
+//!         customise: |bundle| bundle.set_use_isolating(false),
+//!     };
+//! }
+//!
+//! let lang = langid!("en-US");
+//! assert_eq!(
+//!     "Hello Alice!",
+//!     LOCALES.lookup_with_args(&lang, "greeting", [("name", "Alice")])
+//! );
+//! assert_eq!(
+//!     "text one 1 second 2",
+//!     LOCALES.lookup_with_args(
+//!         &lang,
+//!         "parameter2",
+//!         fluent_args!["param" => 1, "multi-word-param" => 2]
+//!     )
+//! );
+//!
+//! let args = fluent_args!["name" => "Bob"];
+//! assert_eq!("Hello Bob!", LOCALES.lookup_complete(&lang, "greeting", Some(&args)));
+//! ```
+//!
+//! [`DynLoader`]: crate::DynLoader
+//! [`LoaderExt`]: crate::LoaderExt
+//! [`FluentArgs`]: fluent_bundle::FluentArgs
+//! [`fluent_args!`]: crate::fluent_args
+
+use std::borrow::Cow;
+
+use fluent_bundle::types::FluentNumber;
+use fluent_bundle::{FluentArgs, FluentValue};
+
+/// Creates [`FluentArgs`] from `name => value` pairs, suitable for passing to
+/// any of the [`DynLoader`](crate::DynLoader) lookup methods.
+///
+/// `fluent-bundle` 0.15 doesn't provide this macro itself, so it is defined
+/// here instead. Names can be anything convertible into a `Cow<str>`, and
+/// values anything convertible into a [`FluentValue`].
+///
+/// ```
+/// let args = fluent_templates::fluent_args!["name" => "Alice", "count" => 3];
+/// assert_eq!(2, args.iter().count());
+/// ```
+#[macro_export]
+macro_rules! fluent_args {
+    ($($name:expr => $value:expr),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut args = $crate::fluent_bundle::FluentArgs::new();
+        $(
+            args.set($name, $crate::fluent_bundle::FluentValue::from($value));
+        )*
+        args
+    }};
+}
+
+/// The name of the argument that [`LoaderExt::lookup_count`] passes the count
+/// as, i.e. `$count` in Fluent.
+///
+/// [`LoaderExt::lookup_count`]: crate::LoaderExt::lookup_count
+pub const COUNT: &str = "count";
+
+/// A type that can be used as the name of a Fluent argument.
+pub trait IntoArgName<'v> {
+    /// Converts `self` into an argument name.
+    fn into_arg_name(self) -> Cow<'v, str>;
+}
+
+impl<'v> IntoArgName<'v> for &'v str {
+    fn into_arg_name(self) -> Cow<'v, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl<'v> IntoArgName<'v> for String {
+    fn into_arg_name(self) -> Cow<'v, str> {
+        Cow::Owned(self)
+    }
+}
+
+impl<'v> IntoArgName<'v> for &'v String {
+    fn into_arg_name(self) -> Cow<'v, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl<'v> IntoArgName<'v> for Cow<'v, str> {
+    fn into_arg_name(self) -> Cow<'v, str> {
+        self
+    }
+}
+
+impl<'v> IntoArgName<'v> for &'v Cow<'_, str> {
+    fn into_arg_name(self) -> Cow<'v, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+/// A type that can be used as the value of a Fluent argument.
+///
+/// This is implemented for every type that `FluentValue` can be created
+/// from, as well as for `&FluentValue` so that existing argument maps can be
+/// passed by reference.
+pub trait IntoArgValue<'v> {
+    /// Converts `self` into an argument value.
+    fn into_arg_value(self) -> FluentValue<'v>;
+}
+
+impl<'v> IntoArgValue<'v> for FluentValue<'v> {
+    fn into_arg_value(self) -> FluentValue<'v> {
+        self
+    }
+}
+
+impl<'v> IntoArgValue<'v> for &FluentValue<'v> {
+    fn into_arg_value(self) -> FluentValue<'v> {
+        self.clone()
+    }
+}
+
+macro_rules! into_arg_value {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl<'v> IntoArgValue<'v> for $ty {
+                fn into_arg_value(self) -> FluentValue<'v> {
+                    FluentValue::from(self)
+                }
+            }
+        )+
+    };
+}
+
+into_arg_value!(
+    &'v str,
+    String,
+    &'v String,
+    Cow<'v, str>,
+    FluentNumber,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    f32,
+    f64,
+);
+
+/// Collects `(name, value)` pairs into the [`FluentArgs`] used by
+/// [`DynLoader::lookup_complete`](crate::DynLoader::lookup_complete).
+pub fn collect<'v, K: IntoArgName<'v>, V: IntoArgValue<'v>>(
+    args: impl IntoIterator<Item = (K, V)>,
+) -> FluentArgs<'v> {
+    args.into_iter()
+        .map(|(name, value)| (name.into_arg_name(), value.into_arg_value()))
+        .collect()
+}
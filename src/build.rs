@@ -0,0 +1,46 @@
+//! Helpers for build scripts of crates that use [`static_loader!`].
+//!
+//! The macro embeds each resource with `include_str!`, so the compiler
+//! rebuilds the crate when one of them changes, but it can't see when a
+//! resource or locale is added or removed. Tracking the locales directories
+//! from a build script keeps the embedded resources consistent with the
+//! files on disk. Embedding the directories with `include_dir!` instead
+//! wouldn't help, as on stable it also embeds each file separately.
+//!
+//! ```no_run
+//! // build.rs
+//! fn main() {
+//!     fluent_templates::build::track_locales("./locales");
+//! }
+//! ```
+//!
+//! [`static_loader!`]: crate::static_loader
+
+use std::path::Path;
+
+/// Tells Cargo to rerun the build script, and so rebuild the crate, when
+/// anything under `locales` changes, including files and locales being added
+/// or removed. `locales` should be the same path as the `locales` or
+/// `core_locales` option of `static_loader!`, relative to `Cargo.toml`.
+pub fn track_locales(locales: impl AsRef<Path>) {
+    println!("{}", rerun_if_changed(locales.as_ref()));
+}
+
+/// Returns the instruction for Cargo to rerun the build script when `path`,
+/// or anything under it if it's a directory, changes.
+fn rerun_if_changed(path: &Path) -> String {
+    format!("cargo:rerun-if-changed={}", path.display())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_the_locales_directory() {
+        assert_eq!(
+            "cargo:rerun-if-changed=./locales",
+            rerun_if_changed(Path::new("./locales"))
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Reports on how completely each locale is translated.
+//!
+//! A [`CoverageReport`] compares the messages defined for every locale
+//! against a reference language, usually the fallback language, which makes
+//! it easy to fail CI when translations are missing.
+//!
+//! ```
+//! use fluent_templates::{coverage::CoverageReport, static_loader};
+//! use unic_langid::langid;
+//!
+//! static_loader! {
+//!     static LOCALES = {
+//!         locales: "./tests/locales",
+//!         fallback_language: "en-US",
+//!     };
+//! }
+//!
+//! let report = CoverageReport::from_loader(&*LOCALES, &langid!("en-US"));
+//! let french = &report.locales[&langid!("fr")];
+//! assert!(french.missing.contains("fallback"));
+//! assert!(!report.is_complete());
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use unic_langid::LanguageIdentifier;
+
+use crate::DynLoader;
+
+/// How completely the locales of a loader are translated, relative to a
+/// reference language.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    /// The language that every other locale is compared against.
+    pub reference: LanguageIdentifier,
+    /// The number of messages defined for the reference language.
+    pub total: usize,
+    /// The coverage of every locale other than the reference language.
+    pub locales: BTreeMap<LanguageIdentifier, LocaleCoverage>,
+}
+
+/// The coverage of a single locale in a [`CoverageReport`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LocaleCoverage {
+    /// Messages defined for the reference language but not this locale.
+    pub missing: BTreeSet<String>,
+    /// Messages defined for this locale but not the reference language.
+    pub extra: BTreeSet<String>,
+    /// The number of reference messages that this locale defines.
+    pub translated: usize,
+    /// The number of messages defined for the reference language.
+    pub total: usize,
+}
+
+impl LocaleCoverage {
+    /// Returns the percentage of reference messages that this locale
+    /// defines, `100.0` if the reference language has no messages.
+    pub fn percentage(&self) -> f64 {
+        if self.total == 0 {
+            100.0
+        } else {
+            self.translated as f64 / self.total as f64 * 100.0
+        }
+    }
+
+    /// Returns whether this locale defines every reference message.
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+impl CoverageReport {
+    /// Creates a report from the message IDs of each locale, comparing them
+    /// against those of `reference`.
+    pub fn new<'a, I, M>(reference: &LanguageIdentifier, locales: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a LanguageIdentifier, M)>,
+        M: IntoIterator<Item = &'a str>,
+    {
+        let locales = locales
+            .into_iter()
+            .map(|(lang, ids)| (lang, ids.into_iter().collect::<BTreeSet<_>>()))
+            .collect::<BTreeMap<_, _>>();
+        let reference_ids = locales.get(reference).cloned().unwrap_or_default();
+
+        Self {
+            reference: reference.clone(),
+            total: reference_ids.len(),
+            locales: locales
+                .into_iter()
+                .filter(|(lang, _)| *lang != reference)
+                .map(|(lang, ids)| {
+                    let missing = reference_ids
+                        .difference(&ids)
+                        .map(|id| id.to_string())
+                        .collect::<BTreeSet<_>>();
+                    let coverage = LocaleCoverage {
+                        extra: ids
+                            .difference(&reference_ids)
+                            .map(|id| id.to_string())
+                            .collect(),
+                        translated: reference_ids.len() - missing.len(),
+                        total: reference_ids.len(),
+                        missing,
+                    };
+                    (lang.clone(), coverage)
+                })
+                .collect(),
+        }
+    }
+
+    /// Creates a report from the messages of every locale in `loader`,
+    /// see [`DynLoader::message_ids`].
+    pub fn from_loader<L: DynLoader + ?Sized>(loader: &L, reference: &LanguageIdentifier) -> Self {
+        Self::new(
+            reference,
+            loader
+                .locales()
+                .map(|lang| (lang, loader.message_ids(lang))),
+        )
+    }
+
+    /// Returns whether every locale defines every reference message.
+    pub fn is_complete(&self) -> bool {
+        self.locales.values().all(LocaleCoverage::is_complete)
+    }
+}
+
+impl fmt::Display for CoverageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}: {} messages", self.reference, self.total)?;
+        for (lang, coverage) in &self.locales {
+            writeln!(
+                f,
+                "{lang}: {:.1}% ({}/{})",
+                coverage.percentage(),
+                coverage.translated,
+                coverage.total
+            )?;
+            for id in &coverage.missing {
+                writeln!(f, "  missing: {id}")?;
+            }
+            for id in &coverage.extra {
+                writeln!(f, "  extra: {id}")?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -26,6 +26,30 @@ pub enum LoaderError {
         /// The original bundle errors
         errors: Vec<fluent_bundle::FluentError>,
     },
+    /// An embedded resource couldn't be decompressed.
+    #[cfg(feature = "compress")]
+    #[error("Failed to decompress Fluent resource: {0}")]
+    Decompress(String),
+    /// The locales directory of a [`WatchingLoader`](crate::WatchingLoader)
+    /// couldn't be watched for changes.
+    #[cfg(feature = "watch")]
+    #[error("Failed to watch for changes: {0}")]
+    Watch(#[from] notify::Error),
+    /// A loader couldn't be rebuilt, with the message of the error that
+    /// the build returned.
+    #[error("Failed to build the loader: {0}")]
+    Build(String),
+}
+
+impl LoaderError {
+    /// Converts an error returned by a loader's build, keeping it if it's
+    /// already a `LoaderError`.
+    pub(crate) fn from_build(error: Box<dyn std::error::Error>) -> Self {
+        error
+            .downcast::<Self>()
+            .map(|error| *error)
+            .unwrap_or_else(|error| Self::Build(error.to_string()))
+    }
 }
 
 /// A wrapper struct around `Vec<fluent_syntax::parser::ParserError>`.
@@ -59,15 +83,76 @@ impl std::error::Error for FluentError {}
 /// An error that happened while looking up messages
 #[derive(Debug, thiserror::Error)]
 pub enum LookupError {
+    /// No message with the given ID was found.
     #[error("Couldn't retrieve message with ID `{0}`")]
     MessageRetrieval(String),
+    /// The message was found, but it has no attribute with the given name.
     #[error("Couldn't find attribute `{attribute}` for message-id `{message_id}`")]
     AttributeNotFound {
+        /// The ID of the message.
         message_id: String,
+        /// The name of the missing attribute.
         attribute: String,
     },
+    /// No resources have been loaded for the requested language.
     #[error("Language ID `{0}` has not been loaded")]
     LangNotLoaded(LanguageIdentifier),
+    /// The message was found, but formatting it produced errors.
     #[error("Fluent errors: {0:?}")]
     FluentError(Vec<fluent_bundle::FluentError>),
+    /// The message or attribute couldn't be found in any language of the
+    /// fallback chain.
+    #[error("{last_error} (tried {})", display_langs(.chain_tried))]
+    Exhausted {
+        /// The language that was asked for.
+        requested: LanguageIdentifier,
+        /// Every language that was searched, in order.
+        chain_tried: Vec<LanguageIdentifier>,
+        /// The most descriptive error from the languages that were tried.
+        last_error: Box<LookupError>,
+    },
+    /// None of the loaders of a [`MultiLoader`](crate::MultiLoader) could
+    /// look up the message, with why each loader that was searched failed.
+    #[error("No loader could look up the message: {}", display_failures(.0))]
+    AllLoadersFailed(Vec<LoaderFailure>),
+}
+
+/// Why a loader of a [`MultiLoader`](crate::MultiLoader) couldn't look up a
+/// message, in a [`LookupError::AllLoadersFailed`].
+#[derive(Debug)]
+pub struct LoaderFailure {
+    /// The index of the loader, or `None` if it's namespaced.
+    pub index: Option<usize>,
+    /// The name that the loader was tagged with, or its namespace.
+    pub name: Option<String>,
+    /// Why the loader couldn't look up the message.
+    pub error: LookupError,
+}
+
+impl fmt::Display for LoaderFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.index, &self.name) {
+            (Some(index), Some(name)) => write!(f, "loader {index} (`{name}`)")?,
+            (Some(index), None) => write!(f, "loader {index}")?,
+            (None, Some(name)) => write!(f, "namespace `{name}`")?,
+            (None, None) => write!(f, "loader")?,
+        }
+        write!(f, ": {}", self.error)
+    }
+}
+
+fn display_failures(failures: &[LoaderFailure]) -> String {
+    failures
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn display_langs(langs: &[LanguageIdentifier]) -> String {
+    langs
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
 }
@@ -1,5 +1,6 @@
 use std::fs;
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
 
 use fluent_bundle::FluentResource;
 pub use unic_langid::{langid, langids, LanguageIdentifier};
@@ -21,6 +22,17 @@ pub fn resource_from_str(src: &str) -> crate::Result<FluentResource> {
         .map_err(|(_, errs)| error::FluentError::from(errs).into())
 }
 
+/// Decompresses and parses a resource embedded by the `compress` option of
+/// the `static_loader!` macro.
+#[cfg(feature = "compress")]
+pub fn resource_from_compressed(src: &[u8]) -> crate::Result<FluentResource> {
+    let src = miniz_oxide::inflate::decompress_to_vec(src)
+        .map_err(|error| error::LoaderError::Decompress(error.to_string()))?;
+    let src = String::from_utf8(src)
+        .map_err(|error| error::LoaderError::Decompress(error.to_string()))?;
+    FluentResource::try_new(src).map_err(|(_, errs)| error::FluentError::from(errs).into())
+}
+
 pub fn resources_from_vec(srcs: &[String]) -> crate::Result<Vec<FluentResource>> {
     let mut vec = Vec::with_capacity(srcs.len());
 
@@ -31,56 +43,199 @@ pub fn resources_from_vec(srcs: &[String]) -> crate::Result<Vec<FluentResource>>
     Ok(vec)
 }
 
-pub(crate) fn read_from_dir<P: AsRef<Path>>(path: P) -> crate::Result<Vec<FluentResource>> {
+/// Returns whether the file name of `path` ends with one of `extensions`,
+/// which can have multiple parts, such as `ftl.txt`.
+pub(crate) fn has_extension(path: &Path, extensions: &[String]) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    extensions.iter().any(|extension| {
+        name.strip_suffix(extension.trim_start_matches('.'))
+            .and_then(|stem| stem.strip_suffix('.'))
+            .is_some_and(|stem| !stem.is_empty())
+    })
+}
+
+/// Which of the files under a directory are read as resources.
+pub(crate) struct FileFilter<'a> {
+    /// The extensions of resources, see [`has_extension`].
+    pub(crate) extensions: &'a [String],
+    /// Whether to read a file, given its path relative to the directory.
+    pub(crate) predicate: Option<&'a (dyn Fn(&Path) -> bool + Send + Sync)>,
+    /// How many levels of subdirectories to read, or `None` for all of them.
+    pub(crate) max_depth: Option<usize>,
+}
+
+impl<'a> FileFilter<'a> {
+    /// Reads every file with one of `extensions`.
+    pub(crate) fn new(extensions: &'a [String]) -> Self {
+        Self {
+            extensions,
+            predicate: None,
+            max_depth: None,
+        }
+    }
+
+    /// Returns whether the file at `path`, relative to the directory being
+    /// read, is a resource.
+    pub(crate) fn matches(&self, path: &Path) -> bool {
+        has_extension(path, self.extensions)
+            && self.predicate.map_or(true, |predicate| predicate(path))
+    }
+
+    /// The maximum depth of the walk, which counts the directory itself.
+    #[cfg(any(feature = "ignore", feature = "walkdir"))]
+    fn walk_depth(&self) -> Option<usize> {
+        self.max_depth.map(|depth| depth + 1)
+    }
+}
+
+/// Reads the files in `path` that `files` matches, ordered by their paths so
+/// that overriding between them is predictable. Files that can't be read are
+/// returned with their errors.
+pub(crate) fn read_sources_from_dir<P: AsRef<Path>>(
+    path: P,
+    files: &FileFilter<'_>,
+) -> Vec<(PathBuf, io::Result<String>)> {
+    let root = path.as_ref();
+
+    // Without either walker, e.g. for `wasm32-unknown-unknown`, only
+    // `std::fs` is used. Hidden files and directories are skipped.
     #[cfg(not(any(feature = "ignore", feature = "walkdir")))]
-    compile_error!("one of the features `ignore` or `walkdir` must be enabled.");
+    {
+        let mut paths = Vec::new();
+        let mut directories = vec![(root.to_owned(), 0)];
+        while let Some((directory, depth)) = directories.pop() {
+            let Ok(entries) = fs::read_dir(&directory) else {
+                continue;
+            };
+            for entry in entries.filter_map(Result::ok) {
+                let Ok(file_type) = entry.file_type() else {
+                    continue;
+                };
+                let entry_path = entry.path();
+                if entry.file_name().to_string_lossy().starts_with('.') {
+                    continue;
+                } else if file_type.is_dir() {
+                    if files.max_depth.map_or(true, |max_depth| depth < max_depth) {
+                        directories.push((entry_path, depth + 1));
+                    }
+                } else if file_type.is_file()
+                    && files.matches(entry_path.strip_prefix(root).unwrap_or(&entry_path))
+                {
+                    paths.push(entry_path);
+                }
+            }
+        }
+
+        paths.sort();
+        #[allow(clippy::needless_return)]
+        return paths
+            .into_iter()
+            .map(|path| {
+                let src = fs::read_to_string(&path);
+                (path, src)
+            })
+            .collect();
+    }
 
     #[cfg(feature = "ignore")]
     {
         let (tx, rx) = flume::unbounded();
 
-        ignore::WalkBuilder::new(path).build_parallel().run(|| {
-            let tx = tx.clone();
-            Box::new(move |result| {
-                if let Ok(entry) = result {
-                    if entry
-                        .file_type()
-                        .as_ref()
-                        .map_or(false, fs::FileType::is_file)
-                        && entry.path().extension().map_or(false, |e| e == "ftl")
-                    {
-                        if let Ok(string) = std::fs::read_to_string(entry.path()) {
-                            let _ = tx.send(string);
-                        } else {
-                            log::warn!("Couldn't read {}", entry.path().display());
+        ignore::WalkBuilder::new(root)
+            .max_depth(files.walk_depth())
+            .build_parallel()
+            .run(|| {
+                let tx = tx.clone();
+                Box::new(move |result| {
+                    if let Ok(entry) = result {
+                        if entry
+                            .file_type()
+                            .as_ref()
+                            .is_some_and(fs::FileType::is_file)
+                            && files
+                                .matches(entry.path().strip_prefix(root).unwrap_or(entry.path()))
+                        {
+                            let src = fs::read_to_string(entry.path());
+                            let _ = tx.send((entry.into_path(), src));
                         }
                     }
-                }
 
-                ignore::WalkState::Continue
-            })
-        });
+                    ignore::WalkState::Continue
+                })
+            });
 
-        return resources_from_vec(&rx.drain().collect::<Vec<_>>());
+        let mut srcs = rx.drain().collect::<Vec<_>>();
+        srcs.sort_by(|(a, _), (b, _)| a.cmp(b));
+        #[allow(clippy::needless_return)]
+        return srcs;
     }
 
     #[cfg(all(not(feature = "ignore"), feature = "walkdir"))]
     {
-        let mut srcs = Vec::new();
-        walkdir::WalkDir::new(path)
+        let mut walk = walkdir::WalkDir::new(root).sort_by_file_name();
+        if let Some(depth) = files.walk_depth() {
+            walk = walk.max_depth(depth);
+        }
+        #[allow(clippy::needless_return)]
+        return walk
             .into_iter()
             .filter_map(|e| e.ok())
             .filter(|e| e.file_type().is_file())
-            .filter(|e| e.path().extension().map_or(false, |e| e == "ftl"))
-            .for_each(|e| {
-                if let Ok(string) = std::fs::read_to_string(e.path()) {
-                    srcs.push(string);
-                } else {
-                    log::warn!("Couldn't read {}", e.path().display());
+            .filter(|e| files.matches(e.path().strip_prefix(root).unwrap_or(e.path())))
+            .map(|e| {
+                let src = fs::read_to_string(e.path());
+                (e.into_path(), src)
+            })
+            .collect();
+    }
+}
+
+/// Like [`read_sources_from_dir`], but reads the files without blocking the
+/// async runtime. Hidden files and directories are skipped, but ignore files
+/// aren't respected.
+#[cfg(feature = "tokio")]
+pub(crate) async fn read_sources_from_dir_async(
+    path: &Path,
+    files: &FileFilter<'_>,
+) -> crate::Result<Vec<(PathBuf, io::Result<String>)>> {
+    let fs_error = |path: &Path| {
+        let path = path.to_owned();
+        move |source| error::LoaderError::Fs { path, source }
+    };
+
+    let mut paths = Vec::new();
+    let mut directories = vec![(path.to_owned(), 0)];
+    while let Some((directory, depth)) = directories.pop() {
+        let mut entries = tokio::fs::read_dir(&directory)
+            .await
+            .map_err(fs_error(&directory))?;
+        while let Some(entry) = entries.next_entry().await.map_err(fs_error(&directory))? {
+            let entry_path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let file_type = entry.file_type().await.map_err(fs_error(&entry_path))?;
+            if file_type.is_dir() {
+                if files.max_depth.map_or(true, |max_depth| depth < max_depth) {
+                    directories.push((entry_path, depth + 1));
                 }
-            });
-        return resources_from_vec(&srcs);
+            } else if file_type.is_file()
+                && files.matches(entry_path.strip_prefix(path).unwrap_or(&entry_path))
+            {
+                paths.push(entry_path);
+            }
+        }
     }
+
+    paths.sort();
+    let mut srcs = Vec::with_capacity(paths.len());
+    for path in paths {
+        let src = tokio::fs::read_to_string(&path).await;
+        srcs.push((path, src));
+    }
+    Ok(srcs)
 }
 
 #[cfg(test)]
@@ -95,9 +250,12 @@ mod tests {
         std::fs::write(dir.path().join("core.ftl"), "foo = bar\n".as_bytes())?;
         std::fs::write(dir.path().join("other.ftl"), "bar = baz\n".as_bytes())?;
         std::fs::write(dir.path().join("invalid.txt"), "baz = foo\n".as_bytes())?;
-        std::fs::write(dir.path().join(".binary_file.swp"), &[0, 1, 2, 3, 4, 5])?;
+        std::fs::write(dir.path().join(".binary_file.swp"), [0, 1, 2, 3, 4, 5])?;
 
-        let result = read_from_dir(dir.path())?;
+        let result = read_sources_from_dir(dir.path(), &FileFilter::new(&["ftl".to_owned()]))
+            .into_iter()
+            .map(|(_, src)| Ok(resource_from_str(&src?)?))
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
         assert_eq!(2, result.len()); // Doesn't include the binary file or the txt file
 
         let mut bundle = FluentBundle::new_concurrent(vec![unic_langid::langid!("en-US")]);
@@ -129,4 +287,19 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_load_from_dir_with_extensions() -> Result<(), Box<dyn Error>> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("core.ftl"), "foo = bar\n".as_bytes())?;
+        std::fs::write(dir.path().join("other.flt"), "bar = baz\n".as_bytes())?;
+        std::fs::write(dir.path().join("export.ftl.txt"), "baz = foo\n".as_bytes())?;
+        std::fs::write(dir.path().join(".ftl.txt"), "qux = foo\n".as_bytes())?;
+
+        let extensions = ["flt".to_owned(), ".ftl.txt".to_owned()];
+        let result = read_sources_from_dir(dir.path(), &FileFilter::new(&extensions));
+        assert_eq!(2, result.len()); // Only `other.flt` and `export.ftl.txt`
+
+        Ok(())
+    }
 }
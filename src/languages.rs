@@ -68,6 +68,66 @@ fn into_specificity(lang: &LanguageIdentifier) -> usize {
     specificity
 }
 
+/// How a loader matches a requested language against the languages it has
+/// resources for.
+///
+/// ```
+/// use fluent_templates::{ArcLoader, DynLoader, NegotiationStrategy};
+/// use unic_langid::langid;
+///
+/// let loader = ArcLoader::builder("./tests/locales", langid!("en-US"))
+///     .negotiation_strategy(NegotiationStrategy::Filtering)
+///     .build()
+///     .unwrap();
+/// assert_eq!(
+///     vec![langid!("zh-CN"), langid!("zh"), langid!("zh-TW"), langid!("en-US")],
+///     loader.fallback_chain(&langid!("zh-CN"))
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NegotiationStrategy {
+    /// Only the requested language and the more general languages it falls
+    /// back to, from the most specific, e.g. `de-DE` and `de` for `de-DE`.
+    /// Languages of other regions or scripts are never used, so a `zh-CN`
+    /// request never gets `zh-TW` resources.
+    #[default]
+    Specificity,
+    /// The single best match for each requested language, as
+    /// `fluent_langneg`'s `Matching` strategy. Messages that it doesn't
+    /// have are taken from the fallback language rather than a more general
+    /// language.
+    Matching,
+    /// Only the single best match of the first requested language that has
+    /// one, as `fluent_langneg`'s `Lookup` strategy.
+    Lookup,
+    /// Every language that matches, as `fluent_langneg`'s `Filtering`
+    /// strategy, including those of other regions, so a `zh-CN` request can
+    /// get `zh-TW` resources.
+    Filtering,
+}
+
+impl NegotiationStrategy {
+    /// Returns the languages of `available` that match `requested`, in order
+    /// of preference.
+    pub(crate) fn negotiate<
+        'a,
+        R: 'a + AsRef<LanguageIdentifier>,
+        A: 'a + AsRef<LanguageIdentifier> + PartialEq,
+    >(
+        self,
+        requested: &[R],
+        available: &'a [A],
+    ) -> Vec<&'a A> {
+        let strategy = match self {
+            Self::Specificity => return filter_matches(requested, available),
+            Self::Matching => fluent_langneg::NegotiationStrategy::Matching,
+            Self::Lookup => fluent_langneg::NegotiationStrategy::Lookup,
+            Self::Filtering => fluent_langneg::NegotiationStrategy::Filtering,
+        };
+        fluent_langneg::negotiate_languages(requested, available, None, strategy)
+    }
+}
+
 pub fn negotiate_languages<
     'a,
     R: 'a + AsRef<LanguageIdentifier>,
@@ -3,10 +3,10 @@
 //! `fluent-templates` lets you to easily integrate Fluent localisation into
 //! your Rust application or library. It does this by providing a high level
 //! "loader" API that loads fluent strings based on simple language negotiation,
-//! and the `FluentLoader` struct which is a `Loader` agnostic container type
+//! and the `FluentLoader` struct which is a `DynLoader` agnostic container type
 //! that comes with optional trait implementations for popular templating
-//! engines such as handlebars or tera that allow you to be able to use your
-//! localisations in your templates with no boilerplate.
+//! engines such as handlebars, tera or minijinja that allow you to be able to
+//! use your localisations in your templates with no boilerplate.
 //!
 //! ## Loaders
 //! Currently this crate provides two different kinds of loaders that cover two
@@ -62,7 +62,8 @@
 //!         fallback_language: "en-US",
 //!         // Optional: A fluent resource that is shared with every locale.
 //!         core_locales: "./tests/locales/core.ftl",
-//!         // Optional: A function that is run over each fluent bundle.
+//!         // Optional: A function that is run over each fluent bundle. It
+//!         // can also take the bundle's language: `|bundle, lang| { .. }`.
 //!         customise: |bundle| {
 //!             // Since this will be called for each locale bundle and
 //!             // `FluentResource`s need to be either `&'static` or behind an
@@ -77,7 +78,10 @@
 //!             });
 //!
 //!             bundle.add_resource(&CRATE_VERSION_FTL);
-//!         }
+//!         },
+//!         // Optional: What to return when a message can't be found, see
+//!         // `OnMissing`. Defaults to `"Unknown localization {text_id}"`.
+//!         on_missing: fluent_templates::OnMissing::DebugPanic,
 //!     };
 //! }
 //! # fn main() {}
@@ -106,10 +110,17 @@
 //!     └── main.ftl
 //! ```
 //!
+//! A regional locale inherits the resources of its language, so a `de-AT`
+//! directory only needs the messages and terms that differ from `de`. Messages
+//! from `de` that reference a term use the `de-AT` version of it.
+//!
 //! ### Looking up fluent resources
-//! You can use the [`Loader`] trait to `lookup` a given fluent resource, and
-//! provide any additional arguments as needed with `lookup_with_args`. You
-//! can also look up attributes by appending a `.` to the name of the message.
+//! You can use the [`DynLoader`] trait to `lookup` a given fluent resource, and
+//! provide any additional arguments as needed with `lookup_complete`, which
+//! accepts the [`FluentArgs`] created by [`fluent_args!`]. The [`LoaderExt`]
+//! trait adds `lookup_with_args`, which accepts any iterator of
+//! `(name, value)` pairs. You can also look up attributes by appending a `.`
+//! to the name of the message.
 //!
 //! #### Example
 //! ```fluent
@@ -130,10 +141,8 @@
 //! ```
 //!
 //! ```
-//! use std::{borrow::Cow, collections::HashMap};
-//!
 //! use unic_langid::{LanguageIdentifier, langid};
-//! use fluent_templates::{Loader, static_loader};
+//! use fluent_templates::{fluent_args, DynLoader, LoaderExt, static_loader};
 //!
 //!const US_ENGLISH: LanguageIdentifier = langid!("en-US");
 //!const FRENCH: LanguageIdentifier = langid!("fr");
@@ -158,42 +167,87 @@
 //!     assert_eq!("Bonjour le monde!", LOCALES.try_lookup(&FRENCH, "hello-world").unwrap());
 //!     assert_eq!("Hallo Welt!", LOCALES.try_lookup(&GERMAN, "hello-world").unwrap());
 //!
-//!     let args = {
-//!         let mut map = HashMap::new();
-//!         map.insert(Cow::from("name"), "Alice".into());
-//!         map
-//!     };
+//!     let args = [("name", "Alice")];
 //!
 //!     assert_eq!("Hello Friend!", LOCALES.lookup(&US_ENGLISH, "greeting.placeholder"));
-//!     assert_eq!("Hello Alice!", LOCALES.lookup_with_args(&US_ENGLISH, "greeting", &args));
+//!     assert_eq!("Hello Alice!", LOCALES.lookup_with_args(&US_ENGLISH, "greeting", args));
 //!     assert_eq!("Salut l'ami!", LOCALES.lookup(&FRENCH, "greeting.placeholder"));
-//!     assert_eq!("Bonjour Alice!", LOCALES.lookup_with_args(&FRENCH, "greeting", &args));
+//!     assert_eq!("Bonjour Alice!", LOCALES.lookup_with_args(&FRENCH, "greeting", args));
 //!     assert_eq!("Hallo Fruend!", LOCALES.lookup(&GERMAN, "greeting.placeholder"));
-//!     assert_eq!("Hallo Alice!", LOCALES.lookup_with_args(&GERMAN, "greeting", &args));
+//!     assert_eq!("Hallo Alice!", LOCALES.lookup_with_args(&GERMAN, "greeting", args));
 //!
 //!     assert_eq!("Hello Friend!", LOCALES.try_lookup(&US_ENGLISH, "greeting.placeholder").unwrap());
-//!     assert_eq!("Hello Alice!", LOCALES.try_lookup_with_args(&US_ENGLISH, "greeting", &args).unwrap());
+//!     assert_eq!("Hello Alice!", LOCALES.try_lookup_with_args(&US_ENGLISH, "greeting", args).unwrap());
 //!     assert_eq!("Salut l'ami!", LOCALES.try_lookup(&FRENCH, "greeting.placeholder").unwrap());
-//!     assert_eq!("Bonjour Alice!", LOCALES.try_lookup_with_args(&FRENCH, "greeting", &args).unwrap());
+//!     assert_eq!("Bonjour Alice!", LOCALES.try_lookup_with_args(&FRENCH, "greeting", args).unwrap());
 //!     assert_eq!("Hallo Fruend!", LOCALES.try_lookup(&GERMAN, "greeting.placeholder").unwrap());
-//!     assert_eq!("Hallo Alice!", LOCALES.try_lookup_with_args(&GERMAN, "greeting", &args).unwrap());
+//!     assert_eq!("Hallo Alice!", LOCALES.try_lookup_with_args(&GERMAN, "greeting", args).unwrap());
 //!
 //!
-//!     let args = {
-//!         let mut map = HashMap::new();
-//!         map.insert(Cow::Borrowed("param"), "1".into());
-//!         map.insert(Cow::Owned(format!("{}-param", "multi-word")), "2".into());
-//!         map
-//!     };
+//!     let args = fluent_args![
+//!         "param" => "1",
+//!         format!("{}-param", "multi-word") => "2",
+//!     ];
 //!
-//!     assert_eq!("text one 1 second 2", LOCALES.lookup_with_args(&US_ENGLISH, "parameter2", &args));
-//!     assert_eq!("texte une 1 seconde 2", LOCALES.lookup_with_args(&FRENCH, "parameter2", &args));
+//!     assert_eq!("text one 1 second 2", LOCALES.lookup_complete(&US_ENGLISH, "parameter2", Some(&args)));
+//!     assert_eq!("texte une 1 seconde 2", LOCALES.lookup_complete(&FRENCH, "parameter2", Some(&args)));
 //!
-//!     assert_eq!("text one 1 second 2", LOCALES.try_lookup_with_args(&US_ENGLISH, "parameter2", &args).unwrap());
-//!     assert_eq!("texte une 1 seconde 2", LOCALES.try_lookup_with_args(&FRENCH, "parameter2", &args).unwrap());
+//!     assert_eq!("text one 1 second 2", LOCALES.try_lookup_complete(&US_ENGLISH, "parameter2", Some(&args)).unwrap());
+//!     assert_eq!("texte une 1 seconde 2", LOCALES.try_lookup_complete(&FRENCH, "parameter2", Some(&args)).unwrap());
 //! }
 //! ```
 //!
+//! ### Recompiling when locales change
+//! Changes to the embedded resources rebuild your crate, but new resources
+//! and locales aren't picked up until something else triggers a rebuild.
+//! Calling [`build::track_locales`] from a build script for each locales
+//! directory keeps the loader consistent with the files on disk.
+//!
+//! ### Reloading
+//! [`ReloadableLoader`] rebuilds an [`ArcLoader`] in place when its `reload` method is
+//! called, e.g. on `SIGHUP`, without handing a new loader to its users.
+//! With the `watch` feature, `WatchingLoader` rebuilds an [`ArcLoader`] in
+//! the background whenever a file under its locales directory changes, so
+//! translations can be edited without restarting the application.
+//! Both have an `on_update` method for registering a callback that receives the
+//! locales that changed, e.g. to invalidate caches of rendered templates.
+//!
+//! ### Async
+//! With the `tokio` feature, `ArcLoaderBuilder::build_async` reads the
+//! resources without blocking the async runtime.
+//!
+//! ### Remote locales
+//! With the `remote` feature, `RemoteLoader` downloads the resources listed
+//! by a JSON manifest over HTTP(S), and its `refresh` method downloads any
+//! that have changed, using their `ETag`s, and rebuilds the loader. Lookups
+//! through the `RemoteLoader` use the latest version.
+//!
+//! ### Embedded locales
+//! With the `include_dir` or `rust-embed` features,
+//! `ArcLoaderBuilder::from_include_dir` and `ArcLoaderBuilder::from_embed`
+//! build a runtime loader from a locales directory embedded by those crates,
+//! without reading the filesystem.
+//!
+//! ### WebAssembly
+//! `ArcLoader` can be used on `wasm32-unknown-unknown` with
+//! `default-features = false`, which reads locales directories with `std::fs`
+//! instead of the `ignore` or `walkdir` crates. As there's no filesystem in the
+//! browser, build the loader with `ArcLoaderBuilder::from_files` from files
+//! fetched over HTTP, or from embedded locales or in-memory sources.
+//!
+//! ### Tracing
+//! With the `tracing` feature, lookups emit [`tracing`] events with the
+//! `fluent_templates` target: a `warn` event when a message fails to format,
+//! and `debug` events when a message is only found in a fallback language or
+//! isn't found at all.
+//!
+//! [`tracing`]: https://docs.rs/tracing
+//!
+//! ### Compression
+//! With the `compress` feature, `static_loader!` accepts `compress: true`,
+//! which embeds the FTL files deflate-compressed to reduce binary size. The
+//! resources are decompressed and parsed the first time the loader is used.
+//!
 //! ### Tera
 //! With the `tera` feature you can use `FluentLoader` as a Tera function.
 //! It accepts a `key` parameter pointing to a fluent resource and `lang` for
@@ -201,8 +255,12 @@
 //! to the function as arguments to the resource. `fluent-templates` will
 //! automatically convert argument keys from Tera's `snake_case` to the fluent's
 //! preferred `kebab-case` arguments.
+//! An attribute of the message can be looked up with an `attr` parameter, e.g.
+//! `{{ fluent(key="login-input", attr="placeholder") }}`.
 //! The `lang` parameter is optional when the default language of the corresponding
-//! `FluentLoader` is set (see [`FluentLoader::with_default_lang`]).
+//! `FluentLoader` is set (see [`FluentLoader::with_default_lang`]), or when the
+//! template is rendered with `FluentLoader::render_tera`, which reads the language
+//! from the `lang` variable of the context.
 //!
 //! ```toml
 //!fluent-templates = { version = "*", features = ["tera"] }
@@ -238,9 +296,53 @@
 //! # }
 //! ```
 //!
+//! ### MiniJinja
+//! With the `minijinja` feature, `FluentLoader::add_to_environment` registers a
+//! `fluent` function and filter, a `translated` test, and `fluent_dir` and
+//! `fluent_locales` functions with a MiniJinja `Environment`. The language is
+//! read from the `lang` argument, or the `lang` variable of the context, or the
+//! default language of the `FluentLoader`. Other keyword arguments are passed to
+//! the message, converted from `snake_case` to `kebab-case`, and an attribute can
+//! be looked up with an `attr` argument. The names can be changed with
+//! `FluentLoader::add_to_environment_with_names`.
+//!
+//! ```toml
+//!fluent-templates = { version = "*", features = ["minijinja"] }
+//!```
+//!
+//! ```rust
+//! use fluent_templates::{FluentLoader, static_loader};
+//!
+//! static_loader! {
+//!     static LOCALES = {
+//!         locales: "./tests/locales",
+//!         fallback_language: "en-US",
+//!         // Removes unicode isolating marks around arguments, you typically
+//!         // should only set to false when testing.
+//!         customise: |bundle| bundle.set_use_isolating(false),
+//!     };
+//! }
+//!
+//! fn main() {
+//! # #[cfg(feature = "minijinja")] {
+//!     let mut env = minijinja::Environment::new();
+//!     FluentLoader::new(&*LOCALES).add_to_environment(&mut env);
+//!     let ctx = minijinja::context! { lang => "en-US" };
+//!     assert_eq!("Hello World!", env.render_str(r#"{{ fluent("hello-world") }}"#, &ctx).unwrap());
+//!     assert_eq!(
+//!         "Hello Alice!",
+//!         env.render_str(r#"{% if "greeting" is translated %}{{ "greeting" | fluent(name="Alice") }}{% endif %}"#, &ctx).unwrap()
+//!     );
+//! # }
+//! }
+//! ```
+//!
 //! ### Handlebars
 //! In handlebars, `fluent-templates` will read the `lang` field in your
-//! [`handlebars::Context`] while rendering.
+//! [`handlebars::Context`] while rendering, unless the `{{fluent}}` helper is
+//! given a `lang` parameter, e.g. `{{fluent "hello-world" lang="fr"}}`. The
+//! default language of the `FluentLoader` is used when the context has no
+//! `lang` field (see [`FluentLoader::with_default_lang`]).
 //!
 //! ```toml
 //!fluent-templates = { version = "*", features = ["handlebars"] }
@@ -270,6 +372,11 @@
 //! }
 //! ```
 //!
+//! All of the helpers of a `FluentLoader` can be registered at once with
+//! `FluentLoader::register_handlebars`, which adds `fluent`, as well as
+//! `fluent-locales`, listing the available locales, and `fluent-dir`, the
+//! text direction of the current language.
+//!
 //! ### Handlebars helper syntax.
 //! The main helper provided is the `{{fluent}}` helper. If you have the
 //! following Fluent file:
@@ -289,6 +396,19 @@
 //! {{fluent "placeholder" variable="baz"}}
 //!```
 //!
+//! An attribute of a message can be included with the `attr` parameter, which
+//! isn't passed to the message as a variable.
+//!
+//! ```fluent
+//! login-input = Email
+//!     .placeholder = email@example.com
+//! ```
+//!
+//! ```hbs
+//! <!-- will render "email@example.com" -->
+//! {{fluent "login-input" attr="placeholder"}}
+//! ```
+//!
 //! You may also use the `{{fluentparam}}` helper to specify [variables],
 //! especially if you need them to be multiline.
 //!
@@ -305,14 +425,31 @@
 //! {{/fluent}}
 //! ```
 //!
+//! Content after an `{{else}}` in the `{{#fluent}}` block is rendered instead
+//! of the message when it can't be found in any language.
+//!
+//! ```hbs
+//! {{#fluent "summer-sale"}}{{else}}<a href="/shop">Shop now</a>{{/fluent}}
+//! ```
+//!
+//! The content of `{{fluentparam}}` is passed as a string, unless it's given
+//! `type="number"`, so that it can select a plural category.
+//!
+//! ```hbs
+//! {{#fluent "emails"}}{{#fluentparam "count" type="number"}}{{inbox.length}}{{/fluentparam}}{{/fluent}}
+//! ```
+//!
 //!
 //! [variables]: https://projectfluent.org/fluent/guide/variables.html
 //! [`static_loader!`]: ./macro.static_loader.html
+//! [`fluent_args!`]: ./macro.fluent_args.html
+//! [`FluentArgs`]: fluent_bundle::FluentArgs
 //! [`StaticLoader`]: ./struct.StaticLoader.html
 //! [`ArcLoader`]: ./struct.ArcLoader.html
 //! [`FluentLoader::with_default_lang`]: ./struct.FluentLoader.html#method.with_default_lang
 //! [`handlebars::Context`]: https://docs.rs/handlebars/3.1.0/handlebars/struct.Context.html
 #![warn(missing_docs)]
+#![allow(clippy::needless_doctest_main)]
 
 #[doc(hidden)]
 pub extern crate fluent_bundle;
@@ -321,15 +458,38 @@ pub extern crate fluent_bundle;
 pub type FluentBundle<R> =
     fluent_bundle::bundle::FluentBundle<R, intl_memoizer::concurrent::IntlLangMemoizer>;
 
-pub use error::LoaderError;
-pub use loader::{ArcLoader, ArcLoaderBuilder, FluentLoader, Loader, MultiLoader, StaticLoader};
+pub use error::{LoaderError, LoaderFailure, LookupError};
+pub use languages::NegotiationStrategy;
+pub use loader::{
+    ArcLoader, ArcLoaderBuilder, AvailableLocales, BuildReport, Duplicates, DynLoader,
+    FluentLoader, LanguageBoundLoader, LazyStaticLoader, LoaderExt, LoaderStats, LocaleMerge,
+    LocaleStats, LocalizedMessage, MapLoader, MissingKey, MissingMessageHandler, MultiLoader,
+    MultiLoaderBuilder, OnMissing, OnParseError, OverlayLoader, PseudoLoader, ReloadableLoader,
+    ResourceSource, SkippedResource, StaticLoader, TextDirection, TracedMessage,
+    UsageTrackingLoader,
+};
+
+#[cfg(feature = "minijinja")]
+pub use loader::MinijinjaNames;
+#[cfg(feature = "watch")]
+pub use loader::WatchingLoader;
+#[cfg(feature = "remote")]
+pub use loader::{RemoteLoader, RemoteLoaderBuilder};
 
+/// The previous name of [`DynLoader`], kept so that existing imports keep
+/// working. The generic lookup methods are now provided by [`LoaderExt`].
+pub use loader::DynLoader as Loader;
+
+pub mod args;
+pub mod build;
+pub mod coverage;
 mod error;
 #[doc(hidden)]
 pub mod fs;
 mod languages;
 #[doc(hidden)]
 pub mod loader;
+pub mod validation;
 
 #[cfg(feature = "macros")]
 pub use fluent_template_macros::static_loader;
@@ -343,7 +503,7 @@ pub type Result<T, E = error::LoaderError> = std::result::Result<T, E>;
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Loader;
+    use crate::{DynLoader, LoaderExt};
     use unic_langid::{langid, LanguageIdentifier};
 
     #[test]
@@ -355,7 +515,35 @@ mod tests {
             .build()
             .unwrap();
 
-        let loader: Box<dyn Loader> = Box::new(loader);
+        let loader: Box<dyn DynLoader> = Box::new(loader);
         assert_eq!("Hello World!", loader.lookup(&US_ENGLISH, "hello-world"));
+        assert_eq!(
+            "Hello Alice!",
+            loader.lookup_with_args(&US_ENGLISH, "greeting", [("name", "Alice")])
+        );
+    }
+
+    #[test]
+    fn trait_objects_implement_loader() {
+        const US_ENGLISH: LanguageIdentifier = langid!("en-US");
+
+        fn greet(loader: impl DynLoader) -> String {
+            loader.lookup_with_args(&US_ENGLISH, "greeting", [("name", "Alice")])
+        }
+
+        let build = || {
+            ArcLoader::builder("./tests/locales", US_ENGLISH)
+                .customize(|bundle| bundle.set_use_isolating(false))
+                .build()
+                .unwrap()
+        };
+
+        let boxed: Box<dyn DynLoader> = Box::new(build());
+        let shared: std::sync::Arc<dyn DynLoader + Send + Sync> = std::sync::Arc::new(build());
+        assert_eq!("Hello Alice!", greet(&boxed));
+        assert_eq!("Hello Alice!", greet(shared.clone()));
+
+        let multi = MultiLoader::from_iter([Box::new(boxed) as Box<dyn DynLoader>]);
+        assert_eq!("Hello World!", multi.lookup(&US_ENGLISH, "hello-world"));
     }
 }
@@ -1,5 +1,5 @@
 //! This modules contains both the `static_loader` and `ArcLoader`
-//! implementations, as well as the `Loader` trait. Which provides a loader
+//! implementations, as well as the `DynLoader` trait. Which provides a loader
 //! agnostic interface.
 
 #[cfg(feature = "handlebars")]
@@ -8,48 +8,82 @@ mod handlebars;
 #[cfg(feature = "tera")]
 mod tera;
 
+#[cfg(feature = "minijinja")]
+mod minijinja;
+
+#[cfg(feature = "remote")]
+mod remote;
+#[cfg(feature = "watch")]
+mod watch;
+
+mod language_bound;
+mod map_loader;
+mod missing;
 mod multi_loader;
+mod overlay_loader;
+mod pseudo_loader;
+mod reloadable;
 mod shared;
+mod source;
+mod stats;
+mod usage_tracking;
 
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, OnceLock};
 
-use crate::FluentBundle;
+use crate::args::{IntoArgName, IntoArgValue};
+use crate::{error::LookupError, FluentBundle};
+use fluent_bundle::types::FluentNumber;
 use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
 use fluent_langneg::negotiate_languages;
+use unic_langid::CharacterDirection;
 
 pub use unic_langid::{langid, langids, LanguageIdentifier};
 
 mod arc_loader;
 mod static_loader;
 
-pub use arc_loader::{ArcLoader, ArcLoaderBuilder};
-pub use multi_loader::MultiLoader;
-pub use static_loader::StaticLoader;
+pub use arc_loader::{ArcLoader, ArcLoaderBuilder, BuildReport, OnParseError, SkippedResource};
+pub use language_bound::LanguageBoundLoader;
+pub use map_loader::MapLoader;
+#[cfg(feature = "minijinja")]
+pub use minijinja::MinijinjaNames;
+pub use missing::{MissingMessageHandler, OnMissing};
+pub use multi_loader::{LocaleMerge, MultiLoader, MultiLoaderBuilder, TracedMessage};
+pub use overlay_loader::OverlayLoader;
+pub use pseudo_loader::PseudoLoader;
+pub use reloadable::ReloadableLoader;
+#[cfg(feature = "remote")]
+pub use remote::{RemoteLoader, RemoteLoaderBuilder};
+pub use source::ResourceSource;
+pub use static_loader::{LazyStaticLoader, StaticLoader};
+pub use stats::{LoaderStats, LocaleStats};
+pub use usage_tracking::UsageTrackingLoader;
+#[cfg(feature = "watch")]
+pub use watch::WatchingLoader;
+
+pub(crate) use shared::References;
 
 /// A loader capable of looking up Fluent keys given a language.
-pub trait Loader {
+///
+/// This trait is object safe, so loaders can be used as `dyn DynLoader`.
+/// The generic conveniences, such as [`LoaderExt::lookup_with_args`], are
+/// provided for every loader, including trait objects, by [`LoaderExt`].
+pub trait DynLoader {
     /// Look up `text_id` for `lang` in Fluent.
     fn lookup(&self, lang: &LanguageIdentifier, text_id: &str) -> String {
         self.lookup_complete(lang, text_id, None)
     }
 
-    /// Look up `text_id` for `lang` with `args` in Fluent.
-    fn lookup_with_args(
-        &self,
-        lang: &LanguageIdentifier,
-        text_id: &str,
-        args: &HashMap<Cow<'static, str>, FluentValue>,
-    ) -> String {
-        self.lookup_complete(lang, text_id, Some(args))
-    }
-
     /// Look up `text_id` for `lang` in Fluent, using any `args` if provided.
     fn lookup_complete(
         &self,
         lang: &LanguageIdentifier,
         text_id: &str,
-        args: Option<&HashMap<Cow<'static, str>, FluentValue>>,
+        args: Option<&FluentArgs>,
     ) -> String;
 
     /// Look up `text_id` for `lang` in Fluent.
@@ -57,88 +91,590 @@ pub trait Loader {
         self.try_lookup_complete(lang, text_id, None)
     }
 
-    /// Look up `text_id` for `lang` with `args` in Fluent.
-    fn try_lookup_with_args(
+    /// Look up `text_id` for `lang` in Fluent, using any `args` if provided.
+    fn try_lookup_complete(
         &self,
         lang: &LanguageIdentifier,
         text_id: &str,
-        args: &HashMap<Cow<'static, str>, FluentValue>,
-    ) -> Option<String> {
-        self.try_lookup_complete(lang, text_id, Some(args))
+        args: Option<&FluentArgs>,
+    ) -> Option<String>;
+
+    /// Look up `text_id` for `lang` in Fluent, returning an error describing
+    /// why the lookup failed instead of a placeholder string.
+    fn lookup_result(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+    ) -> Result<String, LookupError> {
+        self.lookup_complete_result(lang, text_id, None)
     }
 
-    /// Look up `text_id` for `lang` in Fluent, using any `args` if provided.
-    fn try_lookup_complete(
+    /// Look up `text_id` for `lang` in Fluent, using any `args` if provided,
+    /// returning an error describing why the lookup failed.
+    ///
+    /// The default implementation can only report
+    /// [`LookupError::MessageRetrieval`], loaders should override it to
+    /// distinguish missing languages and formatting errors.
+    fn lookup_complete_result(
         &self,
         lang: &LanguageIdentifier,
         text_id: &str,
-        args: Option<&HashMap<Cow<'static, str>, FluentValue>>,
-    ) -> Option<String>;
+        args: Option<&FluentArgs>,
+    ) -> Result<String, LookupError> {
+        self.try_lookup_complete(lang, text_id, args)
+            .ok_or_else(|| LookupError::MessageRetrieval(text_id.to_owned()))
+    }
 
-    /// Returns an Iterator over the locales that are present.
-    fn locales(&self) -> Box<dyn Iterator<Item = &LanguageIdentifier> + '_>;
-}
+    /// Look up `text_id` for `lang` in Fluent, using any `args` if provided.
+    ///
+    /// Unlike [`DynLoader::lookup_complete`] this allows loaders to return the
+    /// message text borrowed from their resources, avoiding an allocation
+    /// for messages without any placeables.
+    fn lookup_cow(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Cow<'_, str> {
+        Cow::Owned(self.lookup_complete(lang, text_id, args))
+    }
 
-impl<L> Loader for std::sync::Arc<L>
-where
-    L: Loader,
-{
-    fn lookup_complete(
+    /// Look up `text_id` for `lang` in Fluent, using any `args` if provided,
+    /// borrowing the message text where possible. See [`DynLoader::lookup_cow`].
+    fn try_lookup_cow(
         &self,
         lang: &LanguageIdentifier,
         text_id: &str,
-        args: Option<&HashMap<Cow<'static, str>, FluentValue>>,
+        args: Option<&FluentArgs>,
+    ) -> Option<Cow<'_, str>> {
+        self.try_lookup_complete(lang, text_id, args)
+            .map(Cow::Owned)
+    }
+
+    /// Look up `text_id` in Fluent for the most preferred of `langs` that
+    /// contains it, using any `args` if provided.
+    ///
+    /// Unlike calling [`DynLoader::lookup_complete`] for each language, every
+    /// requested language is tried before the fallback language.
+    fn lookup_langs(
+        &self,
+        langs: &[LanguageIdentifier],
+        text_id: &str,
+        args: Option<&FluentArgs>,
     ) -> String {
-        L::lookup_complete(self, lang, text_id, args)
+        self.try_lookup_langs(langs, text_id, args)
+            .unwrap_or_else(|| format!("Unknown localization {text_id}"))
     }
 
-    fn try_lookup_complete(
+    /// Look up `text_id` in Fluent for the most preferred of `langs` that
+    /// contains it, using any `args` if provided.
+    /// See [`DynLoader::lookup_langs`].
+    fn try_lookup_langs(
+        &self,
+        langs: &[LanguageIdentifier],
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        let available = self.locales().collect::<Vec<_>>();
+        langs
+            .iter()
+            .flat_map(|lang| crate::languages::negotiate_languages(&[lang], &available, None))
+            .find_map(|lang| self.try_lookup_complete(lang, text_id, args))
+            .or_else(|| self.try_lookup_complete(langs.first()?, text_id, args))
+    }
+
+    /// Look up `text_id` for `lang` in Fluent, using any `args` if provided,
+    /// without falling back to the loader's default fallback language.
+    ///
+    /// Only `lang` and the more general languages it negotiates to are
+    /// searched, e.g. `zh` for `zh-TW`. The default implementation returns
+    /// `None` if `lang` doesn't negotiate to any of [`DynLoader::locales`], and
+    /// otherwise uses [`DynLoader::try_lookup_complete`], so loaders with a
+    /// fallback language should override it.
+    fn lookup_no_default_fallback(
         &self,
         lang: &LanguageIdentifier,
         text_id: &str,
-        args: Option<&HashMap<Cow<'static, str>, FluentValue>>,
+        args: Option<&FluentArgs>,
     ) -> Option<String> {
-        L::try_lookup_complete(self, lang, text_id, args)
+        let available = self.locales().collect::<Vec<_>>();
+        if crate::languages::negotiate_languages(&[lang], &available, None).is_empty() {
+            return None;
+        }
+        self.try_lookup_complete(lang, text_id, args)
     }
 
-    fn locales(&self) -> Box<dyn Iterator<Item = &LanguageIdentifier> + '_> {
-        L::locales(self)
+    /// Look up `text_id` for `lang` in Fluent, using any `args` if provided,
+    /// and write the result into `out`.
+    ///
+    /// Loaders can format the message directly into `out`, avoiding an
+    /// intermediate `String` for each message. As the message is streamed,
+    /// formatting errors are written inline rather than falling back to the
+    /// next language.
+    fn lookup_into(
+        &self,
+        out: &mut dyn fmt::Write,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> fmt::Result {
+        out.write_str(&self.lookup_cow(lang, text_id, args))
+    }
+
+    /// Returns whether `text_id` can be found for `lang`, following the same
+    /// fallback chain as the lookup methods, without formatting the message.
+    ///
+    /// Attributes can be checked by appending a `.` to the message name.
+    ///
+    /// The default implementation can only find the message by formatting it
+    /// with [`DynLoader::lookup_result`], so loaders should override it to
+    /// check their resources directly, as the loaders of this crate do.
+    fn has(&self, lang: &LanguageIdentifier, text_id: &str) -> bool {
+        matches!(
+            self.lookup_result(lang, text_id),
+            Ok(_) | Err(LookupError::FluentError(_))
+        )
+    }
+
+    /// Returns the names of the attributes of the message `text_id` for
+    /// `lang`, following the same fallback chain as the lookup methods.
+    ///
+    /// Each attribute can be looked up by appending `.` and its name to
+    /// `text_id`. The default implementation returns no attributes.
+    fn attributes(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+        let _ = (lang, text_id);
+        Vec::new()
+    }
+
+    /// Look up the message `text_id` for `lang`, formatting its value and
+    /// every one of its attributes with any `args` provided.
+    ///
+    /// The value and attributes are all taken from the same language, the
+    /// first in the fallback chain that contains the message. Returns `None`
+    /// if no language contains the message.
+    fn lookup_message(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<LocalizedMessage> {
+        let attrs = self
+            .attributes(lang, text_id)
+            .into_iter()
+            .filter_map(|attr| {
+                let value = self.try_lookup_complete(lang, &format!("{text_id}.{attr}"), args)?;
+                Some((attr, value))
+            })
+            .collect::<HashMap<_, _>>();
+        let value = self.try_lookup_complete(lang, text_id, args);
+
+        if value.is_none() && attrs.is_empty() {
+            return None;
+        }
+
+        Some(LocalizedMessage {
+            value: value.unwrap_or_default(),
+            attrs,
+        })
+    }
+
+    /// Returns the languages that are searched, in order, when looking up a
+    /// message for `lang`.
+    ///
+    /// The default implementation negotiates `lang` against
+    /// [`DynLoader::locales`], loaders with a fallback language append it to the
+    /// end of the chain.
+    fn fallback_chain(&self, lang: &LanguageIdentifier) -> Vec<LanguageIdentifier> {
+        let available = self.locales().collect::<Vec<_>>();
+        crate::languages::negotiate_languages(&[lang], &available, None)
+            .into_iter()
+            .map(|lang| (*lang).clone())
+            .collect()
+    }
+
+    /// Returns whether the language that `lang` negotiates to, the first
+    /// entry of [`DynLoader::fallback_chain`], is written right-to-left.
+    fn is_rtl(&self, lang: &LanguageIdentifier) -> bool {
+        let chain = self.fallback_chain(lang);
+        chain.first().unwrap_or(lang).character_direction() == CharacterDirection::RTL
+    }
+
+    /// Returns the value of the HTML `dir` attribute for the language that
+    /// `lang` negotiates to, either `"rtl"` or `"ltr"`.
+    fn dir(&self, lang: &LanguageIdentifier) -> &'static str {
+        if self.is_rtl(lang) {
+            "rtl"
+        } else {
+            "ltr"
+        }
+    }
+
+    /// Returns an Iterator over the locales that are present.
+    fn locales(&self) -> Box<dyn Iterator<Item = &LanguageIdentifier> + '_>;
+
+    /// Returns the locales that are present, sorted and without duplicates,
+    /// so that the order is the same between runs, e.g. for language
+    /// pickers.
+    fn locales_sorted(&self) -> Vec<LanguageIdentifier> {
+        let mut locales = self.locales().cloned().collect::<Vec<_>>();
+        locales.sort();
+        locales.dedup();
+        locales
+    }
+
+    /// Returns an Iterator over the IDs of the messages defined for exactly
+    /// `lang`, including any shared resources. Fallback languages are not
+    /// consulted.
+    ///
+    /// The default implementation returns no IDs.
+    fn message_ids(&self, lang: &LanguageIdentifier) -> Box<dyn Iterator<Item = &str> + '_> {
+        let _ = lang;
+        Box::new(std::iter::empty())
+    }
+
+    /// Returns the names of the `$variables` referenced by `text_id` for
+    /// `lang`, following the same fallback chain as the lookup methods, in
+    /// the order they first appear. Variables used by messages that
+    /// `text_id` references are included.
+    ///
+    /// This can be used to check that every argument is passed before
+    /// formatting. The default implementation returns no variables.
+    fn required_args(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+        let _ = (lang, text_id);
+        Vec::new()
+    }
+
+    /// Returns an Iterator over the IDs of the terms defined for exactly
+    /// `lang`, such as `brand-name` for `-brand-name`, including any shared
+    /// resources. Fallback languages are not consulted.
+    ///
+    /// The default implementation returns no IDs.
+    fn term_ids(&self, lang: &LanguageIdentifier) -> Box<dyn Iterator<Item = &str> + '_> {
+        let _ = lang;
+        Box::new(std::iter::empty())
+    }
+
+    /// Formats the term `term_id` for exactly `lang`, using any `args` if
+    /// provided. The leading `-` of `term_id` is optional.
+    ///
+    /// Terms can only be referenced from other messages in Fluent, so this
+    /// is only available in debug builds, for previewing their values.
+    /// The default implementation returns `None`.
+    #[cfg(debug_assertions)]
+    fn format_term(
+        &self,
+        lang: &LanguageIdentifier,
+        term_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        let _ = (lang, term_id, args);
+        None
+    }
+
+    /// Returns the sorted IDs of the messages defined in any locale.
+    fn all_message_ids(&self) -> BTreeSet<&str> {
+        self.locales()
+            .flat_map(|lang| self.message_ids(lang))
+            .collect()
+    }
+
+    /// Returns the number of resources and messages of each locale, e.g. for
+    /// logging what has been loaded.
+    ///
+    /// The default implementation counts the IDs from
+    /// [`DynLoader::message_ids`] and reports no resources.
+    fn stats(&self) -> LoaderStats {
+        LoaderStats::from_loader(self, |_| 0)
     }
 }
 
-impl<'a, L> Loader for &'a L
-where
-    L: Loader,
-{
-    fn lookup_complete(
+/// Generic conveniences for every [`DynLoader`], including trait objects.
+///
+/// This is implemented automatically and can't be implemented by hand.
+pub trait LoaderExt: DynLoader {
+    /// Look up `text_id` for `lang` with `args` in Fluent.
+    ///
+    /// `args` can be any iterator of `(name, value)` pairs, see the
+    /// [`args`](crate::args) module.
+    fn lookup_with_args<'v, K: IntoArgName<'v>, V: IntoArgValue<'v>>(
         &self,
         lang: &LanguageIdentifier,
         text_id: &str,
-        args: Option<&HashMap<Cow<'static, str>, FluentValue>>,
+        args: impl IntoIterator<Item = (K, V)>,
     ) -> String {
-        L::lookup_complete(self, lang, text_id, args)
+        self.lookup_complete(lang, text_id, Some(&crate::args::collect(args)))
     }
 
-    fn try_lookup_complete(
+    /// Look up `text_id` for `lang` with `args` in Fluent.
+    ///
+    /// `args` can be any iterator of `(name, value)` pairs, see the
+    /// [`args`](crate::args) module.
+    fn try_lookup_with_args<'v, K: IntoArgName<'v>, V: IntoArgValue<'v>>(
         &self,
         lang: &LanguageIdentifier,
         text_id: &str,
-        args: Option<&HashMap<Cow<'static, str>, FluentValue>>,
+        args: impl IntoIterator<Item = (K, V)>,
     ) -> Option<String> {
-        L::try_lookup_complete(self, lang, text_id, args)
+        self.try_lookup_complete(lang, text_id, Some(&crate::args::collect(args)))
+    }
+
+    /// Look up `text_id` for `lang` with `args` in Fluent, returning an error
+    /// describing why the lookup failed instead of a placeholder string.
+    ///
+    /// `args` can be any iterator of `(name, value)` pairs, see the
+    /// [`args`](crate::args) module.
+    fn lookup_with_args_result<'v, K: IntoArgName<'v>, V: IntoArgValue<'v>>(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<String, LookupError> {
+        self.lookup_complete_result(lang, text_id, Some(&crate::args::collect(args)))
+    }
+
+    /// Look up `text_id` for `lang` in Fluent, passing `count` as the
+    /// `$count` argument alongside any `extra_args` provided.
+    ///
+    /// This is a shorthand for messages that select a plural category based
+    /// on a count, such as:
+    ///
+    /// ```fluent
+    /// items = { $count ->
+    ///     [one] One item
+    ///    *[other] { $count } items
+    /// }
+    /// ```
+    fn lookup_count<N: Into<FluentNumber>>(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        count: N,
+        extra_args: Option<&FluentArgs>,
+    ) -> String {
+        let mut args = extra_args
+            .into_iter()
+            .flat_map(FluentArgs::iter)
+            .map(|(name, value)| (name, value.clone()))
+            .collect::<FluentArgs>();
+        args.set(crate::args::COUNT, FluentValue::Number(count.into()));
+        self.lookup_complete(lang, text_id, Some(&args))
+    }
+
+    /// Binds this loader to `lang`, for making many lookups in the same
+    /// language. See [`LanguageBoundLoader`].
+    fn for_lang(&self, lang: LanguageIdentifier) -> LanguageBoundLoader<'_, Self> {
+        LanguageBoundLoader::new(self, lang)
     }
 
-    fn locales(&self) -> Box<dyn Iterator<Item = &LanguageIdentifier> + '_> {
-        L::locales(self)
+    /// Wraps this loader so that every string it formats is passed through
+    /// `map`, e.g. to strip Unicode isolation marks. See [`MapLoader`].
+    fn map_output<F>(self, map: F) -> MapLoader<Self, F>
+    where
+        Self: Sized,
+        F: Fn(String) -> String,
+    {
+        MapLoader::new(self, map)
     }
 }
 
-/// A `Loader` agnostic container type with optional trait implementations
+impl<L: DynLoader + ?Sized> LoaderExt for L {}
+
+/// Implements `DynLoader` for a pointer type by forwarding every method to the
+/// loader it points to, or for a wrapper by forwarding to the `$target`
+/// returned by its `$get` method.
+macro_rules! forward_loader {
+    (@impl [$($param:ident)?] $ty:ty => $target:ty, $get:ident) => {
+        impl$(<$param: DynLoader + ?Sized>)? DynLoader for $ty {
+            fn lookup_complete(
+                &self,
+                lang: &LanguageIdentifier,
+                text_id: &str,
+                args: Option<&FluentArgs>,
+            ) -> String {
+                <$target as DynLoader>::lookup_complete(self.$get(), lang, text_id, args)
+            }
+
+            fn try_lookup_complete(
+                &self,
+                lang: &LanguageIdentifier,
+                text_id: &str,
+                args: Option<&FluentArgs>,
+            ) -> Option<String> {
+                <$target as DynLoader>::try_lookup_complete(self.$get(), lang, text_id, args)
+            }
+
+            fn lookup_cow(
+                &self,
+                lang: &LanguageIdentifier,
+                text_id: &str,
+                args: Option<&FluentArgs>,
+            ) -> Cow<'_, str> {
+                <$target as DynLoader>::lookup_cow(self.$get(), lang, text_id, args)
+            }
+
+            fn try_lookup_cow(
+                &self,
+                lang: &LanguageIdentifier,
+                text_id: &str,
+                args: Option<&FluentArgs>,
+            ) -> Option<Cow<'_, str>> {
+                <$target as DynLoader>::try_lookup_cow(self.$get(), lang, text_id, args)
+            }
+
+            fn lookup_complete_result(
+                &self,
+                lang: &LanguageIdentifier,
+                text_id: &str,
+                args: Option<&FluentArgs>,
+            ) -> Result<String, LookupError> {
+                <$target as DynLoader>::lookup_complete_result(self.$get(), lang, text_id, args)
+            }
+
+            fn lookup_langs(
+                &self,
+                langs: &[LanguageIdentifier],
+                text_id: &str,
+                args: Option<&FluentArgs>,
+            ) -> String {
+                <$target as DynLoader>::lookup_langs(self.$get(), langs, text_id, args)
+            }
+
+            fn lookup_no_default_fallback(
+                &self,
+                lang: &LanguageIdentifier,
+                text_id: &str,
+                args: Option<&FluentArgs>,
+            ) -> Option<String> {
+                <$target as DynLoader>::lookup_no_default_fallback(self.$get(), lang, text_id, args)
+            }
+
+            fn try_lookup_langs(
+                &self,
+                langs: &[LanguageIdentifier],
+                text_id: &str,
+                args: Option<&FluentArgs>,
+            ) -> Option<String> {
+                <$target as DynLoader>::try_lookup_langs(self.$get(), langs, text_id, args)
+            }
+
+            fn lookup_into(
+                &self,
+                out: &mut dyn fmt::Write,
+                lang: &LanguageIdentifier,
+                text_id: &str,
+                args: Option<&FluentArgs>,
+            ) -> fmt::Result {
+                <$target as DynLoader>::lookup_into(self.$get(), out, lang, text_id, args)
+            }
+
+            fn has(&self, lang: &LanguageIdentifier, text_id: &str) -> bool {
+                <$target as DynLoader>::has(self.$get(), lang, text_id)
+            }
+
+            fn attributes(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+                <$target as DynLoader>::attributes(self.$get(), lang, text_id)
+            }
+
+            fn lookup_message(
+                &self,
+                lang: &LanguageIdentifier,
+                text_id: &str,
+                args: Option<&FluentArgs>,
+            ) -> Option<LocalizedMessage> {
+                <$target as DynLoader>::lookup_message(self.$get(), lang, text_id, args)
+            }
+
+            fn fallback_chain(&self, lang: &LanguageIdentifier) -> Vec<LanguageIdentifier> {
+                <$target as DynLoader>::fallback_chain(self.$get(), lang)
+            }
+
+            fn locales(&self) -> Box<dyn Iterator<Item = &LanguageIdentifier> + '_> {
+                <$target as DynLoader>::locales(self.$get())
+            }
+
+            fn locales_sorted(&self) -> Vec<LanguageIdentifier> {
+                <$target as DynLoader>::locales_sorted(self.$get())
+            }
+
+            fn message_ids(&self, lang: &LanguageIdentifier) -> Box<dyn Iterator<Item = &str> + '_> {
+                <$target as DynLoader>::message_ids(self.$get(), lang)
+            }
+
+            fn required_args(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+                <$target as DynLoader>::required_args(self.$get(), lang, text_id)
+            }
+
+            fn term_ids(&self, lang: &LanguageIdentifier) -> Box<dyn Iterator<Item = &str> + '_> {
+                <$target as DynLoader>::term_ids(self.$get(), lang)
+            }
+
+            #[cfg(debug_assertions)]
+            fn format_term(
+                &self,
+                lang: &LanguageIdentifier,
+                term_id: &str,
+                args: Option<&FluentArgs>,
+            ) -> Option<String> {
+                <$target as DynLoader>::format_term(self.$get(), lang, term_id, args)
+            }
+
+            fn stats(&self) -> LoaderStats {
+                <$target as DynLoader>::stats(self.$get())
+            }
+        }
+    };
+    ($($ty:ty),+ $(,)?) => {$(
+        forward_loader!(@impl [L] $ty => L, deref);
+    )+};
+}
+
+forward_loader!(Arc<L>, Box<L>, &L);
+forward_loader!(@impl [] ReloadableLoader => reloadable::CurrentLoader, current_loader);
+#[cfg(feature = "watch")]
+forward_loader!(@impl [] WatchingLoader => reloadable::CurrentLoader, current_loader);
+#[cfg(feature = "remote")]
+forward_loader!(@impl [] RemoteLoader => reloadable::CurrentLoader, current_loader);
+
+/// A message formatted by [`DynLoader::lookup_message`], containing its value
+/// as well as all of its attributes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LocalizedMessage {
+    /// The formatted value of the message, empty if the message only has
+    /// attributes.
+    pub value: String,
+    /// The formatted attributes of the message, keyed by attribute name.
+    pub attrs: HashMap<String, String>,
+}
+
+/// A `DynLoader` agnostic container type with optional trait implementations
 /// for integrating with different libraries.
+#[derive(Clone, Debug)]
 pub struct FluentLoader<L> {
+    #[allow(unused)]
     loader: L,
     #[allow(unused)]
     default_lang: Option<LanguageIdentifier>,
+    #[allow(unused)]
+    lang_variable: String,
+    #[allow(unused)]
+    missing_key: MissingKey,
+    #[allow(unused)]
+    escape: Option<bool>,
+}
+
+/// What the template helpers of a [`FluentLoader`] render for a message that
+/// can't be found in any language.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MissingKey {
+    /// Render what the loader returns, see [`OnMissing`], e.g. in
+    /// production.
+    #[default]
+    Fallback,
+    /// Fail to render the template, e.g. in CI.
+    Error,
+    /// Render the message ID wrapped in markers, `[missing: text_id]`, e.g.
+    /// during development.
+    Mark,
 }
 
 impl<L> FluentLoader<L> {
@@ -147,18 +683,150 @@ impl<L> FluentLoader<L> {
         Self {
             loader,
             default_lang: None,
+            lang_variable: String::from("lang"),
+            missing_key: MissingKey::default(),
+            escape: None,
         }
     }
 
     /// Set default language for this `FluentLoader`.
-    /// Template engines can use this value when rendering translations.
-    /// So far this feature is only implemented for Tera.
+    /// Template engines use this value when rendering translations without
+    /// a language argument or a language in the context.
     pub fn with_default_lang(self, lang: LanguageIdentifier) -> Self {
         Self {
-            loader: self.loader,
             default_lang: Some(lang),
+            ..self
+        }
+    }
+
+    /// Sets the context variable that the language is read from, `lang` by
+    /// default. Handlebars helpers and MiniJinja functions always read it,
+    /// while Tera functions only read it in templates rendered with
+    /// [`FluentLoader::render_tera`](FluentLoader::render_tera).
+    pub fn with_lang_variable(self, name: impl Into<String>) -> Self {
+        Self {
+            lang_variable: name.into(),
+            ..self
+        }
+    }
+
+    /// Sets what the template helpers render for a message that can't be
+    /// found, see [`MissingKey`].
+    pub fn with_missing_key(self, missing_key: MissingKey) -> Self {
+        Self {
+            missing_key,
+            ..self
         }
     }
+
+    /// Sets whether the template helpers HTML-escape formatted messages, so
+    /// that markup in translations or arguments can't be injected into the
+    /// page. It can be overridden for a single call with an `escape`
+    /// argument, e.g. `escape=false` for a message that's known to contain
+    /// markup.
+    ///
+    /// None of the template helpers escape by default, so the autoescaping
+    /// of Tera and MiniJinja applies as it does to other functions, e.g. to
+    /// `.html` templates but not `.txt` ones. Tera and MiniJinja functions
+    /// created with this opt out of autoescaping so that messages aren't
+    /// escaped twice. Without it, an `escape=true` argument is meant for
+    /// templates that aren't autoescaped.
+    pub fn with_escape(self, escape: bool) -> Self {
+        Self {
+            escape: Some(escape),
+            ..self
+        }
+    }
+
+    /// Converts this into a template helper that outputs the HTML `dir`
+    /// value for the current language, see [`TextDirection`].
+    pub fn text_direction(self) -> TextDirection<L> {
+        TextDirection(self)
+    }
+
+    /// Converts this into a template helper that outputs the available
+    /// locales, see [`AvailableLocales`].
+    pub fn available_locales(self) -> AvailableLocales<L> {
+        AvailableLocales(self)
+    }
+}
+
+#[cfg(any(feature = "handlebars", feature = "tera", feature = "minijinja"))]
+impl<L: DynLoader> FluentLoader<L> {
+    /// Looks up `text_id` for a template helper, handling a missing message
+    /// as set by [`with_missing_key`](Self::with_missing_key).
+    fn lookup_for_template(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Result<Cow<'_, str>, LookupError> {
+        match self.missing_key {
+            MissingKey::Fallback => Ok(self.loader.lookup_cow(lang, text_id, args)),
+            missing_key => match self.loader.try_lookup_cow(lang, text_id, args) {
+                Some(value) => Ok(value),
+                None if missing_key == MissingKey::Mark => {
+                    Ok(Cow::Owned(format!("[missing: {text_id}]")))
+                }
+                None => Err(LookupError::MessageRetrieval(text_id.to_owned())),
+            },
+        }
+    }
+}
+
+/// A template helper that outputs the value of the HTML `dir` attribute,
+/// `"rtl"` or `"ltr"`, for a language. Created with
+/// [`FluentLoader::text_direction`].
+///
+/// With Handlebars the language is taken from the first parameter if given,
+/// otherwise from the language variable of the context, otherwise the default
+/// language, e.g. `<html dir="{{fluent-dir}}">` with the name used by
+/// [`FluentLoader::register_handlebars`]. With Tera the language is taken from
+/// the `lang` argument, otherwise from the context when rendering with
+/// [`FluentLoader::render_tera`], otherwise the default language, e.g.
+/// `<html dir="{{ dir(lang="ar") }}">` when registered as `dir`. With
+/// MiniJinja it's like Tera, except that the language variable of the context
+/// is always read, e.g. `<html dir="{{ fluent_dir() }}">` with the name used
+/// by [`FluentLoader::add_to_environment`].
+pub struct TextDirection<L>(FluentLoader<L>);
+
+/// A template helper that outputs the locales of the loader, e.g. for a
+/// language picker, sorted by language tag. Created with
+/// [`FluentLoader::available_locales`].
+///
+/// Each locale is an object with its `id`, e.g. `"en-US"`, its `name`, which
+/// is the `language-name` message of the locale if it has one, e.g.
+/// `language-name = Deutsch`, or otherwise its `id`, and `rtl`, whether it's
+/// written right-to-left.
+///
+/// With Handlebars it's used as a subexpression, e.g.
+/// `{{#each (fluent-locales)}}<option value="{{id}}">{{name}}</option>{{/each}}`
+/// with the name used by [`FluentLoader::register_handlebars`], and with Tera
+/// as a function, e.g.
+/// `{% for locale in locales() %}{{ locale.name }}{% endfor %}` when
+/// registered as `locales`, or with MiniJinja as `fluent_locales()` with the
+/// name used by [`FluentLoader::add_to_environment`].
+pub struct AvailableLocales<L>(FluentLoader<L>);
+
+#[cfg(any(feature = "handlebars", feature = "tera", feature = "minijinja"))]
+impl<L: DynLoader> AvailableLocales<L> {
+    /// The message that names a locale in its own language.
+    const NAME_ID: &'static str = "language-name";
+
+    fn json(&self) -> serde_json::Value {
+        let loader = &self.0.loader;
+        loader
+            .locales_sorted()
+            .into_iter()
+            .map(|lang| {
+                let id = lang.to_string();
+                let name = loader
+                    .lookup_no_default_fallback(&lang, Self::NAME_ID, None)
+                    .unwrap_or_else(|| id.clone());
+                serde_json::json!({ "id": id, "name": name, "rtl": loader.is_rtl(&lang) })
+            })
+            .collect()
+    }
 }
 
 /// Constructs a map of languages with a list of potential fallback languages.
@@ -185,34 +853,104 @@ pub fn build_fallbacks(
     map
 }
 
-/// Creates a new static `FluentBundle` for `lang` using `resources`. Optionally
-/// shared resources can be specified with `core_resource` and the bundle can
-/// be customized with `customizer`.
+/// What a loader does when a message or term is defined more than once for a
+/// locale, e.g. by two of its resources.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Duplicates {
+    /// Fail to build the loader.
+    #[default]
+    Error,
+    /// Use the definition from the resource that's added last. Shared
+    /// resources are added first, then those of any parent locale, and then
+    /// the locale's own resources in the order of their paths.
+    Override,
+}
+
+/// Returns the more general locales that `lang` inherits resources from and
+/// are keys of `locales`, from the most general, e.g. `de` for `de-AT`, or
+/// `zh` and `zh-Hant` for `zh-Hant-TW`.
+pub(crate) fn parent_locales<'a, V>(
+    lang: &LanguageIdentifier,
+    locales: &'a HashMap<LanguageIdentifier, V>,
+) -> Vec<&'a LanguageIdentifier> {
+    let mut parent = lang.clone();
+    let mut parents = Vec::new();
+    if parent.variants().len() > 0 {
+        parent.clear_variants();
+        parents.push(parent.clone());
+    }
+    if parent.region.take().is_some() {
+        parents.push(parent.clone());
+    }
+    if parent.script.take().is_some() {
+        parents.push(parent);
+    }
+    parents
+        .iter()
+        .rev()
+        .filter_map(|parent| locales.get_key_value(parent).map(|(lang, _)| lang))
+        .collect()
+}
+
+/// Returns the language that `lang` is an alias of in `aliases`, either
+/// exactly or by its language subtag, so `no-NO` resolves to `nb-NO` when
+/// `no` is an alias of `nb`, or `lang` itself if it isn't an alias.
+pub(crate) fn resolve_alias<'a>(
+    aliases: &HashMap<LanguageIdentifier, LanguageIdentifier>,
+    lang: &'a LanguageIdentifier,
+) -> Cow<'a, LanguageIdentifier> {
+    if let Some(to) = aliases.get(lang) {
+        return Cow::Owned(to.clone());
+    }
+    match aliases.get(&LanguageIdentifier::from_parts(
+        lang.language,
+        None,
+        None,
+        &[],
+    )) {
+        Some(to) => {
+            let mut resolved = lang.clone();
+            resolved.language = to.language;
+            Cow::Owned(resolved)
+        }
+        None => Cow::Borrowed(lang),
+    }
+}
+
+/// Creates a new static `FluentBundle` for `lang` using `resources`. Shared
+/// resources can be specified with `core_resources` and the bundle can be
+/// customized with `customizer`.
 fn create_bundle(
     lang: LanguageIdentifier,
-    resources: &'static [FluentResource],
-    core_resource: Option<&'static FluentResource>,
-    customizer: &impl Fn(&mut FluentBundle<&'static FluentResource>),
+    resources: impl IntoIterator<Item = &'static FluentResource>,
+    core_resources: &'static [FluentResource],
+    customizer: &(impl Fn(&mut FluentBundle<&'static FluentResource>, &LanguageIdentifier) + ?Sized),
 ) -> FluentBundle<&'static FluentResource> {
     let mut bundle: FluentBundle<&'static FluentResource> =
-        FluentBundle::new_concurrent(vec![lang]);
-    if let Some(core) = core_resource {
-        bundle
-            .add_resource(core)
-            .expect("Failed to add core resource to bundle");
-    }
-    for res in resources {
-        bundle
-            .add_resource(res)
-            .expect("Failed to add FTL resources to the bundle.");
+        FluentBundle::new_concurrent(vec![lang.clone()]);
+    // `static_loader!` rejects duplicates within a locales directory unless
+    // they're allowed, so any that are left override earlier definitions:
+    // later locales directories override earlier ones, and locale resources
+    // override those of their parent locales and the core resources.
+    for res in core_resources.iter().chain(resources) {
+        bundle.add_resource_overriding(res);
     }
 
-    customizer(&mut bundle);
+    customizer(&mut bundle, &lang);
     bundle
 }
 
 /// Maps from map of languages containing a list of resources to a map of
 /// languages containing a `FluentBundle` of those resources.
+///
+/// # Panics
+///
+/// Panics if a message or term is defined more than once for a language,
+/// including in `core_resource`.
+#[deprecated(
+    since = "0.14.0",
+    note = "`StaticLoader` builds its bundles lazily with `LazyBundles`"
+)]
 pub fn build_bundles(
     resources: &'static HashMap<LanguageIdentifier, Vec<FluentResource>>,
     core_resource: Option<&'static FluentResource>,
@@ -220,16 +958,115 @@ pub fn build_bundles(
 ) -> HashMap<LanguageIdentifier, FluentBundle<&'static FluentResource>> {
     let mut bundles = HashMap::new();
     for (k, v) in resources.iter() {
-        bundles.insert(
-            k.clone(),
-            create_bundle(k.clone(), v, core_resource, &customizer),
-        );
+        let mut bundle: FluentBundle<&'static FluentResource> =
+            FluentBundle::new_concurrent(vec![k.clone()]);
+        if let Some(core) = core_resource {
+            bundle
+                .add_resource(core)
+                .expect("Failed to add core resource to bundle");
+        }
+        for res in v {
+            bundle
+                .add_resource(res)
+                .expect("Failed to add FTL resources to the bundle.");
+        }
+
+        customizer(&mut bundle);
+        bundles.insert(k.clone(), bundle);
     }
     bundles
 }
 
-fn map_to_fluent_args<'map, T: AsRef<str>>(map: &'map HashMap<T, FluentValue>) -> FluentArgs<'map> {
-    map.iter()
-        .map(|(key, value)| (key.as_ref(), value.clone()))
-        .collect()
+type Customizer =
+    Arc<dyn Fn(&mut FluentBundle<&'static FluentResource>, &LanguageIdentifier) + Send + Sync>;
+
+/// The `FluentBundle`s of a `StaticLoader`, where each bundle is only
+/// created from its resources the first time its language is used.
+pub struct LazyBundles {
+    resources: &'static HashMap<LanguageIdentifier, Vec<FluentResource>>,
+    core_resources: &'static [FluentResource],
+    bundles: HashMap<LanguageIdentifier, OnceLock<FluentBundle<&'static FluentResource>>>,
+    customizer: Customizer,
+    strategy: crate::NegotiationStrategy,
+}
+
+impl LazyBundles {
+    /// Creates the bundles for each language in `resources`, which will be
+    /// built with `core_resources` and customized with `customizer`.
+    pub fn new(
+        resources: &'static HashMap<LanguageIdentifier, Vec<FluentResource>>,
+        core_resources: &'static [FluentResource],
+        customizer: impl Fn(&mut FluentBundle<&'static FluentResource>) + Send + Sync + 'static,
+    ) -> Self {
+        Self::with_lang(resources, core_resources, move |bundle, _| {
+            customizer(bundle)
+        })
+    }
+
+    /// Like [`LazyBundles::new`], but `customizer` is also given the
+    /// language of each bundle.
+    pub fn with_lang(
+        resources: &'static HashMap<LanguageIdentifier, Vec<FluentResource>>,
+        core_resources: &'static [FluentResource],
+        customizer: impl Fn(&mut FluentBundle<&'static FluentResource>, &LanguageIdentifier)
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            resources,
+            core_resources,
+            bundles: resources
+                .keys()
+                .map(|lang| (lang.clone(), OnceLock::new()))
+                .collect(),
+            customizer: Arc::new(customizer),
+            strategy: crate::NegotiationStrategy::default(),
+        }
+    }
+}
+
+/// Clones share the resources and customizer, but build their own bundles.
+impl Clone for LazyBundles {
+    fn clone(&self) -> Self {
+        Self {
+            resources: self.resources,
+            core_resources: self.core_resources,
+            bundles: self
+                .bundles
+                .keys()
+                .map(|lang| (lang.clone(), OnceLock::new()))
+                .collect(),
+            customizer: self.customizer.clone(),
+            strategy: self.strategy,
+        }
+    }
+}
+
+impl shared::Bundles for LazyBundles {
+    type Resource = &'static FluentResource;
+
+    fn get(&self, lang: &LanguageIdentifier) -> Option<&FluentBundle<&'static FluentResource>> {
+        let bundle = self.bundles.get(lang)?;
+        Some(bundle.get_or_init(|| {
+            let resources = parent_locales(lang, self.resources)
+                .into_iter()
+                .chain([lang])
+                .flat_map(|lang| &self.resources[lang]);
+            create_bundle(
+                lang.clone(),
+                resources,
+                self.core_resources,
+                &*self.customizer,
+            )
+        }))
+    }
+
+    fn langs(&self) -> impl Iterator<Item = &LanguageIdentifier> {
+        self.bundles.keys()
+    }
+
+    fn negotiate(&self, langs: &[LanguageIdentifier]) -> Vec<&LanguageIdentifier> {
+        shared::negotiate_chain(self, langs, self.strategy)
+    }
 }
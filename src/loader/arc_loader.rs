@@ -1,29 +1,345 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fmt;
 use std::fs::read_dir;
-use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
 
-use crate::languages::negotiate_languages;
-use crate::FluentBundle;
-use fluent_bundle::{FluentResource, FluentValue};
+use super::shared::{Bundles, NegotiatedBundles, Prefix};
+use super::{Duplicates, LoaderStats, LocalizedMessage, OnMissing, OverlayLoader, ResourceSource};
+use crate::coverage::CoverageReport;
+use crate::validation::ValidationReport;
+use crate::{FluentBundle, NegotiationStrategy};
+use fluent_bundle::{FluentArgs, FluentResource};
 
 use crate::error::{LoaderError, LookupError};
 
 pub use unic_langid::LanguageIdentifier;
 
-type Customize = Option<Box<dyn FnMut(&mut FluentBundle<Arc<FluentResource>>)>>;
+type Customize =
+    Option<Box<dyn FnMut(&mut FluentBundle<Arc<FluentResource>>, &LanguageIdentifier) + Send>>;
+type Resources = HashMap<LanguageIdentifier, Vec<Arc<FluentResource>>>;
+type FilterFiles = Box<dyn Fn(&Path) -> bool + Send + Sync>;
+type MapDirectory = Box<dyn Fn(&str) -> Option<LanguageIdentifier> + Send + Sync>;
+
+/// A resource that was added to an `ArcLoaderBuilder` directly.
+enum Source {
+    /// A Fluent source, which is parsed when the loader is built.
+    Text(String),
+    Parsed(Arc<FluentResource>),
+    /// A file, which is read and parsed when the loader is built.
+    File(PathBuf),
+}
 
 /// A builder pattern struct for constructing `ArcLoader`s.
 pub struct ArcLoaderBuilder<'a, 'b> {
-    location: &'a Path,
+    location: Option<&'a Path>,
+    /// Files embedded in the binary, by their paths relative to the locales
+    /// directory.
+    embedded: Vec<(PathBuf, Cow<'static, [u8]>)>,
+    source: Option<Box<dyn ResourceSource + 'a>>,
+    sources: Vec<(LanguageIdentifier, Source)>,
     fallback: LanguageIdentifier,
     shared: Option<&'b [PathBuf]>,
     customize: Customize,
+    on_missing: OnMissing,
+    extensions: Vec<String>,
+    filter: Option<FilterFiles>,
+    max_depth: Option<usize>,
+    prefix: String,
+    duplicates: Duplicates,
+    negotiation_strategy: NegotiationStrategy,
+    aliases: HashMap<String, LanguageIdentifier>,
+    language_aliases: HashMap<LanguageIdentifier, LanguageIdentifier>,
+    map_directory: Option<MapDirectory>,
+    on_parse_error: OnParseError,
 }
 
 impl<'a, 'b> ArcLoaderBuilder<'a, 'b> {
-    /// Adds Fluent resources that are shared across all localizations.
+    /// Creates a builder for a loader of the given Fluent sources, parsed
+    /// when it's built, instead of the resources in a locales directory.
+    /// ```
+    /// use std::collections::HashMap;
+    /// use fluent_templates::{ArcLoaderBuilder, DynLoader};
+    /// use unic_langid::langid;
+    ///
+    /// let sources = HashMap::from([(langid!("en-US"), vec!["hello = Hello!".to_owned()])]);
+    /// let loader = ArcLoaderBuilder::from_sources(sources, langid!("en-US"))
+    ///     .add_source(langid!("fr"), "hello = Bonjour !")
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!("Bonjour !", loader.lookup(&langid!("fr"), "hello"));
+    /// ```
+    pub fn from_sources(
+        sources: HashMap<LanguageIdentifier, Vec<String>>,
+        fallback: LanguageIdentifier,
+    ) -> Self {
+        let sources = sources
+            .into_iter()
+            .flat_map(|(lang, sources)| {
+                sources
+                    .into_iter()
+                    .map(move |src| (lang.clone(), Source::Text(src)))
+            })
+            .collect();
+        Self {
+            sources,
+            ..Self::new(fallback)
+        }
+    }
+
+    /// Creates a builder for a loader of resources that have already been
+    /// parsed, e.g. to validate them or because they're cached, instead of
+    /// the resources in a locales directory.
+    pub fn from_resources(
+        resources: impl IntoIterator<Item = (LanguageIdentifier, Vec<Arc<FluentResource>>)>,
+        fallback: LanguageIdentifier,
+    ) -> Self {
+        let sources = resources
+            .into_iter()
+            .flat_map(|(lang, resources)| {
+                resources
+                    .into_iter()
+                    .map(move |res| (lang.clone(), Source::Parsed(res)))
+            })
+            .collect();
+        Self {
+            sources,
+            ..Self::new(fallback)
+        }
+    }
+
+    /// Creates a builder for a loader of the resources in `source`, which are
+    /// loaded when it's built, see [`ResourceSource`].
+    pub fn from_resource_source(
+        source: impl ResourceSource + 'a,
+        fallback: LanguageIdentifier,
+    ) -> Self {
+        Self {
+            source: Some(Box::new(source)),
+            ..Self::new(fallback)
+        }
+    }
+
+    /// Creates a builder for a loader of the files in a locales directory,
+    /// given by their paths relative to it and their contents, instead of
+    /// reading it from the filesystem, e.g. on `wasm32-unknown-unknown`
+    /// where the files are fetched over HTTP.
+    /// ```
+    /// use fluent_templates::{ArcLoaderBuilder, DynLoader};
+    /// use unic_langid::langid;
+    ///
+    /// let files = [
+    ///     ("en-US/main.ftl", b"hello = Hello!".to_vec()),
+    ///     ("fr/main.ftl", b"hello = Bonjour !".to_vec()),
+    /// ];
+    /// let loader = ArcLoaderBuilder::from_files(files, langid!("en-US"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!("Bonjour !", loader.lookup(&langid!("fr"), "hello"));
+    /// ```
+    pub fn from_files(
+        files: impl IntoIterator<Item = (impl Into<PathBuf>, impl Into<Cow<'static, [u8]>>)>,
+        fallback: LanguageIdentifier,
+    ) -> Self {
+        let embedded = files
+            .into_iter()
+            .map(|(path, contents)| (path.into(), contents.into()))
+            .collect();
+        Self {
+            embedded,
+            ..Self::new(fallback)
+        }
+    }
+
+    /// Creates a builder for a loader of the locales directory embedded with
+    /// [`include_dir`], instead of reading it from the filesystem.
+    /// ```
+    /// use fluent_templates::{ArcLoaderBuilder, DynLoader};
+    /// use unic_langid::langid;
+    ///
+    /// static LOCALES: include_dir::Dir = include_dir::include_dir!("$CARGO_MANIFEST_DIR/tests/locales");
+    ///
+    /// let loader = ArcLoaderBuilder::from_include_dir(&LOCALES, langid!("en-US"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!("Bonjour le monde!", loader.lookup(&langid!("fr"), "hello-world"));
+    /// ```
+    ///
+    /// [`include_dir`]: https://docs.rs/include_dir
+    #[cfg(feature = "include_dir")]
+    pub fn from_include_dir(
+        dir: &'static include_dir::Dir<'static>,
+        fallback: LanguageIdentifier,
+    ) -> Self {
+        fn files(
+            dir: &'static include_dir::Dir<'static>,
+            out: &mut Vec<(PathBuf, Cow<'static, [u8]>)>,
+        ) {
+            for entry in dir.entries() {
+                match entry {
+                    include_dir::DirEntry::Dir(dir) => files(dir, out),
+                    include_dir::DirEntry::File(file) => {
+                        out.push((file.path().to_owned(), Cow::Borrowed(file.contents())))
+                    }
+                }
+            }
+        }
+
+        let mut embedded = Vec::new();
+        files(dir, &mut embedded);
+        Self {
+            embedded,
+            ..Self::new(fallback)
+        }
+    }
+
+    /// Creates a builder for a loader of the locales directory embedded with
+    /// [`rust-embed`], instead of reading it from the filesystem.
+    /// ```
+    /// use fluent_templates::{ArcLoaderBuilder, DynLoader};
+    /// use unic_langid::langid;
+    ///
+    /// #[derive(rust_embed::Embed)]
+    /// #[folder = "tests/locales"]
+    /// struct Locales;
+    ///
+    /// let loader = ArcLoaderBuilder::from_embed::<Locales>(langid!("en-US"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!("Bonjour le monde!", loader.lookup(&langid!("fr"), "hello-world"));
+    /// ```
+    ///
+    /// [`rust-embed`]: https://docs.rs/rust-embed
+    #[cfg(feature = "rust-embed")]
+    pub fn from_embed<E: rust_embed::RustEmbed>(fallback: LanguageIdentifier) -> Self {
+        let embedded = E::iter()
+            .filter_map(|path| {
+                let file = E::get(&path)?;
+                Some((PathBuf::from(path.as_ref()), file.data))
+            })
+            .collect();
+        Self {
+            embedded,
+            ..Self::new(fallback)
+        }
+    }
+
+    fn new(fallback: LanguageIdentifier) -> Self {
+        Self {
+            location: None,
+            embedded: Vec::new(),
+            source: None,
+            sources: Vec::new(),
+            fallback,
+            shared: None,
+            customize: None,
+            on_missing: OnMissing::default(),
+            extensions: vec!["ftl".to_owned()],
+            filter: None,
+            max_depth: None,
+            prefix: String::new(),
+            duplicates: Duplicates::default(),
+            negotiation_strategy: NegotiationStrategy::default(),
+            aliases: HashMap::new(),
+            language_aliases: HashMap::new(),
+            map_directory: None,
+            on_parse_error: OnParseError::default(),
+        }
+    }
+
+    /// Adds a Fluent source to `lang`, after any resources read from the
+    /// locales directory, e.g. for translations that are stored in a
+    /// database. The source is parsed when the loader is built.
+    pub fn add_source(mut self, lang: LanguageIdentifier, source: impl Into<String>) -> Self {
+        self.sources.push((lang, Source::Text(source.into())));
+        self
+    }
+
+    /// Adds resources that have already been parsed to `lang`, after any
+    /// resources read from the locales directory.
+    pub fn add_resources(
+        mut self,
+        lang: LanguageIdentifier,
+        resources: impl IntoIterator<Item = Arc<FluentResource>>,
+    ) -> Self {
+        self.sources.extend(
+            resources
+                .into_iter()
+                .map(|res| (lang.clone(), Source::Parsed(res))),
+        );
+        self
+    }
+
+    /// Adds the resource in the file at `path` to `lang`, after any resources
+    /// read from the locales directory, for files whose language can't be
+    /// found from their directory, e.g. `messages.fr.ftl`. The file is read
+    /// when the loader is built, which fails if it can't be read.
+    /// ```
+    /// use fluent_templates::{ArcLoader, DynLoader};
+    /// use unic_langid::langid;
+    ///
+    /// let loader = ArcLoader::builder("./tests/locales", langid!("en-US"))
+    ///     .add_file(langid!("ja"), "./tests/locales/fr/main.ftl")
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!("Bonjour le monde!", loader.lookup(&langid!("ja"), "hello-world"));
+    /// ```
+    pub fn add_file(mut self, lang: LanguageIdentifier, path: impl Into<PathBuf>) -> Self {
+        self.sources.push((lang, Source::File(path.into())));
+        self
+    }
+
+    /// Adds the resources in each file to its language, see
+    /// [`add_file`](Self::add_file).
+    pub fn with_files(
+        mut self,
+        files: impl IntoIterator<Item = (LanguageIdentifier, PathBuf)>,
+    ) -> Self {
+        self.sources.extend(
+            files
+                .into_iter()
+                .map(|(lang, path)| (lang, Source::File(path))),
+        );
+        self
+    }
+
+    /// Sets the file extensions of the Fluent resources to load, `ftl` by
+    /// default. Extensions can have multiple parts, such as `ftl.txt`.
+    pub fn extensions(mut self, extensions: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.extensions = extensions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets which of the files with one of the
+    /// [`extensions`](Self::extensions) are resources, given their paths
+    /// relative to their locale or shared resource directory, e.g. to
+    /// exclude a `drafts` directory.
+    /// ```
+    /// use fluent_templates::ArcLoader;
+    /// use unic_langid::langid;
+    ///
+    /// let loader = ArcLoader::builder("./tests/locales", langid!("en-US"))
+    ///     .filter_files(|path| !path.starts_with("drafts"))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn filter_files(mut self, filter: impl Fn(&Path) -> bool + Send + Sync + 'static) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Sets how many levels of subdirectories of each locale or shared
+    /// resource directory are read, where `0` only reads the files directly
+    /// in it. All of them are read by default.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Adds Fluent resources that are shared across all localizations. Each
+    /// path is either a resource, or a directory whose resources with one of
+    /// the [`extensions`](Self::extensions) are all shared.
     pub fn shared_resources(mut self, shared: Option<&'b [PathBuf]>) -> Self {
         self.shared = shared;
         self
@@ -32,65 +348,606 @@ impl<'a, 'b> ArcLoaderBuilder<'a, 'b> {
     /// Allows you to customise each `FluentBundle`.
     pub fn customize(
         mut self,
-        customize: impl FnMut(&mut FluentBundle<Arc<FluentResource>>) + 'static,
+        mut customize: impl FnMut(&mut FluentBundle<Arc<FluentResource>>) + Send + 'static,
+    ) -> Self {
+        self.customize = Some(Box::new(move |bundle, _| customize(bundle)));
+        self
+    }
+
+    /// Allows you to customise each `FluentBundle`, given the language it
+    /// is for, e.g. to add locale-specific functions.
+    pub fn customize_with_lang(
+        mut self,
+        customize: impl FnMut(&mut FluentBundle<Arc<FluentResource>>, &LanguageIdentifier)
+            + Send
+            + 'static,
     ) -> Self {
         self.customize = Some(Box::new(customize));
         self
     }
 
-    /// Constructs an `ArcLoader` from the settings provided.
-    pub fn build(mut self) -> Result<ArcLoader, Box<dyn std::error::Error>> {
+    /// Sets what the infallible lookup methods return when a message can't
+    /// be found, see [`OnMissing`].
+    pub fn on_missing(mut self, on_missing: OnMissing) -> Self {
+        self.on_missing = on_missing;
+        self
+    }
+
+    /// Sets the languages of locale directories whose names aren't language
+    /// identifiers, e.g. `("english", langid!("en-US"))`. Names that use `_`
+    /// or other casings, such as `en_US` or `zh_hans_cn`, are recognised
+    /// without an alias.
+    pub fn aliases(
+        mut self,
+        aliases: impl IntoIterator<Item = (impl Into<String>, LanguageIdentifier)>,
+    ) -> Self {
+        self.aliases = aliases
+            .into_iter()
+            .map(|(name, lang)| (name.into(), lang))
+            .collect();
+        self
+    }
+
+    /// Looks up `from` as `to`, e.g. the legacy `no`, `iw` and `in` as `nb`,
+    /// `he` and `id`, without a locale directory for each. Unlike
+    /// [`aliases`](Self::aliases), which names the languages of directories,
+    /// this applies when messages are looked up. An alias of a language
+    /// without a region or script also applies to its more specific forms,
+    /// so `no-NO` is looked up as `nb-NO`.
+    ///
+    /// ```
+    /// use fluent_templates::{ArcLoader, DynLoader};
+    /// use unic_langid::langid;
+    ///
+    /// let loader = ArcLoader::builder("./tests/locales", langid!("en-US"))
+    ///     .alias(langid!("no"), langid!("fr"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!("Bonjour le monde!", loader.lookup(&langid!("no-NO"), "hello-world"));
+    /// ```
+    pub fn alias(mut self, from: LanguageIdentifier, to: LanguageIdentifier) -> Self {
+        self.language_aliases.insert(from, to);
+        self
+    }
+
+    /// Sets how the names of locale directories that don't have an
+    /// [alias](Self::aliases) are mapped to languages, e.g. for a `french`
+    /// directory, instead of parsing them. Directories it returns `None` for
+    /// are skipped, with a warning.
+    /// ```
+    /// use fluent_templates::{ArcLoader, DynLoader};
+    /// use unic_langid::{langid, LanguageIdentifier};
+    ///
+    /// let loader = ArcLoader::builder("./tests/aliases", langid!("en-US"))
+    ///     .map_directory(|name| match name {
+    ///         "deutsch" => Some(langid!("de")),
+    ///         _ => name.parse::<LanguageIdentifier>().ok(),
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(loader.locales().any(|lang| *lang == langid!("de")));
+    /// ```
+    pub fn map_directory(
+        mut self,
+        map: impl Fn(&str) -> Option<LanguageIdentifier> + Send + Sync + 'static,
+    ) -> Self {
+        self.map_directory = Some(Box::new(map));
+        self
+    }
+
+    /// Sets whether a resource that can't be parsed fails the build, the
+    /// default, or is skipped, see [`OnParseError`]. Skipped resources are
+    /// listed by [`ArcLoader::build_report`].
+    pub fn on_parse_error(mut self, on_parse_error: OnParseError) -> Self {
+        self.on_parse_error = on_parse_error;
+        self
+    }
+
+    /// Sets whether a message or term that's defined more than once for a
+    /// locale fails the build, the default, or overrides the earlier
+    /// definition, see [`Duplicates`]. Overriding allows a locale to have
+    /// files that patch its other files.
+    pub fn duplicates(mut self, duplicates: Duplicates) -> Self {
+        self.duplicates = duplicates;
+        self
+    }
+
+    /// Sets how requested languages are matched against the loaded locales,
+    /// see [`NegotiationStrategy`]. By default only a language and the more
+    /// general languages it falls back to are used.
+    pub fn negotiation_strategy(mut self, strategy: NegotiationStrategy) -> Self {
+        self.negotiation_strategy = strategy;
+        self
+    }
+
+    /// Adds `prefix` to the ID of every message, so `hello` is looked up as
+    /// `myapp-hello` with a prefix of `myapp-`, to avoid collisions with the
+    /// messages of other loaders in a [`MultiLoader`]. Messages still
+    /// reference each other by their unprefixed IDs.
+    ///
+    /// [`MultiLoader`]: crate::MultiLoader
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Reads the resources of every locale in the locales directory or the
+    /// embedded files, then adds those added to the builder, parsing any
+    /// sources. Directories with the same language, e.g. `en-US` and
+    /// `en_US`, are merged in the order of their names.
+    fn read_resources(
+        &self,
+        report: &mut BuildReport,
+    ) -> Result<Resources, Box<dyn std::error::Error>> {
         let mut resources = HashMap::new();
+        if let Some(location) = self.location {
+            self.read_locales(location, &mut resources, report)?;
+        }
+        let files = self.added_files().map(std::fs::read_to_string).collect();
+        self.add_resources_to(&mut resources, files, report)?;
+        Ok(resources)
+    }
+
+    /// Returns the paths of the files added with [`add_file`](Self::add_file),
+    /// in order.
+    fn added_files(&self) -> impl Iterator<Item = &PathBuf> {
+        self.sources.iter().filter_map(|(_, source)| match source {
+            Source::File(path) => Some(path),
+            _ => None,
+        })
+    }
+
+    /// Adds the resources of the resource source and the embedded files,
+    /// then those added to the builder. `files` are the contents of the
+    /// [`added_files`](Self::added_files).
+    fn add_resources_to(
+        &self,
+        resources: &mut Resources,
+        files: Vec<std::io::Result<String>>,
+        report: &mut BuildReport,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(source) = &self.source {
+            for lang in source.languages()? {
+                for src in source.load(&lang)? {
+                    let origin = format!("a resource of {lang} from the resource source");
+                    if let Some(resource) = self.parse(origin, &src, report)? {
+                        resources.entry(lang.clone()).or_default().push(resource);
+                    }
+                }
+            }
+        }
+        self.read_embedded(resources, report)?;
+
+        let mut files = files.into_iter();
+        for (lang, source) in &self.sources {
+            let resource = match source {
+                Source::Text(source) => {
+                    let origin = format!("a source added for {lang}");
+                    match self.parse(origin, source, report)? {
+                        Some(resource) => resource,
+                        None => continue,
+                    }
+                }
+                Source::Parsed(resource) => resource.clone(),
+                Source::File(path) => {
+                    let src = files
+                        .next()
+                        .expect("each added file is read")
+                        .map_err(|source| LoaderError::Fs {
+                            path: path.clone(),
+                            source,
+                        })?;
+                    match self.parse(path.display().to_string(), &src, report)? {
+                        Some(resource) => resource,
+                        None => continue,
+                    }
+                }
+            };
+            resources.entry(lang.clone()).or_default().push(resource);
+        }
 
-        for entry in read_dir(self.location)? {
-            let entry = entry?;
+        Ok(())
+    }
+
+    /// Parses `src`, which is described by `origin`. If it can't be parsed
+    /// and [`OnParseError::Skip`] is set, it's added to `report` instead.
+    fn parse(
+        &self,
+        origin: String,
+        src: &str,
+        report: &mut BuildReport,
+    ) -> Result<Option<Arc<FluentResource>>, LoaderError> {
+        match crate::fs::resource_from_str(src) {
+            Ok(resource) => Ok(Some(Arc::new(resource))),
+            Err(error) if self.on_parse_error == OnParseError::Skip => {
+                log::warn!("Skipping {origin}, which couldn't be parsed: {error}");
+                report.skipped.push(SkippedResource { origin, error });
+                Ok(None)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Parses the files read from a directory. Files that couldn't be read
+    /// are always skipped, and added to `report`.
+    fn parse_files(
+        &self,
+        files: Vec<(PathBuf, std::io::Result<String>)>,
+        report: &mut BuildReport,
+    ) -> Result<Vec<Arc<FluentResource>>, LoaderError> {
+        let mut resources = Vec::with_capacity(files.len());
+        for (path, src) in files {
+            let origin = path.display().to_string();
+            match src {
+                Ok(src) => resources.extend(self.parse(origin, &src, report)?),
+                Err(source) => {
+                    log::warn!("Couldn't read {origin}");
+                    let error = LoaderError::Fs { path, source };
+                    report.skipped.push(SkippedResource { origin, error });
+                }
+            }
+        }
+        Ok(resources)
+    }
+
+    fn read_locales(
+        &self,
+        location: &Path,
+        resources: &mut Resources,
+        report: &mut BuildReport,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut entries = read_dir(location)?.collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
             if entry.file_type()?.is_dir() {
-                if let Ok(lang) = entry.file_name().into_string() {
-                    let lang_resources = crate::fs::read_from_dir(entry.path())?
-                        .into_iter()
-                        .map(Arc::new)
-                        .collect::<Vec<_>>();
-                    resources.insert(lang.parse::<LanguageIdentifier>()?, lang_resources);
+                if let Ok(name) = entry.file_name().into_string() {
+                    let Some(lang) = self.directory_language(&name)? else {
+                        continue;
+                    };
+                    let files = crate::fs::read_sources_from_dir(entry.path(), &self.files());
+                    let lang_resources = self.parse_files(files, report)?;
+                    resources.entry(lang).or_default().extend(lang_resources);
                 }
             }
         }
 
-        let mut bundles = HashMap::new();
-        for (lang, v) in resources.iter() {
-            let mut bundle = FluentBundle::new_concurrent(vec![lang.clone()]);
+        Ok(())
+    }
 
-            for shared_resource in self.shared.unwrap_or(&[]) {
-                bundle
-                    .add_resource(Arc::new(crate::fs::read_from_file(shared_resource)?))
-                    .map_err(|errors| LoaderError::FluentBundle { errors })?;
+    /// Returns which files in locale and shared resource directories are
+    /// resources.
+    fn files(&self) -> crate::fs::FileFilter<'_> {
+        crate::fs::FileFilter {
+            predicate: self.filter.as_deref(),
+            max_depth: self.max_depth,
+            ..crate::fs::FileFilter::new(&self.extensions)
+        }
+    }
+
+    /// Parses the embedded resources in each locale directory, in the order
+    /// of their paths.
+    fn read_embedded(
+        &self,
+        resources: &mut Resources,
+        report: &mut BuildReport,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut files = self.embedded.iter().collect::<Vec<_>>();
+        files.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut languages = HashMap::new();
+        for (path, contents) in files {
+            let mut components = path.components();
+            let Some(Component::Normal(name)) = components.next() else {
+                continue;
+            };
+            // Files outside of a locale directory aren't resources.
+            let relative = components.as_path();
+            let depth = relative.components().count();
+            if depth == 0
+                || self
+                    .max_depth
+                    .is_some_and(|max_depth| depth > max_depth + 1)
+                || !self.files().matches(relative)
+            {
+                continue;
+            }
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+            // Each directory is only mapped once, so it's only warned about
+            // once if it's skipped.
+            if !languages.contains_key(name) {
+                languages.insert(name, self.directory_language(name)?);
+            }
+            let Some(lang) = languages[name].clone() else {
+                continue;
+            };
+            let src = std::str::from_utf8(contents)?;
+            if let Some(resource) = self.parse(path.display().to_string(), src, report)? {
+                resources.entry(lang).or_default().push(resource);
             }
+        }
 
-            for res in v {
-                bundle
-                    .add_resource(res.clone())
-                    .map_err(|errors| LoaderError::FluentBundle { errors })?;
+        Ok(())
+    }
+
+    /// Returns the language of the locale directory called `name`, or
+    /// `None` if it's skipped by [`map_directory`](Self::map_directory).
+    fn directory_language(
+        &self,
+        name: &str,
+    ) -> Result<Option<LanguageIdentifier>, unic_langid::LanguageIdentifierError> {
+        if let Some(lang) = self.aliases.get(name) {
+            return Ok(Some(lang.clone()));
+        }
+        let Some(map_directory) = &self.map_directory else {
+            return name.parse().map(Some);
+        };
+        let lang = map_directory(name);
+        if lang.is_none() {
+            log::warn!("Skipping locale directory `{name}`, which isn't mapped to a language");
+        }
+        Ok(lang)
+    }
+
+    /// Reads the locales directory and reports how completely each locale
+    /// is translated relative to the fallback language, without building
+    /// any bundles. Shared resources are not included.
+    pub fn coverage(&self) -> Result<CoverageReport, Box<dyn std::error::Error>> {
+        let resources = self.read_resources(&mut BuildReport::default())?;
+        Ok(CoverageReport::new(
+            &self.fallback,
+            resources
+                .iter()
+                .map(|(lang, resources)| (lang, super::shared::message_ids(resources))),
+        ))
+    }
+
+    /// Reads the locales directory and the shared resources, and checks them
+    /// for messages that are missing or have different variables compared to
+    /// the fallback language, and for terms that aren't referred to, without
+    /// building any bundles. See [`ValidationReport`].
+    pub fn validate(&self) -> Result<ValidationReport, Box<dyn std::error::Error>> {
+        let mut report = BuildReport::default();
+        let resources = self.read_resources(&mut report)?;
+        let shared = self.read_shared(&mut report)?;
+        Ok(ValidationReport::new(&self.fallback, &resources, &shared))
+    }
+
+    /// Constructs an `ArcLoader` from the settings provided.
+    pub fn build(self) -> Result<ArcLoader, Box<dyn std::error::Error>> {
+        let mut report = BuildReport::default();
+        let resources = self.read_resources(&mut report)?;
+        let shared = self.read_shared(&mut report)?;
+        self.assemble(resources, shared, report)
+    }
+
+    /// Reads and parses the shared resources.
+    fn read_shared(
+        &self,
+        report: &mut BuildReport,
+    ) -> Result<Vec<Arc<FluentResource>>, Box<dyn std::error::Error>> {
+        let mut shared = Vec::new();
+        for path in self.shared.unwrap_or(&[]) {
+            if path.is_dir() {
+                let files = crate::fs::read_sources_from_dir(path, &self.files());
+                shared.extend(self.parse_files(files, report)?);
+            } else {
+                let src = std::fs::read_to_string(path).map_err(|source| LoaderError::Fs {
+                    path: path.clone(),
+                    source,
+                })?;
+                shared.extend(self.parse(path.display().to_string(), &src, report)?);
             }
+        }
+        Ok(shared)
+    }
 
-            if let Some(customize) = self.customize.as_mut() {
-                (customize)(&mut bundle);
+    /// Like [`build`](Self::build), but reads the resources without
+    /// blocking the async runtime, so it can be called from async code
+    /// without `spawn_blocking`. Hidden files are skipped, but unlike
+    /// `build`, ignore files such as `.gitignore` aren't respected.
+    /// ```
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// use fluent_templates::{ArcLoader, DynLoader};
+    /// use unic_langid::langid;
+    ///
+    /// let loader = ArcLoader::builder("./tests/locales", langid!("en-US"))
+    ///     .build_async()
+    ///     .await
+    ///     .unwrap();
+    /// assert_eq!("Bonjour le monde!", loader.lookup(&langid!("fr"), "hello-world"));
+    /// # }
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub async fn build_async(self) -> Result<ArcLoader, Box<dyn std::error::Error>> {
+        let mut report = BuildReport::default();
+        let mut resources = Resources::new();
+        if let Some(location) = self.location {
+            let mut entries = Vec::new();
+            let mut dir = tokio::fs::read_dir(location).await?;
+            while let Some(entry) = dir.next_entry().await? {
+                if entry.file_type().await?.is_dir() {
+                    entries.push(entry);
+                }
+            }
+            entries.sort_by_key(|entry| entry.file_name());
+            for entry in entries {
+                if let Ok(name) = entry.file_name().into_string() {
+                    let Some(lang) = self.directory_language(&name)? else {
+                        continue;
+                    };
+                    let files =
+                        crate::fs::read_sources_from_dir_async(&entry.path(), &self.files())
+                            .await?;
+                    let lang_resources = self.parse_files(files, &mut report)?;
+                    resources.entry(lang).or_default().extend(lang_resources);
+                }
             }
+        }
+        let mut files = Vec::new();
+        for path in self.added_files() {
+            files.push(tokio::fs::read_to_string(path).await);
+        }
+        self.add_resources_to(&mut resources, files, &mut report)?;
+
+        let mut shared = Vec::new();
+        for path in self.shared.unwrap_or(&[]) {
+            if tokio::fs::metadata(path).await?.is_dir() {
+                let files = crate::fs::read_sources_from_dir_async(path, &self.files()).await?;
+                shared.extend(self.parse_files(files, &mut report)?);
+            } else {
+                let src =
+                    tokio::fs::read_to_string(path)
+                        .await
+                        .map_err(|source| LoaderError::Fs {
+                            path: path.clone(),
+                            source,
+                        })?;
+                shared.extend(self.parse(path.display().to_string(), &src, &mut report)?);
+            }
+        }
+
+        self.assemble(resources, shared, report)
+    }
 
+    /// Builds the bundles of the loader from the resources that have been
+    /// read.
+    fn assemble(
+        mut self,
+        resources: Resources,
+        shared: Vec<Arc<FluentResource>>,
+        report: BuildReport,
+    ) -> Result<ArcLoader, Box<dyn std::error::Error>> {
+        let mut bundles = HashMap::new();
+        for lang in resources.keys() {
+            let bundle = build_bundle(
+                lang,
+                &resources,
+                &shared,
+                self.duplicates,
+                &mut self.customize,
+            )?;
             bundles.insert(lang.clone(), bundle);
         }
 
         let fallbacks = super::build_fallbacks(&resources.keys().cloned().collect::<Vec<_>>());
+        let prefix = Prefix::new(
+            self.prefix,
+            resources.iter().map(|(lang, resources)| {
+                (
+                    lang,
+                    super::shared::message_ids(shared.iter().chain(resources)),
+                )
+            }),
+        );
 
         Ok(ArcLoader {
-            bundles,
-            fallbacks,
+            resources: Arc::new(resources),
+            shared: shared.into(),
+            bundles: Arc::new(NegotiatedBundles::new(bundles, self.negotiation_strategy)),
+            fallbacks: Arc::new(fallbacks),
             fallback: self.fallback,
+            on_missing: self.on_missing,
+            prefix: Arc::new(prefix),
+            duplicates: self.duplicates,
+            customize: Arc::new(Mutex::new(self.customize)),
+            report: Arc::new(report),
+            scopes: Arc::default(),
+            aliases: Arc::new(self.language_aliases),
         })
     }
 }
 
+/// What an [`ArcLoaderBuilder`] does with a resource that can't be parsed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OnParseError {
+    /// Fail the build.
+    #[default]
+    Fail,
+    /// Skip the resource, so the loader has the rest of its locale's
+    /// messages, and add it to the loader's [`BuildReport`].
+    Skip,
+}
+
+/// The resources that were skipped when an [`ArcLoader`] was built, from
+/// [`ArcLoader::build_report`], e.g. for reporting them to monitoring.
+#[derive(Debug, Default)]
+pub struct BuildReport {
+    /// The resources that couldn't be read, or couldn't be parsed with
+    /// [`OnParseError::Skip`], in the order they were read.
+    pub skipped: Vec<SkippedResource>,
+}
+
+impl BuildReport {
+    /// Returns whether every resource was loaded.
+    pub fn is_complete(&self) -> bool {
+        self.skipped.is_empty()
+    }
+}
+
+/// A resource that was skipped when building an [`ArcLoader`].
+#[derive(Debug)]
+pub struct SkippedResource {
+    /// The path of the resource, or where it came from if it wasn't read
+    /// from a file.
+    pub origin: String,
+    /// Why it was skipped.
+    pub error: LoaderError,
+}
+
+/// Builds the bundle of `lang` from its resources, those it inherits from
+/// its parent locales, and `shared`.
+fn build_bundle(
+    lang: &LanguageIdentifier,
+    resources: &Resources,
+    shared: &[Arc<FluentResource>],
+    duplicates: Duplicates,
+    customize: &mut Customize,
+) -> Result<FluentBundle<Arc<FluentResource>>, LoaderError> {
+    let mut bundle = FluentBundle::new_concurrent(vec![lang.clone()]);
+    let parents = super::parent_locales(lang, resources);
+
+    match duplicates {
+        Duplicates::Error => {
+            for res in shared.iter().chain(&resources[lang]) {
+                bundle
+                    .add_resource(res.clone())
+                    .map_err(|errors| LoaderError::FluentBundle { errors })?;
+            }
+
+            // Inherit the messages of parent locales, e.g. `de` for
+            // `de-AT`, that the locale doesn't override. The only
+            // errors are for those it does, which are skipped.
+            for parent in parents.into_iter().rev() {
+                for res in &resources[parent] {
+                    let _ = bundle.add_resource(res.clone());
+                }
+            }
+        }
+        Duplicates::Override => {
+            let inherited = parents.into_iter().flat_map(|parent| &resources[parent]);
+            for res in shared.iter().chain(inherited).chain(&resources[lang]) {
+                bundle.add_resource_overriding(res.clone());
+            }
+        }
+    }
+
+    if let Some(customize) = customize.as_mut() {
+        (customize)(&mut bundle, lang);
+    }
+
+    Ok(bundle)
+}
+
 /// A loader that uses `Arc<FluentResource>` as its backing storage. This is
 /// mainly useful for when you need to load fluent at run time. You can
 /// configure the initialisation with `ArcLoaderBuilder`.
+///
+/// Clones are cheap, and share the resources and bundles of the loader until
+/// either of them is changed, e.g. with
+/// [`insert_locale`](ArcLoader::insert_locale), which only changes that
+/// loader.
 /// ```no_run
 /// use fluent_templates::ArcLoader;
 ///
@@ -100,56 +957,275 @@ impl<'a, 'b> ArcLoaderBuilder<'a, 'b> {
 ///     .build()
 ///     .unwrap();
 /// ```
+#[derive(Clone)]
 pub struct ArcLoader {
-    bundles: HashMap<LanguageIdentifier, FluentBundle<Arc<FluentResource>>>,
+    resources: Arc<Resources>,
+    shared: Arc<[Arc<FluentResource>]>,
+    bundles: Arc<NegotiatedBundles<Arc<FluentResource>>>,
     fallback: LanguageIdentifier,
-    fallbacks: HashMap<LanguageIdentifier, Vec<LanguageIdentifier>>,
+    fallbacks: Arc<HashMap<LanguageIdentifier, Vec<LanguageIdentifier>>>,
+    on_missing: OnMissing,
+    prefix: Arc<Prefix>,
+    duplicates: Duplicates,
+    /// Shared by clones, which rebuild bundles with the same customizer.
+    customize: Arc<Mutex<Customize>>,
+    report: Arc<BuildReport>,
+    scopes: Arc<HashMap<String, Scope>>,
+    aliases: Arc<HashMap<LanguageIdentifier, LanguageIdentifier>>,
 }
 
-impl super::Loader for ArcLoader {
-    // Traverse the fallback chain,
+/// Resources that take precedence over a loader's own for a scope, such as a
+/// tenant, and the loader of the bundles that they're added to.
+#[derive(Clone)]
+struct Scope {
+    overrides: Resources,
+    loader: ArcLoader,
+}
+
+impl fmt::Debug for ArcLoader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArcLoader")
+            .field("locales", &super::shared::locale_names(self))
+            .field("fallback", &self.fallback.to_string())
+            .field("shared_resources", &self.shared.len())
+            .field("on_missing", &self.on_missing)
+            .field("scopes", &self.scopes.keys().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+impl super::DynLoader for ArcLoader {
     fn lookup_complete(
         &self,
         lang: &LanguageIdentifier,
         text_id: &str,
-        args: Option<&HashMap<Cow<'static, str>, FluentValue>>,
+        args: Option<&FluentArgs>,
     ) -> String {
-        for lang in negotiate_languages(&[lang], &self.bundles.keys().collect::<Vec<_>>(), None) {
-            if let Ok(val) = self.lookup_single_language(lang, text_id, args) {
-                return val;
-            }
-        }
-        if *lang != self.fallback {
-            if let Ok(val) = self.lookup_single_language(&self.fallback, text_id, args) {
-                return val;
-            }
-        }
-        format!("Unknown localization {text_id}")
+        self.lookup_cow(lang, text_id, args).into_owned()
     }
 
-    // Traverse the fallback chain,
     fn try_lookup_complete(
         &self,
         lang: &LanguageIdentifier,
         text_id: &str,
-        args: Option<&HashMap<Cow<'static, str>, FluentValue>>,
+        args: Option<&FluentArgs>,
     ) -> Option<String> {
-        for lang in negotiate_languages(&[lang], &self.bundles.keys().collect::<Vec<_>>(), None) {
-            if let Ok(val) = self.lookup_single_language(lang, text_id, args) {
-                return Some(val);
-            }
+        self.try_lookup_cow(lang, text_id, args)
+            .map(Cow::into_owned)
+    }
+
+    fn lookup_cow(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Cow<'_, str> {
+        self.try_lookup_cow(lang, text_id, args)
+            .unwrap_or_else(|| Cow::Owned(self.missing(std::slice::from_ref(lang), text_id)))
+    }
+
+    fn try_lookup_cow(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<Cow<'_, str>> {
+        let text_id = self.prefix.strip(text_id)?;
+        super::shared::lookup_with_fallback(
+            &self.bundles,
+            &self.fallback,
+            &self.search_langs(std::slice::from_ref(lang)),
+            text_id,
+            args,
+        )
+        .ok()
+    }
+
+    // Traverse the fallback chain,
+    fn lookup_complete_result(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Result<String, LookupError> {
+        let text_id = self
+            .prefix
+            .strip(text_id)
+            .ok_or_else(|| LookupError::MessageRetrieval(text_id.to_owned()))?;
+        super::shared::lookup_with_fallback(
+            &self.bundles,
+            &self.fallback,
+            &self.search_langs(std::slice::from_ref(lang)),
+            text_id,
+            args,
+        )
+        .map(Cow::into_owned)
+    }
+
+    fn lookup_langs(
+        &self,
+        langs: &[LanguageIdentifier],
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> String {
+        self.try_lookup_langs(langs, text_id, args)
+            .unwrap_or_else(|| self.missing(langs, text_id))
+    }
+
+    fn try_lookup_langs(
+        &self,
+        langs: &[LanguageIdentifier],
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        let text_id = self.prefix.strip(text_id)?;
+        let langs = self.search_langs(langs);
+        super::shared::lookup_with_fallback(&self.bundles, &self.fallback, &langs, text_id, args)
+            .ok()
+            .map(Cow::into_owned)
+    }
+
+    fn lookup_no_default_fallback(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        let text_id = self.prefix.strip(text_id)?;
+        super::shared::lookup_no_default_fallback(
+            &self.bundles,
+            &self.search_langs(std::slice::from_ref(lang)),
+            text_id,
+            args,
+        )
+    }
+
+    fn lookup_into(
+        &self,
+        out: &mut dyn fmt::Write,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> fmt::Result {
+        let written = self.prefix.strip(text_id).and_then(|id| {
+            super::shared::write_with_fallback(
+                &self.bundles,
+                &self.fallback,
+                out,
+                &self.search_langs(std::slice::from_ref(lang)),
+                id,
+                args,
+            )
+        });
+        match written {
+            Some(result) => result,
+            None => out.write_str(&self.missing(std::slice::from_ref(lang), text_id)),
         }
-        if *lang != self.fallback {
-            if let Ok(val) = self.lookup_single_language(&self.fallback, text_id, args) {
-                return Some(val);
+    }
+
+    fn has(&self, lang: &LanguageIdentifier, text_id: &str) -> bool {
+        let Some(text_id) = self.prefix.strip(text_id) else {
+            return false;
+        };
+        super::shared::has_with_fallback(
+            &self.bundles,
+            &self.fallback,
+            &self.search_langs(std::slice::from_ref(lang)),
+            text_id,
+        )
+    }
+
+    fn message_ids(&self, lang: &LanguageIdentifier) -> Box<dyn Iterator<Item = &str> + '_> {
+        match self.resources.get(lang) {
+            Some(resources) => self.prefix.message_ids(
+                lang,
+                super::shared::message_ids(self.shared.iter().chain(resources)),
+            ),
+            None => Box::new(std::iter::empty()),
+        }
+    }
+
+    fn required_args(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+        let Some(text_id) = self.prefix.strip(text_id) else {
+            return Vec::new();
+        };
+        super::shared::required_args_with_fallback(
+            &self.bundles,
+            &self.fallback,
+            &self.search_langs(std::slice::from_ref(lang)),
+            text_id,
+        )
+    }
+
+    fn term_ids(&self, lang: &LanguageIdentifier) -> Box<dyn Iterator<Item = &str> + '_> {
+        match self.resources.get(lang) {
+            Some(resources) => {
+                Box::new(super::shared::term_ids(self.shared.iter().chain(resources)))
             }
+            None => Box::new(std::iter::empty()),
         }
-        None
+    }
+
+    #[cfg(debug_assertions)]
+    fn format_term(
+        &self,
+        lang: &LanguageIdentifier,
+        term_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        super::shared::format_term(
+            self.bundles.get(lang)?,
+            self.shared.iter().chain(self.resources.get(lang)?),
+            term_id,
+            args,
+        )
+    }
+
+    fn attributes(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+        let Some(text_id) = self.prefix.strip(text_id) else {
+            return Vec::new();
+        };
+        super::shared::attributes_with_fallback(
+            &self.bundles,
+            &self.fallback,
+            &self.search_langs(std::slice::from_ref(lang)),
+            text_id,
+        )
+    }
+
+    fn lookup_message(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<LocalizedMessage> {
+        let text_id = self.prefix.strip(text_id)?;
+        super::shared::message_with_fallback(
+            &self.bundles,
+            &self.fallback,
+            &self.search_langs(std::slice::from_ref(lang)),
+            text_id,
+            args,
+        )
+    }
+
+    fn fallback_chain(&self, lang: &LanguageIdentifier) -> Vec<LanguageIdentifier> {
+        let langs = self.search_langs(std::slice::from_ref(lang));
+        super::shared::fallback_chain(&self.bundles, &self.fallback, &langs)
+            .into_iter()
+            .cloned()
+            .collect()
     }
 
     fn locales(&self) -> Box<dyn Iterator<Item = &LanguageIdentifier> + '_> {
         Box::new(self.fallbacks.keys())
     }
+
+    fn stats(&self) -> LoaderStats {
+        LoaderStats::from_loader(self, |lang| {
+            self.resources.get(lang).map_or(0, Vec::len) + self.shared.len()
+        })
+    }
 }
 
 impl ArcLoader {
@@ -157,44 +1233,325 @@ impl ArcLoader {
     pub fn builder<P: AsRef<Path> + ?Sized>(
         location: &P,
         fallback: LanguageIdentifier,
-    ) -> ArcLoaderBuilder {
+    ) -> ArcLoaderBuilder<'_, '_> {
         ArcLoaderBuilder {
-            location: location.as_ref(),
-            fallback,
-            shared: None,
-            customize: None,
+            location: Some(location.as_ref()),
+            ..ArcLoaderBuilder::new(fallback)
         }
     }
 
     /// Convenience function to look up a string for a single language
-    pub fn lookup_single_language<T: AsRef<str>>(
+    pub fn lookup_single_language(
         &self,
         lang: &LanguageIdentifier,
         text_id: &str,
-        args: Option<&HashMap<T, FluentValue>>,
+        args: Option<&FluentArgs>,
     ) -> Result<String, LookupError> {
+        let text_id = self
+            .prefix
+            .strip(text_id)
+            .ok_or_else(|| LookupError::MessageRetrieval(text_id.to_owned()))?;
         super::shared::lookup_single_language(&self.bundles, lang, text_id, args)
     }
 
-    /// Convenience function to look up a string without falling back to the
-    /// default fallback language
-    pub fn lookup_no_default_fallback<S: AsRef<str>>(
+    /// Return the fallback language
+    pub fn fallback(&self) -> &LanguageIdentifier {
+        &self.fallback
+    }
+
+    /// Returns the resources that were skipped when the loader was built,
+    /// because they couldn't be read or parsed.
+    pub fn build_report(&self) -> &BuildReport {
+        &self.report
+    }
+
+    /// Returns the `FluentBundle` for exactly `lang`, for using
+    /// `fluent_bundle` APIs directly.
+    pub fn get_bundle(
         &self,
         lang: &LanguageIdentifier,
-        text_id: &str,
-        args: Option<&HashMap<S, FluentValue>>,
-    ) -> Option<String> {
-        super::shared::lookup_no_default_fallback(
+    ) -> Option<&FluentBundle<Arc<FluentResource>>> {
+        self.bundles.get(lang)
+    }
+
+    /// Adds `lang` with `resources`, replacing its current resources if it
+    /// already has any. Only the bundles of `lang` and the locales that
+    /// inherit from it are rebuilt. If the bundle can't be built, e.g.
+    /// because a message is defined twice, the loader is left unchanged.
+    pub fn insert_locale(
+        &mut self,
+        lang: LanguageIdentifier,
+        resources: Vec<FluentResource>,
+    ) -> Result<(), LoaderError> {
+        let resources = resources.into_iter().map(Arc::new).collect();
+        let previous = Arc::make_mut(&mut self.resources).insert(lang.clone(), resources);
+        if let Err(error) = self.rebuild(&lang) {
+            let all = Arc::make_mut(&mut self.resources);
+            match previous {
+                Some(previous) => all.insert(lang, previous),
+                None => all.remove(&lang),
+            };
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    /// Parses `source` and makes it the only resource of `lang`, adding
+    /// `lang` if the loader doesn't have it, like
+    /// [`insert_locale`](Self::insert_locale).
+    pub fn update_resource(
+        &mut self,
+        lang: LanguageIdentifier,
+        source: impl Into<String>,
+    ) -> Result<(), LoaderError> {
+        let resource = FluentResource::try_new(source.into())
+            .map_err(|(_, errors)| crate::error::FluentError::from(errors))?;
+        self.insert_locale(lang, vec![resource])
+    }
+
+    /// Removes `lang` and its resources, returning whether the loader had
+    /// it. Locales that inherited from it are rebuilt without its messages.
+    pub fn remove_locale(&mut self, lang: &LanguageIdentifier) -> bool {
+        if Arc::make_mut(&mut self.resources).remove(lang).is_none() {
+            return false;
+        }
+        // Only a locale's own resources can fail to be added, and those of
+        // the remaining locales already were.
+        let _ = self.rebuild(lang);
+        true
+    }
+
+    /// Rebuilds the bundles of every locale of the same language as `lang`,
+    /// which includes `lang` and the locales that inherit from it, then
+    /// the fallbacks and prefixed message IDs. Nothing is changed if any
+    /// bundle fails to build.
+    fn rebuild(&mut self, lang: &LanguageIdentifier) -> Result<(), LoaderError> {
+        let mut bundles = HashMap::new();
+        let mut customize = self
+            .customize
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        for locale in self.resources.keys() {
+            if locale.language == lang.language {
+                let bundle = build_bundle(
+                    locale,
+                    &self.resources,
+                    &self.shared,
+                    self.duplicates,
+                    &mut customize,
+                )?;
+                bundles.insert(locale.clone(), Arc::new(bundle));
+            }
+        }
+        drop(customize);
+
+        let prefix = Arc::make_mut(&mut self.prefix);
+        prefix.remove(lang);
+        for locale in bundles.keys() {
+            let ids = super::shared::message_ids(self.shared.iter().chain(&self.resources[locale]));
+            prefix.insert(locale, ids);
+        }
+        Arc::make_mut(&mut self.bundles).update(|all| {
+            all.remove(lang);
+            all.extend(bundles);
+        });
+        self.fallbacks = Arc::new(super::build_fallbacks(
+            &self.resources.keys().cloned().collect::<Vec<_>>(),
+        ));
+
+        // Scopes replace duplicate messages, so rebuilding them can't fail.
+        let mut scopes = std::mem::take(&mut self.scopes);
+        let rebuilt = Arc::make_mut(&mut scopes)
+            .values_mut()
+            .try_for_each(|scope| {
+                scope.loader = self.build_scope(&scope.overrides)?;
+                Ok(())
+            });
+        self.scopes = scopes;
+        rebuilt
+    }
+
+    /// Adds `resources` for `scope`, such as a tenant or a white-label brand,
+    /// which take precedence over the loader's own resources in lookups made
+    /// with [`scoped`](Self::scoped). Any resources the scope already had are
+    /// replaced.
+    ///
+    /// Bundles are only built for the languages of `resources` and the
+    /// locales that inherit from them, and share the loader's parsed
+    /// resources, so a scope costs much less than a loader of its own. The
+    /// scope's messages replace the loader's, and can refer to its terms.
+    /// ```
+    /// use std::sync::Arc;
+    /// use fluent_templates::fluent_bundle::FluentResource;
+    /// use fluent_templates::{ArcLoader, DynLoader};
+    /// use unic_langid::langid;
+    ///
+    /// let mut loader = ArcLoader::builder("./tests/locales", langid!("en-US"))
+    ///     .build()
+    ///     .unwrap();
+    /// let acme = FluentResource::try_new("hello-world = Hello from Acme!".to_owned()).unwrap();
+    /// loader
+    ///     .add_scope("acme", [(langid!("en-US"), vec![Arc::new(acme)])])
+    ///     .unwrap();
+    ///
+    /// let acme = loader.scoped("acme");
+    /// assert_eq!("Hello from Acme!", acme.lookup(&langid!("en-US"), "hello-world"));
+    /// assert_eq!("Bonjour le monde!", acme.lookup(&langid!("fr"), "hello-world"));
+    /// assert_eq!("Hello World!", loader.lookup(&langid!("en-US"), "hello-world"));
+    /// ```
+    pub fn add_scope(
+        &mut self,
+        scope: impl Into<String>,
+        resources: impl IntoIterator<Item = (LanguageIdentifier, Vec<Arc<FluentResource>>)>,
+    ) -> Result<(), LoaderError> {
+        let mut overrides = Resources::new();
+        for (lang, resources) in resources {
+            overrides.entry(lang).or_default().extend(resources);
+        }
+        let loader = self.build_scope(&overrides)?;
+        Arc::make_mut(&mut self.scopes).insert(scope.into(), Scope { overrides, loader });
+        Ok(())
+    }
+
+    /// Removes `scope` and its resources, returning whether the loader had
+    /// it.
+    pub fn remove_scope(&mut self, scope: &str) -> bool {
+        Arc::make_mut(&mut self.scopes).remove(scope).is_some()
+    }
+
+    /// Returns a loader that looks up messages in the resources of `scope`
+    /// before the loader's own, see [`add_scope`](Self::add_scope). A scope
+    /// that hasn't been added has no resources, so its lookups are the same
+    /// as the loader's.
+    pub fn scoped(&self, scope: &str) -> OverlayLoader<&ArcLoader, &ArcLoader> {
+        static EMPTY: OnceLock<ArcLoader> = OnceLock::new();
+        let overrides = match self.scopes.get(scope) {
+            Some(scope) => &scope.loader,
+            None => EMPTY.get_or_init(|| ArcLoader {
+                resources: Arc::default(),
+                shared: Arc::new([]),
+                bundles: Arc::new(NegotiatedBundles::new(
+                    HashMap::new(),
+                    NegotiationStrategy::default(),
+                )),
+                fallback: LanguageIdentifier::default(),
+                fallbacks: Arc::default(),
+                on_missing: OnMissing::default(),
+                prefix: Arc::default(),
+                duplicates: Duplicates::default(),
+                customize: Arc::default(),
+                report: Arc::default(),
+                scopes: Arc::default(),
+                aliases: Arc::default(),
+            }),
+        };
+        OverlayLoader::new(self, overrides)
+    }
+
+    /// Builds the loader of a scope with `overrides`, whose bundles have the
+    /// loader's resources followed by those of the scope.
+    fn build_scope(&self, overrides: &Resources) -> Result<ArcLoader, LoaderError> {
+        let mut resources = (*self.resources).clone();
+        for (lang, overrides) in overrides {
+            resources
+                .entry(lang.clone())
+                .or_default()
+                .extend(overrides.iter().cloned());
+        }
+        let langs = resources
+            .keys()
+            .filter(|lang| {
+                overrides.contains_key(lang)
+                    || super::parent_locales(lang, &resources)
+                        .into_iter()
+                        .any(|parent| overrides.contains_key(parent))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut bundles = HashMap::new();
+        let mut prefix = (*self.prefix).clone();
+        let mut customize = self
+            .customize
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        for lang in &langs {
+            let bundle = build_bundle(
+                lang,
+                &resources,
+                &self.shared,
+                Duplicates::Override,
+                &mut customize,
+            )?;
+            bundles.insert(lang.clone(), bundle);
+            prefix.insert(
+                lang,
+                super::shared::message_ids(self.shared.iter().chain(&resources[lang])),
+            );
+        }
+        resources.retain(|lang, _| langs.contains(lang));
+
+        Ok(ArcLoader {
+            resources: Arc::new(resources),
+            shared: self.shared.clone(),
+            bundles: Arc::new(NegotiatedBundles::new(bundles, self.bundles.strategy())),
+            fallback: self.fallback.clone(),
+            fallbacks: Arc::new(super::build_fallbacks(&langs)),
+            on_missing: OnMissing::default(),
+            prefix: Arc::new(prefix),
+            duplicates: Duplicates::Override,
+            customize: Arc::default(),
+            report: Arc::default(),
+            scopes: Arc::default(),
+            aliases: self.aliases.clone(),
+        })
+    }
+
+    /// Returns the locales whose resources differ between `self` and
+    /// `other`, including those that only one of them has, in order. Every
+    /// locale has changed if the shared resources have.
+    pub(crate) fn changed_locales(&self, other: &ArcLoader) -> Vec<LanguageIdentifier> {
+        fn same(a: &[Arc<FluentResource>], b: &[Arc<FluentResource>]) -> bool {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.source() == b.source())
+        }
+
+        let shared_changed = !same(&self.shared, &other.shared);
+        let mut changed = self
+            .resources
+            .keys()
+            .chain(other.resources.keys())
+            .filter(|lang| {
+                shared_changed
+                    || match (self.resources.get(*lang), other.resources.get(*lang)) {
+                        (Some(a), Some(b)) => !same(a, b),
+                        _ => true,
+                    }
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        changed.sort();
+        changed.dedup();
+        changed
+    }
+
+    fn missing(&self, langs: &[LanguageIdentifier], text_id: &str) -> String {
+        super::shared::resolve_missing(
             &self.bundles,
-            &self.fallbacks,
-            lang,
+            &self.fallback,
+            &self.on_missing,
+            &self.search_langs(langs),
             text_id,
-            args,
         )
     }
 
-    /// Return the fallback language
-    pub fn fallback(&self) -> &LanguageIdentifier {
-        &self.fallback
+    /// Returns `langs` with their aliases resolved.
+    fn search_langs<'a>(&self, langs: &'a [LanguageIdentifier]) -> Cow<'a, [LanguageIdentifier]> {
+        if self.aliases.is_empty() {
+            return Cow::Borrowed(langs);
+        }
+        langs
+            .iter()
+            .map(|lang| super::resolve_alias(&self.aliases, lang).into_owned())
+            .collect()
     }
 }
@@ -1,15 +1,36 @@
+use std::borrow::Cow;
+
 use handlebars::{
-    Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, RenderErrorReason,
-    Renderable,
+    Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, RenderError,
+    RenderErrorReason, Renderable, ScopedJson,
 };
 
-use fluent_bundle::FluentValue;
+use fluent_bundle::{FluentArgs, FluentValue};
 use handlebars::template::{Parameter, TemplateElement};
 use serde_json::Value as Json;
-use std::borrow::Cow;
-use std::collections::HashMap;
+use unic_langid::LanguageIdentifier;
+
+use crate::{AvailableLocales, DynLoader, FluentLoader, TextDirection};
+
+/// The hash parameter of `{{fluent}}` that names an attribute of the message.
+const ATTR_KEY: &str = "attr";
+/// The hash parameter of `{{fluent}}` that overrides the language of the
+/// context.
+const LANG_KEY: &str = "lang";
+
+/// The hash parameter of `{{fluentparam}}` that sets the type of its value,
+/// `"string"` by default or `"number"`, e.g. to select a plural category.
+const TYPE_KEY: &str = "type";
+
+/// The hash parameter of `{{fluent}}` that overrides whether the message is
+/// HTML-escaped.
+const ESCAPE_KEY: &str = "escape";
 
-use crate::{FluentLoader, Loader};
+/// Returns whether a hash parameter of `{{fluent}}` is used by the helper
+/// rather than passed to the message.
+fn is_helper_key(key: &str) -> bool {
+    key == ATTR_KEY || key == LANG_KEY || key == ESCAPE_KEY
+}
 
 #[derive(Default)]
 struct StringOutput {
@@ -23,7 +44,7 @@ impl Output for StringOutput {
     }
 }
 
-impl<L: Loader + Send + Sync> HelperDef for FluentLoader<L> {
+impl<L: DynLoader + Send + Sync> HelperDef for FluentLoader<L> {
     fn call<'reg: 'rc, 'rc>(
         &self,
         h: &Helper<'rc>,
@@ -58,31 +79,83 @@ impl<L: Loader + Send + Sync> HelperDef for FluentLoader<L> {
             .into());
         };
 
-        let mut args: Option<HashMap<Cow<'static, str>, FluentValue>> = if h.hash().is_empty() {
+        let escape = match h.hash_get(ESCAPE_KEY).map(|escape| escape.value()) {
+            Some(Json::Bool(escape)) => *escape,
+            Some(_) => {
+                return Err(RenderErrorReason::ParamTypeMismatchForName(
+                    "fluent",
+                    ESCAPE_KEY.to_string(),
+                    "boolean".to_string(),
+                )
+                .into())
+            }
+            None => self.escape.unwrap_or(false),
+        };
+        let attr = match h.hash_get(ATTR_KEY).map(|attr| attr.value()) {
+            Some(Json::String(attr)) => Some(attr),
+            Some(_) => {
+                return Err(RenderErrorReason::ParamTypeMismatchForName(
+                    "fluent",
+                    ATTR_KEY.to_string(),
+                    "string".to_string(),
+                )
+                .into())
+            }
+            None => None,
+        };
+        let lang = match h.hash_get(LANG_KEY).map(|lang| lang.value()) {
+            Some(Json::String(lang)) => Some(lang.parse().map_err(|_| {
+                RenderErrorReason::ParamTypeMismatchForName(
+                    "fluent",
+                    LANG_KEY.to_string(),
+                    "language identifier".to_string(),
+                )
+            })?),
+            Some(_) => {
+                return Err(RenderErrorReason::ParamTypeMismatchForName(
+                    "fluent",
+                    LANG_KEY.to_string(),
+                    "string".to_string(),
+                )
+                .into())
+            }
+            None => None,
+        };
+
+        let mut args: Option<FluentArgs> = if h.hash().keys().all(|k| is_helper_key(k)) {
             None
         } else {
-            let map = h
-                .hash()
-                .iter()
-                .filter_map(|(k, v)| {
-                    let json = v.value();
-                    let val = match json {
-                        // `Number::as_f64` can't fail here because we haven't
-                        // enabled `arbitrary_precision` feature
-                        // in `serde_json`.
-                        Json::Number(n) => n.as_f64().unwrap().into(),
-                        Json::String(s) => s.to_owned().into(),
-                        _ => return None,
-                    };
-                    Some((Cow::from(k.to_string()), val))
-                })
-                .collect();
+            let mut map = FluentArgs::new();
+            for (k, v) in h.hash().iter().filter(|(k, _)| !is_helper_key(k)) {
+                let val: FluentValue = match v.value() {
+                    // `Number::as_f64` can't fail here because we haven't
+                    // enabled `arbitrary_precision` feature
+                    // in `serde_json`.
+                    Json::Number(n) => n.as_f64().unwrap().into(),
+                    Json::String(s) => s.to_owned().into(),
+                    // Matches the `[true]` and `[false]` variants of select
+                    // expressions.
+                    Json::Bool(b) => b.to_string().into(),
+                    // A missing value, e.g. from a path that doesn't exist in
+                    // the context, leaves the variable unset.
+                    Json::Null => continue,
+                    Json::Array(_) | Json::Object(_) => {
+                        return Err(RenderErrorReason::ParamTypeMismatchForName(
+                            "fluent",
+                            k.to_string(),
+                            "string, number or boolean".to_string(),
+                        )
+                        .into())
+                    }
+                };
+                map.set(k.to_string(), val);
+            }
             Some(map)
         };
 
         if let Some(tpl) = h.template() {
             if args.is_none() {
-                args = Some(HashMap::new());
+                args = Some(FluentArgs::new());
             }
             let args = args.as_mut().unwrap();
             for element in &tpl.elements {
@@ -110,28 +183,163 @@ impl<L: Loader + Send + Sync> HelperDef for FluentLoader<L> {
                             RenderErrorReason::ParamNotFoundForIndex("fluentparam", 0).into()
                         );
                     };
+                    let number = match block.hash.get(TYPE_KEY) {
+                        Some(Parameter::Literal(Json::String(ty))) if ty == "number" => true,
+                        Some(Parameter::Literal(Json::String(ty))) if ty == "string" => false,
+                        None => false,
+                        Some(_) => {
+                            return Err(RenderErrorReason::ParamTypeMismatchForName(
+                                "fluentparam",
+                                TYPE_KEY.into(),
+                                r#""string" or "number""#.into(),
+                            )
+                            .into())
+                        }
+                    };
                     if let Some(ref tpl) = block.template {
                         let mut s = StringOutput::default();
                         tpl.render(reg, context, rcx, &mut s)?;
-                        args.insert(
-                            Cow::Owned(String::from(id)),
-                            FluentValue::String(s.s.into()),
-                        );
+                        let value = if number {
+                            let n = s.s.trim().parse::<f64>().map_err(|_| {
+                                RenderErrorReason::Other(format!(
+                                    "{{{{fluentparam \"{id}\"}}}} must contain a number, not `{}`",
+                                    s.s.trim()
+                                ))
+                            })?;
+                            FluentValue::from(n)
+                        } else {
+                            FluentValue::String(s.s.into())
+                        };
+                        args.set(String::from(id), value);
                     }
                 }
             }
         }
-        let lang = context
-            .data()
-            .get("lang")
-            .expect("Language not set in context")
-            .as_str()
-            .expect("Language must be string")
-            .parse()
-            .expect("Language not valid identifier");
-
-        let response = self.loader.lookup_complete(&lang, id, args.as_ref());
+        let lang = match lang {
+            Some(lang) => lang,
+            None => self.handlebars_lang(context)?,
+        };
+
+        let id = match attr {
+            Some(attr) => Cow::Owned(format!("{id}.{attr}")),
+            None => Cow::Borrowed(id.as_str()),
+        };
+        // An `{{else}}` block is rendered in place of a missing message.
+        let response = match h.inverse() {
+            Some(inverse) => match self.loader.try_lookup_cow(&lang, &id, args.as_ref()) {
+                Some(response) => response,
+                None => return inverse.render(reg, context, rcx, out),
+            },
+            None => self
+                .lookup_for_template(&lang, &id, args.as_ref())
+                .map_err(|error| RenderErrorReason::NestedError(Box::new(error)))?,
+        };
+        let response = if escape {
+            Cow::Owned(handlebars::html_escape(&response))
+        } else {
+            response
+        };
         out.write(&response)
             .map_err(|error| RenderErrorReason::NestedError(Box::new(error)).into())
     }
 }
+
+impl<L: DynLoader + Clone + Send + Sync + 'static> FluentLoader<L> {
+    /// Registers the helpers of this loader with `handlebars`: `fluent`, and
+    /// `fluent-locales` and `fluent-dir`, see
+    /// [`available_locales`](Self::available_locales) and
+    /// [`text_direction`](Self::text_direction).
+    ///
+    /// ```
+    /// use fluent_templates::{ArcLoader, FluentLoader};
+    /// use unic_langid::langid;
+    ///
+    /// let loader = ArcLoader::builder("./tests/locales", langid!("en-US")).build().unwrap();
+    /// let mut handlebars = handlebars::Handlebars::new();
+    /// FluentLoader::new(loader).register_handlebars(&mut handlebars);
+    ///
+    /// let template = r#"<html dir="{{fluent-dir}}">{{fluent "hello-world"}}</html>"#;
+    /// let data = serde_json::json!({ "lang": "fr" });
+    /// assert_eq!(
+    ///     r#"<html dir="ltr">Bonjour le monde!</html>"#,
+    ///     handlebars.render_template(template, &data).unwrap()
+    /// );
+    /// ```
+    pub fn register_handlebars(self, handlebars: &mut Handlebars<'_>) {
+        handlebars.register_helper("fluent-locales", Box::new(self.clone().available_locales()));
+        handlebars.register_helper("fluent-dir", Box::new(self.clone().text_direction()));
+        handlebars.register_helper("fluent", Box::new(self));
+    }
+}
+
+impl<L> FluentLoader<L> {
+    /// Returns the language given by the language variable of the context,
+    /// or the default language if the context doesn't have the variable.
+    fn handlebars_lang(&self, context: &Context) -> Result<LanguageIdentifier, RenderError> {
+        let invalid = || {
+            RenderErrorReason::Other(format!(
+                "`{}` in the context must be a valid unicode language identifier",
+                self.lang_variable
+            ))
+        };
+        match context.data().get(&self.lang_variable) {
+            Some(lang) => Ok(lang
+                .as_str()
+                .ok_or_else(invalid)?
+                .parse()
+                .map_err(|_| invalid())?),
+            None => self.default_lang.clone().ok_or_else(|| {
+                RenderErrorReason::Other(format!(
+                    "`{}` isn't set in the context and there's no default language",
+                    self.lang_variable
+                ))
+                .into()
+            }),
+        }
+    }
+}
+
+impl<L: DynLoader + Send + Sync> HelperDef for TextDirection<L> {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        context: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let lang = match h.param(0).map(|param| param.value()) {
+            Some(Json::String(lang)) => lang.parse().map_err(|_| {
+                RenderErrorReason::ParamTypeMismatchForName(
+                    "dir",
+                    "0".to_string(),
+                    "language identifier".to_string(),
+                )
+            })?,
+            Some(_) => {
+                return Err(RenderErrorReason::ParamTypeMismatchForName(
+                    "dir",
+                    "0".to_string(),
+                    "string".to_string(),
+                )
+                .into())
+            }
+            None => self.0.handlebars_lang(context)?,
+        };
+
+        out.write(self.0.loader.dir(&lang))
+            .map_err(|error| RenderErrorReason::NestedError(Box::new(error)).into())
+    }
+}
+
+impl<L: DynLoader + Send + Sync> HelperDef for AvailableLocales<L> {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        _: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        Ok(ScopedJson::Derived(self.json()))
+    }
+}
@@ -0,0 +1,99 @@
+use fluent_bundle::FluentArgs;
+use unic_langid::LanguageIdentifier;
+
+use super::DynLoader;
+use crate::args::{IntoArgName, IntoArgValue};
+
+/// A loader bound to a single language, created with [`LoaderExt::for_lang`].
+///
+/// This is useful when the language is resolved once, such as per request in
+/// a web handler, and then used for many lookups.
+///
+/// ```
+/// use fluent_templates::{static_loader, LoaderExt};
+/// use unic_langid::langid;
+///
+/// static_loader! {
+///     static LOCALES = {
+///         locales: "./tests/locales",
+///         fallback_language: "en-US",
+///         customise: |bundle| bundle.set_use_isolating(false),
+///     };
+/// }
+///
+/// let french = LOCALES.for_lang(langid!("fr"));
+/// assert_eq!("Bonjour le monde!", french.get("hello-world"));
+/// assert_eq!("Bonjour Alice!", french.get_with_args("greeting", [("name", "Alice")]));
+/// ```
+pub struct LanguageBoundLoader<'a, L: ?Sized> {
+    loader: &'a L,
+    lang: LanguageIdentifier,
+}
+
+impl<'a, L: DynLoader + ?Sized> LanguageBoundLoader<'a, L> {
+    /// Binds `loader` to `lang`.
+    pub fn new(loader: &'a L, lang: LanguageIdentifier) -> Self {
+        Self { loader, lang }
+    }
+
+    /// Returns the language that lookups are made in.
+    pub fn lang(&self) -> &LanguageIdentifier {
+        &self.lang
+    }
+
+    /// Returns the underlying loader.
+    pub fn loader(&self) -> &'a L {
+        self.loader
+    }
+
+    /// Look up `text_id` in Fluent. See [`DynLoader::lookup`].
+    pub fn get(&self, text_id: &str) -> String {
+        self.loader.lookup_complete(&self.lang, text_id, None)
+    }
+
+    /// Look up `text_id` with `args` in Fluent. See
+    /// [`LoaderExt::lookup_with_args`].
+    pub fn get_with_args<'v, K: IntoArgName<'v>, V: IntoArgValue<'v>>(
+        &self,
+        text_id: &str,
+        args: impl IntoIterator<Item = (K, V)>,
+    ) -> String {
+        self.get_complete(text_id, Some(&crate::args::collect(args)))
+    }
+
+    /// Look up `text_id` in Fluent, using any `args` if provided. See
+    /// [`DynLoader::lookup_complete`].
+    pub fn get_complete(&self, text_id: &str, args: Option<&FluentArgs>) -> String {
+        self.loader.lookup_complete(&self.lang, text_id, args)
+    }
+
+    /// Look up `text_id` in Fluent. See [`DynLoader::try_lookup`].
+    pub fn try_get(&self, text_id: &str) -> Option<String> {
+        self.loader.try_lookup_complete(&self.lang, text_id, None)
+    }
+
+    /// Look up `text_id` with `args` in Fluent. See
+    /// [`LoaderExt::try_lookup_with_args`].
+    pub fn try_get_with_args<'v, K: IntoArgName<'v>, V: IntoArgValue<'v>>(
+        &self,
+        text_id: &str,
+        args: impl IntoIterator<Item = (K, V)>,
+    ) -> Option<String> {
+        self.loader
+            .try_lookup_complete(&self.lang, text_id, Some(&crate::args::collect(args)))
+    }
+
+    /// Checks whether `text_id` can be found. See [`DynLoader::has`].
+    pub fn has(&self, text_id: &str) -> bool {
+        self.loader.has(&self.lang, text_id)
+    }
+}
+
+impl<L: ?Sized> Clone for LanguageBoundLoader<'_, L> {
+    fn clone(&self) -> Self {
+        Self {
+            loader: self.loader,
+            lang: self.lang.clone(),
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use fluent_bundle::FluentArgs;
+use unic_langid::LanguageIdentifier;
+
+use super::{DynLoader, LoaderStats, LocalizedMessage};
+use crate::error::LookupError;
+
+/// A loader that transforms every string formatted by another loader,
+/// created with [`LoaderExt::map_output`].
+///
+/// ```
+/// use fluent_templates::{static_loader, LoaderExt};
+/// use unic_langid::langid;
+///
+/// static_loader! {
+///     static LOCALES = {
+///         locales: "./tests/locales",
+///         fallback_language: "en-US",
+///     };
+/// }
+///
+/// // Removes the Unicode isolation marks around arguments, e.g. for
+/// // plain text emails.
+/// let plain = (&*LOCALES).map_output(|text| text.replace(['\u{2068}', '\u{2069}'], ""));
+/// assert_eq!(
+///     "Hello Alice!",
+///     plain.lookup_with_args(&langid!("en-US"), "greeting", [("name", "Alice")])
+/// );
+/// ```
+pub struct MapLoader<L, F> {
+    loader: L,
+    map: F,
+}
+
+impl<L, F> MapLoader<L, F>
+where
+    L: DynLoader,
+    F: Fn(String) -> String,
+{
+    /// Wraps `loader`, passing every string it formats through `map`.
+    pub fn new(loader: L, map: F) -> Self {
+        Self { loader, map }
+    }
+
+    /// Returns the wrapped loader.
+    pub fn into_inner(self) -> L {
+        self.loader
+    }
+}
+
+impl<L, F> DynLoader for MapLoader<L, F>
+where
+    L: DynLoader,
+    F: Fn(String) -> String,
+{
+    fn lookup_complete(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> String {
+        (self.map)(self.loader.lookup_complete(lang, text_id, args))
+    }
+
+    fn try_lookup_complete(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        self.loader
+            .try_lookup_complete(lang, text_id, args)
+            .map(&self.map)
+    }
+
+    fn lookup_complete_result(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Result<String, LookupError> {
+        self.loader
+            .lookup_complete_result(lang, text_id, args)
+            .map(&self.map)
+    }
+
+    fn lookup_cow(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Cow<'_, str> {
+        Cow::Owned(self.lookup_complete(lang, text_id, args))
+    }
+
+    fn try_lookup_cow(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<Cow<'_, str>> {
+        self.try_lookup_complete(lang, text_id, args)
+            .map(Cow::Owned)
+    }
+
+    fn lookup_langs(
+        &self,
+        langs: &[LanguageIdentifier],
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> String {
+        (self.map)(self.loader.lookup_langs(langs, text_id, args))
+    }
+
+    fn try_lookup_langs(
+        &self,
+        langs: &[LanguageIdentifier],
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        self.loader
+            .try_lookup_langs(langs, text_id, args)
+            .map(&self.map)
+    }
+
+    fn lookup_no_default_fallback(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        self.loader
+            .lookup_no_default_fallback(lang, text_id, args)
+            .map(&self.map)
+    }
+
+    fn lookup_into(
+        &self,
+        out: &mut dyn fmt::Write,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> fmt::Result {
+        let mut text = String::new();
+        self.loader.lookup_into(&mut text, lang, text_id, args)?;
+        out.write_str(&(self.map)(text))
+    }
+
+    fn has(&self, lang: &LanguageIdentifier, text_id: &str) -> bool {
+        self.loader.has(lang, text_id)
+    }
+
+    fn attributes(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+        self.loader.attributes(lang, text_id)
+    }
+
+    fn lookup_message(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<LocalizedMessage> {
+        let message = self.loader.lookup_message(lang, text_id, args)?;
+        Some(LocalizedMessage {
+            value: (self.map)(message.value),
+            attrs: message
+                .attrs
+                .into_iter()
+                .map(|(name, value)| (name, (self.map)(value)))
+                .collect(),
+        })
+    }
+
+    fn fallback_chain(&self, lang: &LanguageIdentifier) -> Vec<LanguageIdentifier> {
+        self.loader.fallback_chain(lang)
+    }
+
+    fn locales(&self) -> Box<dyn Iterator<Item = &LanguageIdentifier> + '_> {
+        self.loader.locales()
+    }
+
+    fn message_ids(&self, lang: &LanguageIdentifier) -> Box<dyn Iterator<Item = &str> + '_> {
+        self.loader.message_ids(lang)
+    }
+
+    fn required_args(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+        self.loader.required_args(lang, text_id)
+    }
+
+    fn term_ids(&self, lang: &LanguageIdentifier) -> Box<dyn Iterator<Item = &str> + '_> {
+        self.loader.term_ids(lang)
+    }
+
+    fn stats(&self) -> LoaderStats {
+        self.loader.stats()
+    }
+
+    #[cfg(debug_assertions)]
+    fn format_term(
+        &self,
+        lang: &LanguageIdentifier,
+        term_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        self.loader.format_term(lang, term_id, args).map(&self.map)
+    }
+}
@@ -0,0 +1,273 @@
+use std::borrow::Cow;
+
+use fluent_bundle::{FluentArgs, FluentValue};
+use minijinja::value::{Kwargs, ValueKind};
+use minijinja::{Environment, Error, ErrorKind, HtmlEscape, State, Value};
+use unic_langid::LanguageIdentifier;
+
+use crate::{AvailableLocales, DynLoader, FluentLoader, TextDirection};
+
+/// The keyword argument of `fluent` that names an attribute of the message.
+const ATTR_KEY: &str = "attr";
+/// The keyword argument that overrides the language of the context.
+const LANG_KEY: &str = "lang";
+/// The keyword argument of `fluent` that overrides whether the message is
+/// HTML-escaped.
+const ESCAPE_KEY: &str = "escape";
+
+/// The names that [`FluentLoader::add_to_environment_with_names`] registers
+/// the functions, filter and test of a loader under.
+///
+/// ```
+/// use fluent_templates::{ArcLoader, FluentLoader, MinijinjaNames};
+/// use unic_langid::langid;
+///
+/// let loader = ArcLoader::builder("./tests/locales", langid!("en-US")).build().unwrap();
+/// let mut env = minijinja::Environment::new();
+/// let names = MinijinjaNames {
+///     function: "t",
+///     filter: "t",
+///     ..MinijinjaNames::default()
+/// };
+/// FluentLoader::new(loader).add_to_environment_with_names(&mut env, &names);
+///
+/// let context = minijinja::context! { lang => "fr" };
+/// assert_eq!(
+///     "Bonjour le monde!",
+///     env.render_str(r#"{{ "hello-world" | t }}"#, context).unwrap()
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MinijinjaNames {
+    /// The function that formats a message, `fluent` by default, e.g.
+    /// `{{ fluent("hello-world") }}`.
+    pub function: &'static str,
+    /// The filter that formats a message, `fluent` by default, e.g.
+    /// `{{ "hello-world" | fluent(lang="fr") }}`.
+    pub filter: &'static str,
+    /// The test of whether a message can be found, `translated` by default,
+    /// e.g. `{% if "summer-sale" is translated %}`.
+    pub test: &'static str,
+    /// The function that returns the text direction of a language,
+    /// `fluent_dir` by default, see [`TextDirection`].
+    pub dir: &'static str,
+    /// The function that lists the locales of the loader, `fluent_locales`
+    /// by default, see [`AvailableLocales`].
+    pub locales: &'static str,
+}
+
+impl Default for MinijinjaNames {
+    fn default() -> Self {
+        Self {
+            function: "fluent",
+            filter: "fluent",
+            test: "translated",
+            dir: "fluent_dir",
+            locales: "fluent_locales",
+        }
+    }
+}
+
+/// Converts a keyword argument to the value of a Fluent variable, or `None`
+/// if it's missing, e.g. from a variable that isn't in the context, so that
+/// the variable is left unset.
+fn value_to_fluent(key: &str, value: Value) -> Result<Option<FluentValue<'static>>, Error> {
+    Ok(Some(match value.kind() {
+        ValueKind::Number => match value.as_i64() {
+            Some(n) => FluentValue::from(n),
+            None => FluentValue::from(f64::try_from(value)?),
+        },
+        ValueKind::String => {
+            FluentValue::String(value.as_str().unwrap_or_default().to_owned().into())
+        }
+        // Matches the `[true]` and `[false]` variants of select expressions.
+        ValueKind::Bool => FluentValue::String(value.is_true().to_string().into()),
+        ValueKind::Undefined | ValueKind::None => return Ok(None),
+        _ => {
+            return Err(Error::new(
+                ErrorKind::InvalidOperation,
+                format!("`{key}` must be a string, number or boolean"),
+            ))
+        }
+    }))
+}
+
+impl<L> FluentLoader<L> {
+    /// Returns the language given by the `lang` argument, or else the
+    /// language variable of the context, or else the default language.
+    fn minijinja_lang(
+        &self,
+        state: &State,
+        lang: Option<&str>,
+    ) -> Result<LanguageIdentifier, Error> {
+        let invalid = |name: &str| {
+            Error::new(
+                ErrorKind::InvalidOperation,
+                format!("`{name}` must be a valid unicode language identifier"),
+            )
+        };
+        if let Some(lang) = lang {
+            return lang.parse().map_err(|_| invalid(LANG_KEY));
+        }
+        match state
+            .lookup(&self.lang_variable)
+            .filter(|lang| !lang.is_undefined() && !lang.is_none())
+        {
+            Some(lang) => lang
+                .as_str()
+                .and_then(|lang| lang.parse().ok())
+                .ok_or_else(|| invalid(&self.lang_variable)),
+            None => self.default_lang.clone().ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidOperation,
+                    format!(
+                        "`{}` isn't set in the context and there's no default language",
+                        self.lang_variable
+                    ),
+                )
+            }),
+        }
+    }
+}
+
+impl<L: DynLoader> FluentLoader<L> {
+    fn minijinja_fluent(&self, state: &State, id: &str, kwargs: Kwargs) -> Result<Value, Error> {
+        let lang = self.minijinja_lang(state, kwargs.get(LANG_KEY)?)?;
+        let id = match kwargs.get::<Option<&str>>(ATTR_KEY)? {
+            Some(attr) => Cow::Owned(format!("{id}.{attr}")),
+            None => Cow::Borrowed(id),
+        };
+        let escape = kwargs.get::<Option<bool>>(ESCAPE_KEY)?.or(self.escape);
+
+        let mut args = FluentArgs::new();
+        for key in kwargs.args() {
+            if key == LANG_KEY || key == ATTR_KEY || key == ESCAPE_KEY {
+                continue;
+            }
+            if let Some(value) = value_to_fluent(key, kwargs.get(key)?)? {
+                args.set(heck::ToKebabCase::to_kebab_case(key), value);
+            }
+        }
+        kwargs.assert_all_used()?;
+
+        let response = self
+            .lookup_for_template(&lang, &id, Some(&args))
+            .map_err(|error| {
+                Error::new(ErrorKind::InvalidOperation, error.to_string()).with_source(error)
+            })?;
+        // Without an escape setting MiniJinja's autoescaping decides, and
+        // with one the message is marked as safe so it's only escaped once.
+        Ok(match escape {
+            Some(true) => Value::from_safe_string(HtmlEscape(&response).to_string()),
+            Some(false) => Value::from_safe_string(response.into_owned()),
+            None => Value::from(response.into_owned()),
+        })
+    }
+}
+
+impl<L: DynLoader + Send + Sync + 'static> FluentLoader<L> {
+    /// Converts this into a MiniJinja function that formats a message, which
+    /// can also be registered as a filter, e.g.
+    /// `{{ fluent("greeting", name=user) }}` or
+    /// `{{ "login-input" | fluent(attr="placeholder") }}`.
+    ///
+    /// The language is taken from the `lang` argument, otherwise the
+    /// language variable of the context, see
+    /// [`with_lang_variable`](Self::with_lang_variable), otherwise the
+    /// default language. Other keyword arguments are passed to the message,
+    /// converted from `snake_case` to `kebab-case`.
+    ///
+    /// ```
+    /// use fluent_templates::{ArcLoader, FluentLoader};
+    /// use unic_langid::langid;
+    ///
+    /// let loader = ArcLoader::builder("./tests/locales", langid!("en-US")).build().unwrap();
+    /// let mut env = minijinja::Environment::new();
+    /// env.add_function("fluent", FluentLoader::new(loader).into_minijinja_fn());
+    ///
+    /// let context = minijinja::context! { lang => "fr" };
+    /// assert_eq!(
+    ///     "Bonjour le monde!",
+    ///     env.render_str(r#"{{ fluent("hello-world") }}"#, context).unwrap()
+    /// );
+    /// ```
+    pub fn into_minijinja_fn(
+        self,
+    ) -> impl Fn(&State, &str, Kwargs) -> Result<Value, Error> + Send + Sync + 'static {
+        move |state: &State, id: &str, kwargs: Kwargs| self.minijinja_fluent(state, id, kwargs)
+    }
+
+    /// Converts this into a MiniJinja test of whether a message can be found
+    /// for the language of the context, or the language given as its
+    /// argument, including in its fallback languages, e.g.
+    /// `{% if "summer-sale" is translated %}` or
+    /// `{% if "summer-sale" is translated("fr") %}`.
+    pub fn into_minijinja_test(
+        self,
+    ) -> impl Fn(&State, &str, Option<&str>) -> Result<bool, Error> + Send + Sync + 'static {
+        move |state: &State, id: &str, lang: Option<&str>| {
+            let lang = self.minijinja_lang(state, lang)?;
+            Ok(self.loader.has(&lang, id))
+        }
+    }
+}
+
+impl<L: DynLoader + Clone + Send + Sync + 'static> FluentLoader<L> {
+    /// Registers the functions, filter and test of this loader with `env`:
+    /// the `fluent` function and filter, the `translated` test, and the
+    /// `fluent_dir` and `fluent_locales` functions, see
+    /// [`text_direction`](Self::text_direction) and
+    /// [`available_locales`](Self::available_locales).
+    ///
+    /// ```
+    /// use fluent_templates::{ArcLoader, FluentLoader};
+    /// use unic_langid::langid;
+    ///
+    /// let loader = ArcLoader::builder("./tests/locales", langid!("en-US")).build().unwrap();
+    /// let mut env = minijinja::Environment::new();
+    /// FluentLoader::new(loader).add_to_environment(&mut env);
+    ///
+    /// let template = r#"<html dir="{{ fluent_dir() }}">{% if "hello-world" is translated %}{{ "hello-world" | fluent }}{% endif %}</html>"#;
+    /// assert_eq!(
+    ///     r#"<html dir="ltr">Bonjour le monde!</html>"#,
+    ///     env.render_str(template, minijinja::context! { lang => "fr" }).unwrap()
+    /// );
+    /// ```
+    pub fn add_to_environment(self, env: &mut Environment<'_>) {
+        self.add_to_environment_with_names(env, &MinijinjaNames::default());
+    }
+
+    /// Registers the functions, filter and test of this loader with `env`,
+    /// like [`add_to_environment`](Self::add_to_environment), under `names`.
+    pub fn add_to_environment_with_names(self, env: &mut Environment<'_>, names: &MinijinjaNames) {
+        env.add_function(names.function, self.clone().into_minijinja_fn());
+        env.add_filter(names.filter, self.clone().into_minijinja_fn());
+        env.add_test(names.test, self.clone().into_minijinja_test());
+        env.add_function(names.dir, self.clone().text_direction().into_minijinja_fn());
+        env.add_function(names.locales, self.available_locales().into_minijinja_fn());
+    }
+}
+
+impl<L: DynLoader + Send + Sync + 'static> TextDirection<L> {
+    /// Converts this into a MiniJinja function that returns the text
+    /// direction of the language given by its `lang` argument, otherwise the
+    /// language of the context, e.g. `<html dir="{{ fluent_dir() }}">`.
+    pub fn into_minijinja_fn(
+        self,
+    ) -> impl Fn(&State, Kwargs) -> Result<String, Error> + Send + Sync + 'static {
+        move |state: &State, kwargs: Kwargs| {
+            let lang = self.0.minijinja_lang(state, kwargs.get(LANG_KEY)?)?;
+            kwargs.assert_all_used()?;
+            Ok(self.0.loader.dir(&lang).to_owned())
+        }
+    }
+}
+
+impl<L: DynLoader + Send + Sync + 'static> AvailableLocales<L> {
+    /// Converts this into a MiniJinja function that returns the locales of
+    /// the loader, e.g.
+    /// `{% for locale in fluent_locales() %}{{ locale.name }}{% endfor %}`.
+    pub fn into_minijinja_fn(self) -> impl Fn() -> Value + Send + Sync + 'static {
+        move || Value::from_serialize(self.json())
+    }
+}
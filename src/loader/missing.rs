@@ -0,0 +1,103 @@
+use std::fmt;
+use std::sync::Arc;
+
+use unic_langid::LanguageIdentifier;
+
+/// Produces the text to use for a message that a loader couldn't resolve.
+///
+/// This is implemented for closures taking the same arguments as
+/// [`MissingMessageHandler::handle`], so it can be used to log or count
+/// missing messages, or to show markers for them during development.
+///
+/// ```
+/// use fluent_templates::{ArcLoader, DynLoader, OnMissing};
+/// use unic_langid::{langid, LanguageIdentifier};
+///
+/// let loader = ArcLoader::builder("./tests/locales", langid!("en-US"))
+///     .on_missing(OnMissing::handler(
+///         |lang: &LanguageIdentifier, text_id: &str, _fallback_attempted: bool| {
+///             format!("[{lang}:{text_id}]")
+///         },
+///     ))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!("[fr:missing]", loader.lookup(&langid!("fr"), "missing"));
+/// ```
+pub trait MissingMessageHandler: Send + Sync {
+    /// Returns the text to use in place of `text_id`, which couldn't be
+    /// found for `lang`. `fallback_attempted` is `true` if languages other
+    /// than `lang` were searched as well.
+    fn handle(&self, lang: &LanguageIdentifier, text_id: &str, fallback_attempted: bool) -> String;
+}
+
+impl<F> MissingMessageHandler for F
+where
+    F: Fn(&LanguageIdentifier, &str, bool) -> String + Send + Sync,
+{
+    fn handle(&self, lang: &LanguageIdentifier, text_id: &str, fallback_attempted: bool) -> String {
+        self(lang, text_id, fallback_attempted)
+    }
+}
+
+/// What a loader returns from the infallible lookup methods, such as
+/// [`DynLoader::lookup`], when a message can't be found in any language.
+///
+/// The fallible methods, such as [`DynLoader::try_lookup`] and
+/// [`DynLoader::lookup_result`], are unaffected and always report the failure
+/// to the caller.
+///
+/// [`DynLoader::lookup`]: crate::DynLoader::lookup
+/// [`DynLoader::try_lookup`]: crate::DynLoader::try_lookup
+/// [`DynLoader::lookup_result`]: crate::DynLoader::lookup_result
+#[derive(Clone, Default)]
+pub enum OnMissing {
+    /// Return `"Unknown localization {text_id}"`.
+    #[default]
+    Placeholder,
+    /// Panic when debug assertions are enabled, otherwise behave like
+    /// [`OnMissing::Placeholder`].
+    DebugPanic,
+    /// Always panic.
+    Panic,
+    /// Return the text produced by a [`MissingMessageHandler`].
+    Handler(Arc<dyn MissingMessageHandler>),
+}
+
+impl OnMissing {
+    /// Creates an [`OnMissing::Handler`] from `handler`.
+    pub fn handler(handler: impl MissingMessageHandler + 'static) -> Self {
+        Self::Handler(Arc::new(handler))
+    }
+
+    /// Produces the text to use in place of `text_id`, which couldn't be
+    /// found for `lang`.
+    pub(crate) fn resolve(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        fallback_attempted: bool,
+    ) -> String {
+        match self {
+            Self::Placeholder => format!("Unknown localization {text_id}"),
+            Self::DebugPanic if !cfg!(debug_assertions) => {
+                format!("Unknown localization {text_id}")
+            }
+            Self::DebugPanic | Self::Panic => {
+                panic!("Unknown localization {text_id} for language {lang}")
+            }
+            Self::Handler(handler) => handler.handle(lang, text_id, fallback_attempted),
+        }
+    }
+}
+
+impl fmt::Debug for OnMissing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Placeholder => f.write_str("Placeholder"),
+            Self::DebugPanic => f.write_str("DebugPanic"),
+            Self::Panic => f.write_str("Panic"),
+            Self::Handler(_) => f.write_str("Handler(..)"),
+        }
+    }
+}
@@ -1,7 +1,10 @@
-use crate::Loader;
-use fluent_bundle::FluentValue;
+use super::{LoaderStats, LocalizedMessage, OnMissing};
+use crate::error::{LoaderFailure, LookupError};
+use crate::DynLoader;
+use fluent_bundle::FluentArgs;
 use std::borrow::Cow;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
 
 pub use unic_langid::LanguageIdentifier;
 
@@ -17,11 +20,14 @@ pub use unic_langid::LanguageIdentifier;
 /// allows you to query multiple localization sources from one single source.
 ///
 /// Note that a [`M̀ultiloader`] is most useful where each of your fluent modules
-/// is specially namespaced to avoid name collisions.
+/// is specially namespaced to avoid name collisions, e.g. with the `prefix`
+/// option of `static_loader!` or [`ArcLoaderBuilder::prefix`].
+///
+/// [`ArcLoaderBuilder::prefix`]: crate::ArcLoaderBuilder::prefix
 ///
 /// # Usage
 /// ```rust
-/// use fluent_templates::{ArcLoader, StaticLoader, MultiLoader, Loader};
+/// use fluent_templates::{ArcLoader, StaticLoader, MultiLoader, DynLoader};
 /// use unic_langid::{LanguageIdentifier, langid};
 ///
 /// const US_ENGLISH: LanguageIdentifier = langid!("en-US");
@@ -43,10 +49,10 @@ pub use unic_langid::LanguageIdentifier;
 ///         .build()
 ///         .unwrap();
 ///
-///     let mut multiloader = MultiLoader::from_iter([
-///         Box::new(&*LOCALES) as Box<dyn Loader>,
-///     ]);
-///     multiloader.push_back(Box::new(cn_loader) as Box<dyn Loader>);
+///     let multiloader = MultiLoader::builder()
+///         .with(&*LOCALES)
+///         .with(cn_loader)
+///         .build();
 ///     assert_eq!("Hello World!", multiloader.lookup(&US_ENGLISH, "hello-world"));
 ///     assert_eq!("儿", multiloader.lookup(&CHINESE, "exists"));
 /// }
@@ -54,8 +60,117 @@ pub use unic_langid::LanguageIdentifier;
 ///
 /// # Order of search
 /// The one that is inserted first is also the one searched first.
-pub struct MultiLoader {
-    loaders: VecDeque<Box<dyn Loader>>,
+///
+/// # Namespaces
+/// A loader added with [`push_namespaced`](MultiLoader::push_namespaced) is
+/// only searched for IDs in its namespace, such as `payments::invoice-title`,
+/// which are looked up in it directly without searching the other loaders.
+/// ```rust
+/// use fluent_templates::{ArcLoader, DynLoader, MultiLoader};
+/// use unic_langid::langid;
+///
+/// let mut multiloader = MultiLoader::new();
+/// let payments = ArcLoader::builder("./tests/locales", langid!("en-US")).build().unwrap();
+/// multiloader.push_namespaced("payments", Box::new(payments));
+/// assert_eq!(
+///     "Hello World!",
+///     multiloader.lookup(&langid!("en-US"), "payments::hello-world")
+/// );
+/// assert!(!multiloader.has(&langid!("en-US"), "hello-world"));
+/// ```
+///
+/// # Loader types
+/// The loaders are `Box<dyn DynLoader + Send + Sync>` by default, so that
+/// loaders of any thread-safe type can be combined, and the [`MultiLoader`]
+/// can be shared between threads, e.g. in a `static`. Loaders of a single
+/// type can be used instead, so that lookups are statically dispatched, or
+/// `Box<dyn DynLoader>` for loaders that aren't thread-safe.
+/// ```rust
+/// use fluent_templates::{ArcLoader, DynLoader, MultiLoader};
+/// use unic_langid::langid;
+///
+/// let build = |fallback| ArcLoader::builder("./tests/locales", fallback).build().unwrap();
+/// let multiloader = MultiLoader::from_iter([build(langid!("en-US")), build(langid!("zh-CN"))]);
+/// std::thread::spawn(move || {
+///     assert_eq!("儿", multiloader.lookup(&langid!("zh-CN"), "exists"));
+/// })
+/// .join()
+/// .unwrap();
+/// ```
+pub struct MultiLoader<L = Box<dyn DynLoader + Send + Sync>> {
+    loaders: VecDeque<Layer<L>>,
+    namespaces: BTreeMap<String, L>,
+    on_missing: OnMissing,
+    locales: LocaleMerge,
+}
+
+/// Which languages a [`MultiLoader`] reports as its
+/// [`locales`](DynLoader::locales), from those of its loaders.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LocaleMerge {
+    /// Every language that any loader has.
+    #[default]
+    Union,
+    /// Only the languages that every loader that's searched for them,
+    /// including the namespaced ones, has, e.g. for a language picker that
+    /// shouldn't offer languages that some messages aren't translated to.
+    Intersection,
+}
+
+/// A loader of a [`MultiLoader`] that isn't namespaced, the name it was
+/// tagged with, if any, and the languages it's searched for, or none if it's
+/// searched for every language.
+struct Layer<L> {
+    name: Option<String>,
+    langs: Vec<LanguageIdentifier>,
+    loader: L,
+}
+
+impl<L> From<L> for Layer<L> {
+    fn from(loader: L) -> Self {
+        Self {
+            name: None,
+            langs: Vec::new(),
+            loader,
+        }
+    }
+}
+
+/// Returns whether a loader that's searched for `served` languages, or every
+/// language if there are none, is searched for any of `langs`. A served
+/// language also covers its more specific forms, e.g. `ja` covers `ja-JP`.
+fn serves(served: &[LanguageIdentifier], langs: &[LanguageIdentifier]) -> bool {
+    served.is_empty()
+        || langs
+            .iter()
+            .any(|lang| served.iter().any(|own| own.matches(lang, true, false)))
+}
+
+/// A message found by [`MultiLoader::lookup_traced`], and the loader that it
+/// was found in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TracedMessage<'a> {
+    /// The formatted message.
+    pub value: String,
+    /// The index of the loader, or `None` if it's namespaced.
+    pub index: Option<usize>,
+    /// The name the loader was tagged with, or its namespace.
+    pub name: Option<&'a str>,
+}
+
+/// Separates the namespace of a message ID from the ID in its loader. `.`
+/// can't be used, as it separates the names of attributes.
+const NAMESPACE_SEPARATOR: &str = "::";
+
+impl<L> Default for MultiLoader<L> {
+    fn default() -> Self {
+        Self {
+            loaders: VecDeque::new(),
+            namespaces: BTreeMap::new(),
+            on_missing: OnMissing::default(),
+            locales: LocaleMerge::default(),
+        }
+    }
 }
 
 impl MultiLoader {
@@ -64,67 +179,652 @@ impl MultiLoader {
         Self::default()
     }
 
+    /// Creates a [`MultiLoaderBuilder`], which boxes the loaders added to
+    /// it, so that loaders of different types can be combined without
+    /// casting them.
+    pub fn builder() -> MultiLoaderBuilder {
+        MultiLoaderBuilder::default()
+    }
+}
+
+/// A builder for a [`MultiLoader`] of loaders of different types, which are
+/// searched in the order they're added.
+///
+/// ```rust
+/// use fluent_templates::{ArcLoader, DynLoader, MultiLoader};
+/// use unic_langid::langid;
+///
+/// fluent_templates::static_loader! {
+///     static LOCALES = {
+///         locales: "./tests/locales",
+///         fallback_language: "en-US",
+///     };
+/// }
+///
+/// let payments = ArcLoader::builder("./tests/locales", langid!("en-US")).build().unwrap();
+/// let multiloader = MultiLoader::builder()
+///     .with(&*LOCALES)
+///     .with_namespaced("payments", payments)
+///     .build();
+/// assert_eq!("Hello World!", multiloader.lookup(&langid!("en-US"), "hello-world"));
+/// ```
+#[derive(Default)]
+pub struct MultiLoaderBuilder {
+    loader: MultiLoader,
+}
+
+impl MultiLoaderBuilder {
+    /// Adds a loader, which is searched after the loaders added before it.
+    pub fn with(mut self, loader: impl DynLoader + Send + Sync + 'static) -> Self {
+        self.loader.push_back(Box::new(loader));
+        self
+    }
+
+    /// Adds a loader that's only searched for `langs`, see
+    /// [`MultiLoader::push_for_langs`].
+    pub fn with_for_langs(
+        mut self,
+        langs: &[LanguageIdentifier],
+        loader: impl DynLoader + Send + Sync + 'static,
+    ) -> Self {
+        self.loader.push_for_langs(langs, Box::new(loader));
+        self
+    }
+
+    /// Adds a loader for the IDs in `namespace`, see
+    /// [`MultiLoader::push_namespaced`].
+    pub fn with_namespaced(
+        mut self,
+        namespace: impl Into<String>,
+        loader: impl DynLoader + Send + Sync + 'static,
+    ) -> Self {
+        self.loader.push_namespaced(namespace, Box::new(loader));
+        self
+    }
+
+    /// Builds the [`MultiLoader`].
+    pub fn build(self) -> MultiLoader {
+        self.loader
+    }
+}
+
+impl<L: DynLoader> MultiLoader<L> {
     /// Creates a [`MultiLoader`] from an iterator of loaders.
-    pub fn from_iter(iter: impl IntoIterator<Item = Box<dyn Loader>>) -> Self {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_iter(iter: impl IntoIterator<Item = L>) -> Self {
         Self {
-            loaders: iter.into_iter().collect(),
+            loaders: iter.into_iter().map(Layer::from).collect(),
+            ..Self::default()
         }
     }
 
+    /// Sets what the infallible lookup methods return when none of the
+    /// loaders can find a message, see [`OnMissing`]. The settings of the
+    /// individual loaders are not used.
+    pub fn with_on_missing(mut self, on_missing: OnMissing) -> Self {
+        self.on_missing = on_missing;
+        self
+    }
+
+    /// Sets which languages are reported as the loader's
+    /// [`locales`](DynLoader::locales), see [`LocaleMerge`]. Each language is
+    /// only reported once, in the order of the loaders that have it.
+    pub fn with_locale_merge(mut self, merge: LocaleMerge) -> Self {
+        self.locales = merge;
+        self
+    }
+
     /// Pushes a loader in front of all the others in terms of precedence.
-    pub fn push_front(&mut self, loader: Box<dyn Loader>) {
-        self.loaders.push_front(loader);
+    pub fn push_front(&mut self, loader: L) {
+        self.loaders.push_front(loader.into());
     }
 
     /// Pushes a loader at the back in terms of precedence.
-    pub fn push_back(&mut self, loader: Box<dyn Loader>) {
-        self.loaders.push_back(loader);
+    pub fn push_back(&mut self, loader: L) {
+        self.loaders.push_back(loader.into());
     }
 
-    /// Pushes a loader at the back in terms of precedence.
-    pub fn remove(&mut self, idx: usize) -> Option<Box<dyn Loader>> {
-        self.loaders.remove(idx)
+    /// Pushes a loader at the back in terms of precedence, which is only
+    /// searched for `langs` and their more specific forms, e.g. a resource
+    /// pack for `ja` and `ko` that's also used for `ja-JP`. Lookups in other
+    /// languages skip it entirely.
+    pub fn push_for_langs(&mut self, langs: &[LanguageIdentifier], loader: L) {
+        self.loaders.push_back(Layer {
+            langs: langs.to_vec(),
+            ..loader.into()
+        });
     }
-}
 
-impl Default for MultiLoader {
-    fn default() -> Self {
-        Self {
-            loaders: VecDeque::default(),
+    /// Inserts a loader at `idx` in terms of precedence, so that it's
+    /// searched after the first `idx` loaders.
+    ///
+    /// # Panics
+    /// Panics if `idx` is greater than the number of loaders.
+    pub fn insert(&mut self, idx: usize, loader: L) {
+        self.loaders.insert(idx, loader.into());
+    }
+
+    /// Removes the loader at `idx`, returning it if there was one.
+    pub fn remove(&mut self, idx: usize) -> Option<L> {
+        self.loaders.remove(idx).map(|layer| layer.loader)
+    }
+
+    /// Replaces the loader at `idx` with `loader`, e.g. one that was rebuilt
+    /// after its resources changed, returning the previous one, or `None`
+    /// without adding `loader` if there's no loader at `idx`. The new loader
+    /// keeps the position, name and languages of the previous one, so no
+    /// lookup sees the `MultiLoader` without either of them.
+    ///
+    /// A `MultiLoader` that's shared across the application, e.g. in an
+    /// `Arc<RwLock<MultiLoader>>`, only has to be locked for writing while
+    /// the loaders are swapped, and the previous loader can be dropped after
+    /// the lock is released.
+    ///
+    /// ```rust
+    /// use std::sync::RwLock;
+    /// use fluent_templates::{ArcLoader, DynLoader, MultiLoader};
+    /// use unic_langid::langid;
+    ///
+    /// let build = |source: &str| {
+    ///     ArcLoader::builder("./tests/locales", langid!("en-US"))
+    ///         .add_source(langid!("en-US"), source)
+    ///         .duplicates(fluent_templates::Duplicates::Override)
+    ///         .build()
+    ///         .unwrap()
+    /// };
+    /// let mut multiloader = MultiLoader::from_iter([build("hello-world = Hi!")]);
+    /// multiloader.set_name(0, "overrides");
+    /// let multiloader = RwLock::new(multiloader);
+    ///
+    /// let previous = multiloader
+    ///     .write()
+    ///     .unwrap()
+    ///     .replace_named("overrides", build("hello-world = Hey!"));
+    /// assert!(previous.is_some());
+    /// let multiloader = multiloader.read().unwrap();
+    /// assert_eq!("Hey!", multiloader.lookup(&langid!("en-US"), "hello-world"));
+    /// ```
+    pub fn replace(&mut self, idx: usize, loader: L) -> Option<L> {
+        let layer = self.loaders.get_mut(idx)?;
+        Some(std::mem::replace(&mut layer.loader, loader))
+    }
+
+    /// Replaces the first loader tagged with `name` like
+    /// [`replace`](Self::replace).
+    pub fn replace_named(&mut self, name: &str, loader: L) -> Option<L> {
+        self.replace(self.position(name)?, loader)
+    }
+
+    /// Moves the loader at `from` to `to` in terms of precedence, keeping
+    /// its name, e.g. to reorder override layers at run time.
+    ///
+    /// # Panics
+    /// Panics if either index is out of bounds.
+    pub fn reorder(&mut self, from: usize, to: usize) {
+        let layer = self.loaders.remove(from).expect("index out of bounds");
+        self.loaders.insert(to, layer);
+    }
+
+    /// Returns the number of loaders, not including the namespaced ones.
+    pub fn len(&self) -> usize {
+        self.loaders.len()
+    }
+
+    /// Returns whether there are no loaders, not including the namespaced
+    /// ones.
+    pub fn is_empty(&self) -> bool {
+        self.loaders.is_empty()
+    }
+
+    /// Returns the loader at `idx`.
+    pub fn get(&self, idx: usize) -> Option<&L> {
+        self.loaders.get(idx).map(|layer| &layer.loader)
+    }
+
+    /// Returns the loaders in the order they're searched in, with their
+    /// names, not including the namespaced ones.
+    pub fn iter(&self) -> impl Iterator<Item = (Option<&str>, &L)> {
+        self.loaders
+            .iter()
+            .map(|layer| (layer.name.as_deref(), &layer.loader))
+    }
+
+    /// Tags the loader at `idx` with `name`, which is reported by
+    /// [`lookup_traced`](Self::lookup_traced) and can be found with
+    /// [`position`](Self::position). Returns whether there is a loader at
+    /// `idx`.
+    pub fn set_name(&mut self, idx: usize, name: impl Into<String>) -> bool {
+        match self.loaders.get_mut(idx) {
+            Some(layer) => {
+                layer.name = Some(name.into());
+                true
+            }
+            None => false,
         }
     }
+
+    /// Returns the index of the first loader tagged with `name`.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.loaders
+            .iter()
+            .position(|layer| layer.name.as_deref() == Some(name))
+    }
+
+    /// Looks up `text_id` like [`DynLoader::try_lookup_complete`], also
+    /// returning which loader the message was found in, e.g. to debug which
+    /// override layer answered a lookup.
+    ///
+    /// ```rust
+    /// use fluent_templates::{ArcLoader, DynLoader, MultiLoader};
+    /// use unic_langid::langid;
+    ///
+    /// let build = |source: &str| {
+    ///     ArcLoader::builder("./tests/locales", langid!("en-US"))
+    ///         .add_source(langid!("en-US"), source)
+    ///         .duplicates(fluent_templates::Duplicates::Override)
+    ///         .build()
+    ///         .unwrap()
+    /// };
+    /// let mut multiloader = MultiLoader::from_iter([build("hello-world = Hi!")]);
+    /// multiloader.push_back(build(""));
+    /// multiloader.set_name(0, "overrides");
+    ///
+    /// let traced = multiloader
+    ///     .lookup_traced(&langid!("en-US"), "hello-world", None)
+    ///     .unwrap();
+    /// assert_eq!(("Hi!", Some("overrides")), (&*traced.value, traced.name));
+    /// multiloader.reorder(0, 1);
+    /// let traced = multiloader
+    ///     .lookup_traced(&langid!("en-US"), "hello-world", None)
+    ///     .unwrap();
+    /// assert_eq!(("Hello World!", Some(0)), (&*traced.value, traced.index));
+    /// ```
+    pub fn lookup_traced(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<TracedMessage<'_>> {
+        if let Some((namespace, loader, id)) = self.namespaced(text_id) {
+            let value = loader.try_lookup_complete(lang, id, args)?;
+            return Some(TracedMessage {
+                value,
+                index: None,
+                name: Some(namespace),
+            });
+        }
+        let langs = std::slice::from_ref(lang);
+        let layers = self.loaders.iter().enumerate();
+        layers
+            .filter(|(_, layer)| serves(&layer.langs, langs))
+            .find_map(|(index, layer)| {
+                let value = layer.loader.try_lookup_complete(lang, text_id, args)?;
+                Some(TracedMessage {
+                    value,
+                    index: Some(index),
+                    name: layer.name.as_deref(),
+                })
+            })
+    }
+
+    /// Returns the message IDs that more than one loader defines for exactly
+    /// the same language, with the indices of those loaders in order of
+    /// precedence, by language. Only the first of them is ever used, so the
+    /// others are silently shadowed, e.g. when two crates' resource packs
+    /// both define `title`.
+    ///
+    /// Namespaced loaders can't conflict, and loaders that are only searched
+    /// for some languages only conflict in those.
+    pub fn check_conflicts(&self) -> BTreeMap<LanguageIdentifier, BTreeMap<String, Vec<usize>>> {
+        let langs = self
+            .loaders
+            .iter()
+            .flat_map(|layer| layer.loader.locales())
+            .collect::<BTreeSet<_>>();
+        let mut conflicts = BTreeMap::new();
+        for lang in langs {
+            let mut providers = BTreeMap::<String, Vec<usize>>::new();
+            for (index, layer) in self.loaders.iter().enumerate() {
+                if !serves(&layer.langs, std::slice::from_ref(lang)) {
+                    continue;
+                }
+                let ids = layer.loader.message_ids(lang).collect::<BTreeSet<_>>();
+                for id in ids {
+                    providers.entry(id.to_owned()).or_default().push(index);
+                }
+            }
+            providers.retain(|_, indices| indices.len() > 1);
+            if !providers.is_empty() {
+                conflicts.insert(lang.clone(), providers);
+            }
+        }
+        conflicts
+    }
+
+    /// Adds a loader for the IDs in `namespace`, e.g. `payments` for
+    /// `payments::invoice-title`, which is looked up as `invoice-title` in
+    /// `loader`. The loader is only searched for IDs in its namespace, and
+    /// those are only searched for in it, so its messages can't be shadowed
+    /// by, or shadow, those of other loaders. Any loader that the namespace
+    /// already had is replaced.
+    ///
+    /// The languages of the loader are included in
+    /// [`locales`](DynLoader::locales), but its messages and terms aren't
+    /// listed by [`message_ids`](DynLoader::message_ids) and
+    /// [`term_ids`](DynLoader::term_ids).
+    pub fn push_namespaced(&mut self, namespace: impl Into<String>, loader: L) {
+        self.namespaces.insert(namespace.into(), loader);
+    }
+
+    /// Removes the loader of `namespace`, returning it if there was one.
+    pub fn remove_namespaced(&mut self, namespace: &str) -> Option<L> {
+        self.namespaces.remove(namespace)
+    }
+
+    /// Returns the loaders to search for `text_id` in `langs`, and the ID to
+    /// look up in them: only the loader of its namespace if it has one, or
+    /// else every loader that isn't namespaced and is searched for `langs`.
+    fn route<'a: 'l, 'l, 'id>(
+        &'a self,
+        langs: &'l [LanguageIdentifier],
+        text_id: &'id str,
+    ) -> (impl Iterator<Item = &'a L> + 'l, &'id str) {
+        let (loader, layers, text_id) = match self.namespaced(text_id) {
+            Some((_, loader, id)) => (Some(loader), self.loaders.range(0..0), id),
+            None => (None, self.loaders.range(..), text_id),
+        };
+        let layers = layers
+            .filter(move |layer| serves(&layer.langs, langs))
+            .map(|layer| &layer.loader);
+        (loader.into_iter().chain(layers), text_id)
+    }
+
+    /// Returns the namespace of `text_id` and its loader, if it has one, and
+    /// the ID to look up in it.
+    fn namespaced<'a, 'id>(&'a self, text_id: &'id str) -> Option<(&'a str, &'a L, &'id str)> {
+        let (namespace, id) = text_id.split_once(NAMESPACE_SEPARATOR)?;
+        let (namespace, loader) = self.namespaces.get_key_value(namespace)?;
+        Some((namespace, loader, id))
+    }
+
+    /// Returns the loaders that aren't namespaced and are searched for
+    /// `lang`.
+    fn layers(&self, lang: &LanguageIdentifier) -> Vec<&L> {
+        self.loaders
+            .iter()
+            .filter(|layer| serves(&layer.langs, std::slice::from_ref(lang)))
+            .map(|layer| &layer.loader)
+            .collect()
+    }
+
+    /// Returns every loader, including the namespaced ones, with the
+    /// languages they're searched for.
+    fn all_loaders(&self) -> impl Iterator<Item = (&L, &[LanguageIdentifier])> {
+        let layers = self
+            .loaders
+            .iter()
+            .map(|layer| (&layer.loader, &*layer.langs));
+        layers.chain(self.namespaces.values().map(|loader| (loader, &[][..])))
+    }
+
+    fn missing(&self, lang: &LanguageIdentifier, text_id: &str) -> String {
+        let fallback_attempted = self
+            .fallback_chain(lang)
+            .iter()
+            .any(|searched| searched != lang);
+        self.on_missing.resolve(lang, text_id, fallback_attempted)
+    }
+}
+
+impl<L: DynLoader> fmt::Debug for MultiLoader<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultiLoader")
+            .field("loaders", &self.loaders.len())
+            .field("namespaces", &self.namespaces.keys().collect::<Vec<_>>())
+            .field("locales", &super::shared::locale_names(self))
+            .field("on_missing", &self.on_missing)
+            .finish()
+    }
 }
 
-impl crate::Loader for MultiLoader {
+impl<L: DynLoader> crate::DynLoader for MultiLoader<L> {
     fn lookup_complete(
         &self,
-        lang: &unic_langid::LanguageIdentifier,
+        lang: &LanguageIdentifier,
         text_id: &str,
-        args: Option<&std::collections::HashMap<Cow<'static, str>, fluent_bundle::FluentValue>>,
+        args: Option<&FluentArgs>,
     ) -> String {
-        for loader in self.loaders.iter() {
-            if let Some(text) = loader.try_lookup_complete(lang, text_id, args) {
-                return text;
-            }
-        }
-        format!("Unknown localization {text_id}")
+        self.lookup_cow(lang, text_id, args).into_owned()
     }
 
     fn try_lookup_complete(
         &self,
         lang: &LanguageIdentifier,
         text_id: &str,
-        args: Option<&HashMap<Cow<'static, str>, FluentValue>>,
+        args: Option<&FluentArgs>,
     ) -> Option<String> {
-        for loader in self.loaders.iter() {
-            if let Some(text) = loader.try_lookup_complete(lang, text_id, args) {
+        self.try_lookup_cow(lang, text_id, args)
+            .map(Cow::into_owned)
+    }
+
+    fn lookup_cow(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Cow<'_, str> {
+        self.try_lookup_cow(lang, text_id, args)
+            .unwrap_or_else(|| Cow::Owned(self.missing(lang, text_id)))
+    }
+
+    fn try_lookup_cow(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<Cow<'_, str>> {
+        let (loaders, text_id) = self.route(std::slice::from_ref(lang), text_id);
+        for loader in loaders {
+            if let Some(text) = loader.try_lookup_cow(lang, text_id, args) {
                 return Some(text);
             }
         }
         None
     }
 
+    fn lookup_complete_result(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Result<String, LookupError> {
+        if let Some((namespace, loader, id)) = self.namespaced(text_id) {
+            return loader
+                .lookup_complete_result(lang, id, args)
+                .map_err(|error| {
+                    LookupError::AllLoadersFailed(vec![LoaderFailure {
+                        index: None,
+                        name: Some(namespace.to_owned()),
+                        error,
+                    }])
+                });
+        }
+
+        let mut failures = Vec::new();
+        let langs = std::slice::from_ref(lang);
+        let layers = self.loaders.iter().enumerate();
+        for (index, layer) in layers.filter(|(_, layer)| serves(&layer.langs, langs)) {
+            match layer.loader.lookup_complete_result(lang, text_id, args) {
+                Ok(text) => return Ok(text),
+                Err(error) => failures.push(LoaderFailure {
+                    index: Some(index),
+                    name: layer.name.clone(),
+                    error,
+                }),
+            }
+        }
+        if failures.is_empty() {
+            return Err(LookupError::LangNotLoaded(lang.clone()));
+        }
+        Err(LookupError::AllLoadersFailed(failures))
+    }
+
+    fn lookup_langs(
+        &self,
+        langs: &[LanguageIdentifier],
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> String {
+        self.try_lookup_langs(langs, text_id, args)
+            .unwrap_or_else(|| {
+                self.missing(
+                    langs.first().unwrap_or(&LanguageIdentifier::default()),
+                    text_id,
+                )
+            })
+    }
+
+    fn try_lookup_langs(
+        &self,
+        langs: &[LanguageIdentifier],
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        let (mut loaders, text_id) = self.route(langs, text_id);
+        loaders.find_map(|loader| loader.try_lookup_langs(langs, text_id, args))
+    }
+
+    fn lookup_no_default_fallback(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        let (mut loaders, text_id) = self.route(std::slice::from_ref(lang), text_id);
+        loaders.find_map(|loader| loader.lookup_no_default_fallback(lang, text_id, args))
+    }
+
+    fn lookup_into(
+        &self,
+        out: &mut dyn fmt::Write,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> fmt::Result {
+        let (mut loaders, id) = self.route(std::slice::from_ref(lang), text_id);
+        match loaders.find(|loader| loader.has(lang, id)) {
+            Some(loader) => loader.lookup_into(out, lang, id, args),
+            None => out.write_str(&self.missing(lang, text_id)),
+        }
+    }
+
+    fn has(&self, lang: &LanguageIdentifier, text_id: &str) -> bool {
+        let (mut loaders, text_id) = self.route(std::slice::from_ref(lang), text_id);
+        loaders.any(|loader| loader.has(lang, text_id))
+    }
+
+    fn attributes(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+        let (loaders, text_id) = self.route(std::slice::from_ref(lang), text_id);
+        loaders
+            .map(|loader| loader.attributes(lang, text_id))
+            .find(|attributes| !attributes.is_empty())
+            .unwrap_or_default()
+    }
+
+    fn lookup_message(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<LocalizedMessage> {
+        let (mut loaders, text_id) = self.route(std::slice::from_ref(lang), text_id);
+        loaders.find_map(|loader| loader.lookup_message(lang, text_id, args))
+    }
+
+    fn fallback_chain(&self, lang: &LanguageIdentifier) -> Vec<LanguageIdentifier> {
+        let mut chain = Vec::new();
+        for lang in self
+            .all_loaders()
+            .filter(|(_, served)| serves(served, std::slice::from_ref(lang)))
+            .flat_map(|(loader, _)| loader.fallback_chain(lang))
+        {
+            if !chain.contains(&lang) {
+                chain.push(lang);
+            }
+        }
+        chain
+    }
+
     fn locales(&self) -> Box<dyn Iterator<Item = &LanguageIdentifier> + '_> {
-        Box::new(self.loaders.iter().map(|loader| loader.locales()).flatten())
+        let mut locales = Vec::new();
+        for (loader, served) in self.all_loaders() {
+            for lang in loader.locales() {
+                if serves(served, std::slice::from_ref(lang)) && !locales.contains(&lang) {
+                    locales.push(lang);
+                }
+            }
+        }
+        if self.locales == LocaleMerge::Intersection {
+            locales.retain(|lang| {
+                self.all_loaders()
+                    .filter(|(_, served)| serves(served, std::slice::from_ref(*lang)))
+                    .all(|(loader, _)| loader.locales().any(|other| other == *lang))
+            });
+        }
+        Box::new(locales.into_iter())
+    }
+
+    fn message_ids(&self, lang: &LanguageIdentifier) -> Box<dyn Iterator<Item = &str> + '_> {
+        let layers = self.layers(lang);
+        let lang = lang.clone();
+        Box::new(
+            layers
+                .into_iter()
+                .flat_map(move |loader| loader.message_ids(&lang)),
+        )
+    }
+
+    fn required_args(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+        let (mut loaders, text_id) = self.route(std::slice::from_ref(lang), text_id);
+        loaders
+            .find(|loader| loader.has(lang, text_id))
+            .map(|loader| loader.required_args(lang, text_id))
+            .unwrap_or_default()
+    }
+
+    fn term_ids(&self, lang: &LanguageIdentifier) -> Box<dyn Iterator<Item = &str> + '_> {
+        let layers = self.layers(lang);
+        let lang = lang.clone();
+        Box::new(
+            layers
+                .into_iter()
+                .flat_map(move |loader| loader.term_ids(&lang)),
+        )
+    }
+
+    fn stats(&self) -> LoaderStats {
+        let stats = self
+            .all_loaders()
+            .map(|(loader, _)| loader.stats())
+            .collect::<Vec<_>>();
+        LoaderStats::from_loader(self, |lang| {
+            stats
+                .iter()
+                .filter_map(|stats| stats.locales.get(lang))
+                .map(|stats| stats.resources)
+                .sum()
+        })
+    }
+
+    #[cfg(debug_assertions)]
+    fn format_term(
+        &self,
+        lang: &LanguageIdentifier,
+        term_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        self.layers(lang)
+            .into_iter()
+            .find_map(|loader| loader.format_term(lang, term_id, args))
     }
 }
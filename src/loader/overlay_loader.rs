@@ -0,0 +1,176 @@
+use std::collections::BTreeSet;
+
+use fluent_bundle::FluentArgs;
+use unic_langid::LanguageIdentifier;
+
+use super::{DynLoader, LoaderStats};
+use crate::error::LookupError;
+
+/// A loader that layers `overrides` on top of a `base` loader, e.g. to ship
+/// translations in the binary with a [`StaticLoader`] while allowing them to
+/// be fixed at run-time with an [`ArcLoader`].
+///
+/// Unlike a [`MultiLoader`], which searches each loader's whole fallback
+/// chain in turn, the layers are searched together one language at a time:
+/// a message is taken from `overrides` if it has it for a language, then from
+/// `base`, before moving on to the next language in the fallback chain. So an
+/// override in the fallback language doesn't hide a translation in `base`.
+///
+/// ```
+/// use fluent_templates::{static_loader, ArcLoader, DynLoader, OverlayLoader};
+/// use unic_langid::langid;
+///
+/// static_loader! {
+///     static LOCALES = {
+///         locales: "./tests/locales",
+///         fallback_language: "en-US",
+///     };
+/// }
+///
+/// let overrides = ArcLoader::builder("./tests/locales", langid!("en-US"))
+///     .build()
+///     .unwrap();
+/// let loader = OverlayLoader::new(&*LOCALES, overrides);
+/// assert_eq!("Bonjour le monde!", loader.lookup(&langid!("fr"), "hello-world"));
+/// ```
+///
+/// [`StaticLoader`]: crate::StaticLoader
+/// [`ArcLoader`]: crate::ArcLoader
+/// [`MultiLoader`]: crate::MultiLoader
+pub struct OverlayLoader<B, O> {
+    base: B,
+    overrides: O,
+}
+
+impl<B: DynLoader, O: DynLoader> OverlayLoader<B, O> {
+    /// Layers `overrides` on top of `base`.
+    pub fn new(base: B, overrides: O) -> Self {
+        Self { base, overrides }
+    }
+
+    /// Returns the base loader.
+    pub fn base(&self) -> &B {
+        &self.base
+    }
+
+    /// Returns the loader whose messages take precedence.
+    pub fn overrides(&self) -> &O {
+        &self.overrides
+    }
+
+    /// Returns the base loader and the overrides.
+    pub fn into_inner(self) -> (B, O) {
+        (self.base, self.overrides)
+    }
+}
+
+impl<B: DynLoader, O: DynLoader> DynLoader for OverlayLoader<B, O> {
+    fn lookup_complete(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> String {
+        self.try_lookup_complete(lang, text_id, args)
+            .unwrap_or_else(|| self.base.lookup_complete(lang, text_id, args))
+    }
+
+    fn try_lookup_complete(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        self.fallback_chain(lang).iter().find_map(|lang| {
+            self.overrides
+                .lookup_no_default_fallback(lang, text_id, args)
+                .or_else(|| self.base.lookup_no_default_fallback(lang, text_id, args))
+        })
+    }
+
+    fn lookup_complete_result(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Result<String, LookupError> {
+        match self.try_lookup_complete(lang, text_id, args) {
+            Some(text) => Ok(text),
+            None => self.base.lookup_complete_result(lang, text_id, args),
+        }
+    }
+
+    fn has(&self, lang: &LanguageIdentifier, text_id: &str) -> bool {
+        self.overrides.has(lang, text_id) || self.base.has(lang, text_id)
+    }
+
+    fn attributes(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+        let mut attributes = self.overrides.attributes(lang, text_id);
+        for attribute in self.base.attributes(lang, text_id) {
+            if !attributes.contains(&attribute) {
+                attributes.push(attribute);
+            }
+        }
+        attributes
+    }
+
+    fn fallback_chain(&self, lang: &LanguageIdentifier) -> Vec<LanguageIdentifier> {
+        let available = self.locales().collect::<Vec<_>>();
+        let mut chain = crate::languages::negotiate_languages(&[lang], &available, None)
+            .into_iter()
+            .map(|lang| (*lang).clone())
+            .collect::<Vec<_>>();
+        for lang in self.base.fallback_chain(lang) {
+            if !chain.contains(&lang) {
+                chain.push(lang);
+            }
+        }
+        chain
+    }
+
+    fn locales(&self) -> Box<dyn Iterator<Item = &LanguageIdentifier> + '_> {
+        let locales = self
+            .base
+            .locales()
+            .chain(self.overrides.locales())
+            .collect::<BTreeSet<_>>();
+        Box::new(locales.into_iter())
+    }
+
+    fn message_ids(&self, lang: &LanguageIdentifier) -> Box<dyn Iterator<Item = &str> + '_> {
+        let ids = self
+            .base
+            .message_ids(lang)
+            .chain(self.overrides.message_ids(lang))
+            .collect::<BTreeSet<_>>();
+        Box::new(ids.into_iter())
+    }
+
+    fn required_args(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+        if self.overrides.has(lang, text_id) {
+            self.overrides.required_args(lang, text_id)
+        } else {
+            self.base.required_args(lang, text_id)
+        }
+    }
+
+    fn term_ids(&self, lang: &LanguageIdentifier) -> Box<dyn Iterator<Item = &str> + '_> {
+        let ids = self
+            .base
+            .term_ids(lang)
+            .chain(self.overrides.term_ids(lang))
+            .collect::<BTreeSet<_>>();
+        Box::new(ids.into_iter())
+    }
+
+    fn stats(&self) -> LoaderStats {
+        let stats = [self.base.stats(), self.overrides.stats()];
+        LoaderStats::from_loader(self, |lang| {
+            stats
+                .iter()
+                .filter_map(|stats| stats.locales.get(lang))
+                .map(|stats| stats.resources)
+                .sum()
+        })
+    }
+}
@@ -0,0 +1,290 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use fluent_bundle::{FluentArgs, FluentValue};
+use unic_langid::LanguageIdentifier;
+
+use super::{DynLoader, LoaderStats, LocalizedMessage};
+use crate::error::LookupError;
+
+/// Marks the start of an argument's value in formatted text.
+const ARG_START: char = '\u{E000}';
+/// Marks the end of an argument's value in formatted text.
+const ARG_END: char = '\u{E001}';
+
+const UPPERCASE: [char; 26] = [
+    'Å', 'Ɓ', 'Ç', 'Ð', 'É', 'Ƒ', 'Ĝ', 'Ĥ', 'Î', 'Ĵ', 'Ķ', 'Ļ', 'Ṁ', 'Ñ', 'Ö', 'Þ', 'Ǫ', 'Ŕ', 'Š',
+    'Ţ', 'Û', 'Ṽ', 'Ŵ', 'Ẋ', 'Ý', 'Ž',
+];
+const LOWERCASE: [char; 26] = [
+    'å', 'ƀ', 'ç', 'ð', 'é', 'ƒ', 'ĝ', 'ĥ', 'î', 'ĵ', 'ķ', 'ļ', 'ṁ', 'ñ', 'ö', 'þ', 'ǫ', 'ŕ', 'š',
+    'ţ', 'û', 'ṽ', 'ŵ', 'ẋ', 'ý', 'ž',
+];
+
+/// A loader that pseudo-localizes every message of another loader, for
+/// finding hard-coded strings and layouts that can't fit longer text.
+///
+/// Latin letters are replaced with accented versions and every message is
+/// bracketed and padded, while the values of arguments and any unresolved
+/// `{ $placeables }` are left untouched.
+///
+/// ```
+/// use fluent_templates::{static_loader, DynLoader, LoaderExt, PseudoLoader};
+/// use unic_langid::langid;
+///
+/// static_loader! {
+///     static LOCALES = {
+///         locales: "./tests/locales",
+///         fallback_language: "en-US",
+///         customise: |bundle| bundle.set_use_isolating(false),
+///     };
+/// }
+///
+/// let pseudo = PseudoLoader::new(&*LOCALES);
+/// let lang = langid!("en-US");
+/// assert_eq!("[!!! Ĥéļļö Ŵöŕļð! !!!]", pseudo.lookup(&lang, "hello-world"));
+/// assert_eq!(
+///     "[!!! Ĥéļļö Alice! !!!]",
+///     pseudo.lookup_with_args(&lang, "greeting", [("name", "Alice")])
+/// );
+/// ```
+pub struct PseudoLoader<L> {
+    loader: L,
+}
+
+impl<L: DynLoader> PseudoLoader<L> {
+    /// Wraps `loader`, pseudo-localizing every message it formats.
+    pub fn new(loader: L) -> Self {
+        Self { loader }
+    }
+
+    /// Returns the wrapped loader.
+    pub fn into_inner(self) -> L {
+        self.loader
+    }
+}
+
+/// Marks the string values in `args`, so that they can be skipped by
+/// `pseudolocalize`.
+fn mark_args<'a>(args: Option<&'a FluentArgs>) -> Option<FluentArgs<'a>> {
+    args.map(|args| {
+        args.iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    FluentValue::String(text) => {
+                        FluentValue::String(format!("{ARG_START}{text}{ARG_END}").into())
+                    }
+                    value => value.clone(),
+                };
+                (name.to_owned(), value)
+            })
+            .collect()
+    })
+}
+
+/// Accents and brackets `text`, skipping marked argument values and
+/// placeables that Fluent couldn't resolve.
+fn pseudolocalize(text: &str) -> String {
+    let mut output = String::with_capacity(text.len() * 2 + 10);
+    let mut depth = 0usize;
+    output.push_str("[!!! ");
+    for c in text.chars() {
+        match c {
+            ARG_START => depth += 1,
+            ARG_END => depth = depth.saturating_sub(1),
+            '{' => {
+                depth += 1;
+                output.push(c);
+            }
+            '}' => {
+                depth = depth.saturating_sub(1);
+                output.push(c);
+            }
+            'A'..='Z' if depth == 0 => output.push(UPPERCASE[(c as u8 - b'A') as usize]),
+            'a'..='z' if depth == 0 => output.push(LOWERCASE[(c as u8 - b'a') as usize]),
+            c => output.push(c),
+        }
+    }
+    output.push_str(" !!!]");
+    output
+}
+
+impl<L: DynLoader> DynLoader for PseudoLoader<L> {
+    fn lookup_complete(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> String {
+        let args = mark_args(args);
+        pseudolocalize(&self.loader.lookup_complete(lang, text_id, args.as_ref()))
+    }
+
+    fn try_lookup_complete(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        let args = mark_args(args);
+        self.loader
+            .try_lookup_complete(lang, text_id, args.as_ref())
+            .map(|text| pseudolocalize(&text))
+    }
+
+    fn lookup_complete_result(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Result<String, LookupError> {
+        let args = mark_args(args);
+        self.loader
+            .lookup_complete_result(lang, text_id, args.as_ref())
+            .map(|text| pseudolocalize(&text))
+    }
+
+    fn lookup_cow(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Cow<'_, str> {
+        Cow::Owned(self.lookup_complete(lang, text_id, args))
+    }
+
+    fn try_lookup_cow(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<Cow<'_, str>> {
+        self.try_lookup_complete(lang, text_id, args)
+            .map(Cow::Owned)
+    }
+
+    fn lookup_langs(
+        &self,
+        langs: &[LanguageIdentifier],
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> String {
+        let args = mark_args(args);
+        pseudolocalize(&self.loader.lookup_langs(langs, text_id, args.as_ref()))
+    }
+
+    fn try_lookup_langs(
+        &self,
+        langs: &[LanguageIdentifier],
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        let args = mark_args(args);
+        self.loader
+            .try_lookup_langs(langs, text_id, args.as_ref())
+            .map(|text| pseudolocalize(&text))
+    }
+
+    fn lookup_no_default_fallback(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        let args = mark_args(args);
+        self.loader
+            .lookup_no_default_fallback(lang, text_id, args.as_ref())
+            .map(|text| pseudolocalize(&text))
+    }
+
+    fn lookup_into(
+        &self,
+        out: &mut dyn fmt::Write,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> fmt::Result {
+        let args = mark_args(args);
+        let mut text = String::new();
+        self.loader
+            .lookup_into(&mut text, lang, text_id, args.as_ref())?;
+        out.write_str(&pseudolocalize(&text))
+    }
+
+    fn has(&self, lang: &LanguageIdentifier, text_id: &str) -> bool {
+        self.loader.has(lang, text_id)
+    }
+
+    fn attributes(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+        self.loader.attributes(lang, text_id)
+    }
+
+    fn lookup_message(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<LocalizedMessage> {
+        let args = mark_args(args);
+        let message = self.loader.lookup_message(lang, text_id, args.as_ref())?;
+        Some(LocalizedMessage {
+            value: pseudolocalize(&message.value),
+            attrs: message
+                .attrs
+                .into_iter()
+                .map(|(name, value)| (name, pseudolocalize(&value)))
+                .collect(),
+        })
+    }
+
+    fn fallback_chain(&self, lang: &LanguageIdentifier) -> Vec<LanguageIdentifier> {
+        self.loader.fallback_chain(lang)
+    }
+
+    fn locales(&self) -> Box<dyn Iterator<Item = &LanguageIdentifier> + '_> {
+        self.loader.locales()
+    }
+
+    fn message_ids(&self, lang: &LanguageIdentifier) -> Box<dyn Iterator<Item = &str> + '_> {
+        self.loader.message_ids(lang)
+    }
+
+    fn required_args(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+        self.loader.required_args(lang, text_id)
+    }
+
+    fn term_ids(&self, lang: &LanguageIdentifier) -> Box<dyn Iterator<Item = &str> + '_> {
+        self.loader.term_ids(lang)
+    }
+
+    fn stats(&self) -> LoaderStats {
+        self.loader.stats()
+    }
+
+    #[cfg(debug_assertions)]
+    fn format_term(
+        &self,
+        lang: &LanguageIdentifier,
+        term_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        let args = mark_args(args);
+        self.loader
+            .format_term(lang, term_id, args.as_ref())
+            .map(|text| pseudolocalize(&text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pseudolocalize_skips_arguments_and_placeables() {
+        assert_eq!("[!!! Ĥéļļö Ŵöŕļð !!!]", pseudolocalize("Hello World"));
+        assert_eq!(
+            "[!!! Ĥéļļö Bob, {$place} !!!]",
+            pseudolocalize(&format!("Hello {ARG_START}Bob{ARG_END}, {{$place}}"))
+        );
+    }
+}
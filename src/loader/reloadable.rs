@@ -0,0 +1,289 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+use fluent_bundle::FluentArgs;
+
+use super::{ArcLoader, DynLoader, LoaderStats, LocalizedMessage};
+use crate::error::{LoaderError, LookupError};
+use crate::LanguageIdentifier;
+
+type Build = dyn Fn() -> Result<ArcLoader, Box<dyn std::error::Error>> + Send + Sync;
+type OnUpdate = dyn Fn(&[LanguageIdentifier]) + Send + Sync;
+
+/// An [`ArcLoader`] that can be rebuilt from disk in place, e.g. when a
+/// long-running server receives `SIGHUP`, without handing a new loader to
+/// everything that uses it.
+///
+/// Each lookup through the loader uses the latest loader, so it can be
+/// shared, e.g. by a [`FluentLoader`](crate::FluentLoader), and sees every
+/// reload. Lookups that should all see the same resources can be made on the
+/// snapshot returned by [`ReloadableLoader::current`] instead.
+///
+/// [`DynLoader::locales`], [`DynLoader::message_ids`] and
+/// [`DynLoader::term_ids`] borrow from the loader, which a reload may drop,
+/// so they don't return anything here. List them from a snapshot instead,
+/// e.g. `loader.current().locales()`. [`DynLoader::locales_sorted`] and
+/// [`DynLoader::stats`] return owned values, and so use the latest loader
+/// like lookups do.
+///
+/// ```
+/// use std::sync::Arc;
+/// use fluent_templates::{ArcLoader, DynLoader, ReloadableLoader};
+/// use unic_langid::langid;
+///
+/// let loader = Arc::new(
+///     ReloadableLoader::new(|| ArcLoader::builder("./tests/locales", langid!("en-US")).build())
+///         .unwrap(),
+/// );
+///
+/// // e.g. from a signal handler or an admin endpoint.
+/// loader.reload().unwrap();
+/// assert_eq!("Hello World!", loader.lookup(&langid!("en-US"), "hello-world"));
+/// ```
+pub struct ReloadableLoader {
+    build: Box<Build>,
+    loader: CurrentLoader,
+}
+
+impl ReloadableLoader {
+    /// Builds the loader with `build`, which is called again on each reload.
+    pub fn new(
+        build: impl Fn() -> Result<ArcLoader, Box<dyn std::error::Error>> + Send + Sync + 'static,
+    ) -> Result<Self, LoaderError> {
+        Ok(Self {
+            loader: CurrentLoader::new(build().map_err(LoaderError::from_build)?),
+            build: Box::new(build),
+        })
+    }
+
+    /// Returns the latest loader. The returned loader isn't updated, so call
+    /// this again to see later changes.
+    pub fn current(&self) -> Arc<ArcLoader> {
+        self.loader.get()
+    }
+
+    /// Rebuilds the loader, replacing it for later calls to
+    /// [`ReloadableLoader::current`]. The previous loader is kept if this
+    /// fails.
+    pub fn reload(&self) -> Result<(), LoaderError> {
+        self.loader
+            .replace((self.build)().map_err(LoaderError::from_build)?);
+        Ok(())
+    }
+
+    /// Calls `on_update` with the locales whose resources changed, in order,
+    /// whenever a reload changes any of them, e.g. to invalidate caches of
+    /// rendered templates. It's called after the new loader is returned by
+    /// [`ReloadableLoader::current`], and mustn't add another callback.
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use fluent_templates::{ArcLoader, ReloadableLoader};
+    /// use unic_langid::langid;
+    ///
+    /// let loader =
+    ///     ReloadableLoader::new(|| ArcLoader::builder("./tests/locales", langid!("en-US")).build())
+    ///         .unwrap();
+    /// let updates = Arc::new(Mutex::new(Vec::new()));
+    /// let log = updates.clone();
+    /// loader.on_update(move |changed| log.lock().unwrap().push(changed.to_vec()));
+    ///
+    /// // Nothing changed on disk, so the callback isn't called.
+    /// loader.reload().unwrap();
+    /// assert!(updates.lock().unwrap().is_empty());
+    /// ```
+    pub fn on_update(&self, on_update: impl Fn(&[LanguageIdentifier]) + Send + Sync + 'static) {
+        self.loader.on_update(on_update);
+    }
+
+    pub(crate) fn current_loader(&self) -> &CurrentLoader {
+        &self.loader
+    }
+}
+
+impl fmt::Debug for ReloadableLoader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReloadableLoader")
+            .field("loader", &self.current())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The latest version of a loader that's rebuilt in place, and the
+/// callbacks that are called when it changes.
+pub(crate) struct CurrentLoader {
+    loader: RwLock<Arc<ArcLoader>>,
+    on_update: Mutex<Vec<Box<OnUpdate>>>,
+}
+
+impl CurrentLoader {
+    pub(crate) fn new(loader: ArcLoader) -> Self {
+        Self {
+            loader: RwLock::new(Arc::new(loader)),
+            on_update: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn get(&self) -> Arc<ArcLoader> {
+        self.loader
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replaces the loader with `loader`, then calls the callbacks with the
+    /// locales that changed, if there are any.
+    pub(crate) fn replace(&self, loader: ArcLoader) {
+        let loader = Arc::new(loader);
+        let previous = std::mem::replace(
+            &mut *self.loader.write().unwrap_or_else(PoisonError::into_inner),
+            loader.clone(),
+        );
+
+        let changed = previous.changed_locales(&loader);
+        if !changed.is_empty() {
+            for on_update in &*self
+                .on_update
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+            {
+                on_update(&changed);
+            }
+        }
+    }
+
+    pub(crate) fn on_update(
+        &self,
+        on_update: impl Fn(&[LanguageIdentifier]) + Send + Sync + 'static,
+    ) {
+        self.on_update
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Box::new(on_update));
+    }
+}
+
+impl DynLoader for CurrentLoader {
+    fn lookup_complete(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> String {
+        self.get().lookup_complete(lang, text_id, args)
+    }
+
+    fn try_lookup_complete(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        self.get().try_lookup_complete(lang, text_id, args)
+    }
+
+    fn try_lookup_cow(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<Cow<'_, str>> {
+        self.try_lookup_complete(lang, text_id, args)
+            .map(Cow::Owned)
+    }
+
+    fn lookup_complete_result(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Result<String, LookupError> {
+        self.get().lookup_complete_result(lang, text_id, args)
+    }
+
+    fn lookup_langs(
+        &self,
+        langs: &[LanguageIdentifier],
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> String {
+        self.get().lookup_langs(langs, text_id, args)
+    }
+
+    fn try_lookup_langs(
+        &self,
+        langs: &[LanguageIdentifier],
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        self.get().try_lookup_langs(langs, text_id, args)
+    }
+
+    fn lookup_no_default_fallback(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        self.get().lookup_no_default_fallback(lang, text_id, args)
+    }
+
+    fn lookup_into(
+        &self,
+        out: &mut dyn fmt::Write,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> fmt::Result {
+        self.get().lookup_into(out, lang, text_id, args)
+    }
+
+    fn has(&self, lang: &LanguageIdentifier, text_id: &str) -> bool {
+        self.get().has(lang, text_id)
+    }
+
+    fn attributes(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+        self.get().attributes(lang, text_id)
+    }
+
+    fn lookup_message(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<LocalizedMessage> {
+        self.get().lookup_message(lang, text_id, args)
+    }
+
+    fn fallback_chain(&self, lang: &LanguageIdentifier) -> Vec<LanguageIdentifier> {
+        self.get().fallback_chain(lang)
+    }
+
+    // The latest loader can be dropped by a reload while its locales and
+    // IDs are borrowed, so they're only listed by snapshots.
+    fn locales(&self) -> Box<dyn Iterator<Item = &LanguageIdentifier> + '_> {
+        Box::new(std::iter::empty())
+    }
+
+    fn locales_sorted(&self) -> Vec<LanguageIdentifier> {
+        self.get().locales_sorted()
+    }
+
+    fn required_args(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+        self.get().required_args(lang, text_id)
+    }
+
+    #[cfg(debug_assertions)]
+    fn format_term(
+        &self,
+        lang: &LanguageIdentifier,
+        term_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        self.get().format_term(lang, term_id, args)
+    }
+
+    fn stats(&self) -> LoaderStats {
+        self.get().stats()
+    }
+}
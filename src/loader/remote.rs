@@ -0,0 +1,222 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use super::reloadable::CurrentLoader;
+use super::{ArcLoader, ArcLoaderBuilder};
+use crate::LanguageIdentifier;
+
+type Configure =
+    dyn for<'a, 'b> Fn(ArcLoaderBuilder<'a, 'b>) -> ArcLoaderBuilder<'a, 'b> + Send + Sync;
+
+type Sources = HashMap<LanguageIdentifier, Vec<String>>;
+
+/// A builder for [`RemoteLoader`]s.
+pub struct RemoteLoaderBuilder {
+    base_url: String,
+    fallback: LanguageIdentifier,
+    manifest: String,
+    configure: Box<Configure>,
+}
+
+impl RemoteLoaderBuilder {
+    /// Sets the path of the manifest relative to the base URL,
+    /// `manifest.json` by default.
+    pub fn manifest(mut self, manifest: impl Into<String>) -> Self {
+        self.manifest = manifest.into();
+        self
+    }
+
+    /// Configures the [`ArcLoaderBuilder`] that each version of the loader is
+    /// built with, e.g. to customise its bundles or set its prefix.
+    pub fn configure(
+        mut self,
+        configure: impl for<'a, 'b> Fn(ArcLoaderBuilder<'a, 'b>) -> ArcLoaderBuilder<'a, 'b>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.configure = Box::new(configure);
+        self
+    }
+
+    /// Downloads the manifest and resources, and builds the loader.
+    pub fn build(self) -> Result<RemoteLoader, Box<dyn std::error::Error>> {
+        let remote = Remote {
+            base_url: self.base_url,
+            fallback: self.fallback,
+            manifest: self.manifest,
+            configure: self.configure,
+            agent: ureq::Agent::new(),
+            cache: Mutex::new(HashMap::new()),
+        };
+        let loader = remote.build(remote.download()?.1)?;
+        Ok(RemoteLoader {
+            remote,
+            loader: CurrentLoader::new(loader),
+            stale: AtomicBool::new(false),
+        })
+    }
+}
+
+/// An [`ArcLoader`] of resources that are downloaded over HTTP(S), so that
+/// translations can be updated without deploying the application.
+///
+/// The resources are listed by a JSON manifest that maps each locale to the
+/// paths of its resources, relative to the base URL:
+///
+/// ```json
+/// { "en-US": ["main.ftl", "errors.ftl"], "fr": ["fr/main.ftl"] }
+/// ```
+///
+/// [`RemoteLoader::refresh`] downloads any files that have changed, using
+/// their `ETag`s, and rebuilds the loader if there are any. Lookups through
+/// the loader use the latest version, and locales and IDs are listed from the
+/// snapshot returned by [`RemoteLoader::current`], as with a
+/// [`ReloadableLoader`](crate::ReloadableLoader).
+///
+/// ```no_run
+/// use fluent_templates::{DynLoader, RemoteLoader};
+/// use unic_langid::langid;
+///
+/// let loader = RemoteLoader::builder("https://cdn.example.com/locales", langid!("en-US"))
+///     .configure(|builder| builder.customize(|bundle| bundle.set_use_isolating(false)))
+///     .build()
+///     .unwrap();
+///
+/// // e.g. periodically, or from an admin endpoint.
+/// loader.refresh().unwrap();
+/// println!("{}", loader.lookup(&langid!("en-US"), "hello-world"));
+/// ```
+pub struct RemoteLoader {
+    remote: Remote,
+    loader: CurrentLoader,
+    /// Whether a refresh failed, so the loader may not match the cached
+    /// responses, and has to be rebuilt even if they're unchanged.
+    stale: AtomicBool,
+}
+
+impl RemoteLoader {
+    /// Creates a builder for a loader of the resources at `base_url`.
+    pub fn builder(
+        base_url: impl Into<String>,
+        fallback: LanguageIdentifier,
+    ) -> RemoteLoaderBuilder {
+        RemoteLoaderBuilder {
+            base_url: base_url.into(),
+            fallback,
+            manifest: "manifest.json".to_owned(),
+            configure: Box::new(|builder| builder),
+        }
+    }
+
+    /// Returns the latest loader. The returned loader isn't updated, so call
+    /// this again to see later changes.
+    pub fn current(&self) -> Arc<ArcLoader> {
+        self.loader.get()
+    }
+
+    /// Downloads the manifest and resources again, rebuilding the loader if
+    /// any of them have changed, and returns whether it was rebuilt. The
+    /// previous loader is kept if this fails.
+    pub fn refresh(&self) -> Result<bool, Box<dyn std::error::Error>> {
+        let stale = self.stale.swap(true, Ordering::SeqCst);
+        let (changed, sources) = self.remote.download()?;
+        if changed || stale {
+            self.loader.replace(self.remote.build(sources)?);
+        }
+        self.stale.store(false, Ordering::SeqCst);
+        Ok(changed || stale)
+    }
+
+    /// Calls `on_update` with the locales whose resources changed whenever a
+    /// refresh rebuilds the loader, see
+    /// [`ReloadableLoader::on_update`](crate::ReloadableLoader::on_update).
+    pub fn on_update(&self, on_update: impl Fn(&[LanguageIdentifier]) + Send + Sync + 'static) {
+        self.loader.on_update(on_update);
+    }
+
+    pub(crate) fn current_loader(&self) -> &CurrentLoader {
+        &self.loader
+    }
+}
+
+impl fmt::Debug for RemoteLoader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteLoader")
+            .field("base_url", &self.remote.base_url)
+            .field("manifest", &self.remote.manifest)
+            .field("loader", &self.current())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Where the resources are downloaded from, and the responses that have
+/// been downloaded so far.
+struct Remote {
+    base_url: String,
+    fallback: LanguageIdentifier,
+    manifest: String,
+    configure: Box<Configure>,
+    agent: ureq::Agent,
+    /// The `ETag` and body of each URL that had one.
+    cache: Mutex<HashMap<String, (String, String)>>,
+}
+
+impl Remote {
+    /// Downloads the manifest and the sources of each locale it lists. Also
+    /// returns whether any of them changed since they were last downloaded.
+    fn download(&self) -> Result<(bool, Sources), Box<dyn std::error::Error>> {
+        let (mut changed, manifest) = self.fetch(&self.manifest)?;
+        let manifest = serde_json::from_str::<BTreeMap<String, Vec<String>>>(&manifest)?;
+
+        let mut sources = HashMap::new();
+        for (lang, paths) in manifest {
+            let mut lang_sources = Vec::with_capacity(paths.len());
+            for path in paths {
+                let (path_changed, source) = self.fetch(&path)?;
+                changed |= path_changed;
+                lang_sources.push(source);
+            }
+            sources.insert(lang.parse::<LanguageIdentifier>()?, lang_sources);
+        }
+
+        Ok((changed, sources))
+    }
+
+    fn build(&self, sources: Sources) -> Result<ArcLoader, Box<dyn std::error::Error>> {
+        let builder = ArcLoaderBuilder::from_sources(sources, self.fallback.clone());
+        (self.configure)(builder).build()
+    }
+
+    /// Downloads `path`, or returns its cached body if it's unchanged. Also
+    /// returns whether it was downloaded.
+    fn fetch(&self, path: &str) -> Result<(bool, String), Box<dyn std::error::Error>> {
+        let url = format!(
+            "{}/{}",
+            self.base_url.trim_end_matches('/'),
+            path.trim_start_matches('/')
+        );
+        let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let mut request = self.agent.get(&url);
+        if let Some((etag, _)) = cache.get(&url) {
+            request = request.set("If-None-Match", etag);
+        }
+        let response = request.call()?;
+        if response.status() == 304 {
+            if let Some((_, body)) = cache.get(&url) {
+                return Ok((false, body.clone()));
+            }
+        }
+
+        let etag = response.header("ETag").map(str::to_owned);
+        let body = response.into_string()?;
+        match etag {
+            Some(etag) => cache.insert(url, (etag, body.clone())),
+            None => cache.remove(&url),
+        };
+        Ok((true, body))
+    }
+}
@@ -1,26 +1,146 @@
-use std::borrow::Borrow;
+use std::borrow::{Borrow, Cow};
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
 
-use crate::{error::LookupError, FluentBundle};
-use fluent_bundle::{FluentResource, FluentValue};
+use super::{LocalizedMessage, OnMissing};
+use crate::{error::LookupError, FluentBundle, NegotiationStrategy};
+use fluent_bundle::{FluentArgs, FluentResource};
+use fluent_syntax::ast;
 
 pub use unic_langid::LanguageIdentifier;
 
-pub fn lookup_single_language<T: AsRef<str>, R: Borrow<FluentResource>>(
-    bundles: &HashMap<LanguageIdentifier, FluentBundle<R>>,
-    lang: &LanguageIdentifier,
-    text_id: &str,
-    args: Option<&HashMap<T, FluentValue>>,
-) -> Result<String, LookupError> {
-    let bundle = bundles
-        .get(lang)
-        .ok_or_else(|| LookupError::LangNotLoaded(lang.clone()))?;
+/// The bundles of a loader, keyed by their language.
+pub trait Bundles {
+    /// The type of resources the bundles hold.
+    type Resource: Borrow<FluentResource>;
 
-    let mut errors = Vec::new();
+    /// Returns the bundle for exactly `lang`.
+    fn get(&self, lang: &LanguageIdentifier) -> Option<&FluentBundle<Self::Resource>>;
+
+    /// Returns the languages that there are bundles for.
+    fn langs(&self) -> impl Iterator<Item = &LanguageIdentifier>;
+
+    /// Negotiates each of `langs` in order of preference against the
+    /// bundles, without adding the default fallback language.
+    fn negotiate(&self, langs: &[LanguageIdentifier]) -> Vec<&LanguageIdentifier> {
+        negotiate_chain(self, langs, NegotiationStrategy::default())
+    }
+}
+
+impl<R: Borrow<FluentResource>> Bundles for HashMap<LanguageIdentifier, FluentBundle<R>> {
+    type Resource = R;
+
+    fn get(&self, lang: &LanguageIdentifier) -> Option<&FluentBundle<R>> {
+        HashMap::get(self, lang)
+    }
+
+    fn langs(&self) -> impl Iterator<Item = &LanguageIdentifier> {
+        self.keys()
+    }
+}
+
+impl<B: Bundles + ?Sized> Bundles for Arc<B> {
+    type Resource = B::Resource;
+
+    fn get(&self, lang: &LanguageIdentifier) -> Option<&FluentBundle<B::Resource>> {
+        (**self).get(lang)
+    }
+
+    fn langs(&self) -> impl Iterator<Item = &LanguageIdentifier> {
+        (**self).langs()
+    }
+
+    fn negotiate(&self, langs: &[LanguageIdentifier]) -> Vec<&LanguageIdentifier> {
+        (**self).negotiate(langs)
+    }
+}
+
+/// Bundles with the negotiated languages of each of their languages
+/// precomputed, so that lookups in a loaded language don't negotiate. Clones
+/// share the bundles.
+#[derive(Clone)]
+pub(crate) struct NegotiatedBundles<R> {
+    bundles: HashMap<LanguageIdentifier, Arc<FluentBundle<R>>>,
+    chains: HashMap<LanguageIdentifier, Vec<LanguageIdentifier>>,
+    strategy: NegotiationStrategy,
+}
+
+impl<R: Borrow<FluentResource>> NegotiatedBundles<R> {
+    pub(crate) fn new(
+        bundles: HashMap<LanguageIdentifier, FluentBundle<R>>,
+        strategy: NegotiationStrategy,
+    ) -> Self {
+        let mut this = Self {
+            bundles: bundles
+                .into_iter()
+                .map(|(lang, bundle)| (lang, Arc::new(bundle)))
+                .collect(),
+            chains: HashMap::new(),
+            strategy,
+        };
+        this.renegotiate();
+        this
+    }
+
+    pub(crate) fn strategy(&self) -> NegotiationStrategy {
+        self.strategy
+    }
+
+    /// Changes the bundles with `update`, then negotiates their languages
+    /// again.
+    pub(crate) fn update(
+        &mut self,
+        update: impl FnOnce(&mut HashMap<LanguageIdentifier, Arc<FluentBundle<R>>>),
+    ) {
+        update(&mut self.bundles);
+        self.renegotiate();
+    }
+
+    fn renegotiate(&mut self) {
+        self.chains = self
+            .bundles
+            .keys()
+            .map(|lang| {
+                let chain = negotiate_chain(self, std::slice::from_ref(lang), self.strategy);
+                (lang.clone(), chain.into_iter().cloned().collect())
+            })
+            .collect();
+    }
+}
+
+impl<R: Borrow<FluentResource>> Bundles for NegotiatedBundles<R> {
+    type Resource = R;
+
+    fn get(&self, lang: &LanguageIdentifier) -> Option<&FluentBundle<R>> {
+        self.bundles.get(lang).map(Arc::as_ref)
+    }
+
+    fn langs(&self) -> impl Iterator<Item = &LanguageIdentifier> {
+        self.bundles.keys()
+    }
+
+    fn negotiate(&self, langs: &[LanguageIdentifier]) -> Vec<&LanguageIdentifier> {
+        match langs {
+            [lang] => match self.chains.get(lang) {
+                Some(chain) => chain.iter().collect(),
+                None => negotiate_chain(self, langs, self.strategy),
+            },
+            _ => negotiate_chain(self, langs, self.strategy),
+        }
+    }
+}
+
+/// Finds the pattern for `text_id` in `bundle`, which may refer to an
+/// attribute using `message.attribute` syntax.
+fn find_pattern<'bundle, R: Borrow<FluentResource>>(
+    bundle: &'bundle FluentBundle<R>,
+    text_id: &str,
+) -> Result<&'bundle ast::Pattern<&'bundle str>, LookupError> {
     let message_retrieve_error = || LookupError::MessageRetrieval(text_id.to_owned());
 
-    let pattern = if let Some((msg, attr)) = text_id.split_once('.') {
-        bundle
+    if let Some((msg, attr)) = text_id.split_once('.') {
+        Ok(bundle
             .get_message(msg)
             .ok_or_else(message_retrieve_error)?
             .attributes()
@@ -29,38 +149,578 @@ pub fn lookup_single_language<T: AsRef<str>, R: Borrow<FluentResource>>(
                 message_id: msg.to_owned(),
                 attribute: attr.to_owned(),
             })?
-            .value()
+            .value())
     } else {
         bundle
             .get_message(text_id)
             .ok_or_else(message_retrieve_error)?
             .value()
-            .ok_or_else(message_retrieve_error)?
-    };
+            .ok_or_else(message_retrieve_error)
+    }
+}
+
+/// Negotiates each of `langs` in order of preference against the loaded
+/// bundles with `strategy`, without adding the default fallback language.
+pub(crate) fn negotiate_chain<'a, B: Bundles + ?Sized>(
+    bundles: &'a B,
+    langs: &[LanguageIdentifier],
+    strategy: NegotiationStrategy,
+) -> Vec<&'a LanguageIdentifier> {
+    let available = bundles.langs().collect::<Vec<_>>();
+    let mut chain = Vec::new();
+    for lang in langs {
+        for &lang in strategy.negotiate(&[lang], &available) {
+            if !chain.contains(&lang) {
+                chain.push(lang);
+            }
+        }
+    }
+    chain
+}
+
+/// Returns the sorted names of the locales of `loader`, for `Debug` output.
+pub fn locale_names(loader: &(impl super::DynLoader + ?Sized)) -> Vec<String> {
+    loader
+        .locales_sorted()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Returns the languages to search for `langs` in order, finishing with the
+/// `fallback` language.
+pub fn fallback_chain<'a, B: Bundles + ?Sized>(
+    bundles: &'a B,
+    fallback: &'a LanguageIdentifier,
+    langs: &[LanguageIdentifier],
+) -> Vec<&'a LanguageIdentifier> {
+    let mut chain = bundles.negotiate(langs);
+    if !chain.contains(&fallback) {
+        chain.push(fallback);
+    }
+    chain
+}
+
+pub fn lookup_single_language<B: Bundles + ?Sized>(
+    bundles: &B,
+    lang: &LanguageIdentifier,
+    text_id: &str,
+    args: Option<&FluentArgs>,
+) -> Result<String, LookupError> {
+    lookup_single_language_cow(bundles, lang, text_id, args).map(Cow::into_owned)
+}
+
+/// Like `lookup_single_language`, but borrows the formatted text from the
+/// bundle when the message has no placeables.
+pub fn lookup_single_language_cow<'bundle, B: Bundles + ?Sized>(
+    bundles: &'bundle B,
+    lang: &LanguageIdentifier,
+    text_id: &str,
+    args: Option<&FluentArgs>,
+) -> Result<Cow<'bundle, str>, LookupError> {
+    let bundle = bundles
+        .get(lang)
+        .ok_or_else(|| LookupError::LangNotLoaded(lang.clone()))?;
 
-    let args = args.map(super::map_to_fluent_args);
-    let value = bundle.format_pattern(pattern, args.as_ref(), &mut errors);
+    let pattern = find_pattern(bundle, text_id).inspect_err(|_error| {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            target: "fluent_templates",
+            %lang,
+            text_id,
+            error = %_error,
+            "message not found"
+        );
+    })?;
+    let mut errors = Vec::new();
+    let value = bundle.format_pattern(pattern, args, &mut errors);
 
     if errors.is_empty() {
-        Ok(value.into())
+        Ok(value)
     } else {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            target: "fluent_templates",
+            %lang,
+            text_id,
+            ?errors,
+            "failed to format message"
+        );
         Err(LookupError::FluentError(errors))
     }
 }
 
-pub fn lookup_no_default_fallback<S: AsRef<str>, R: Borrow<FluentResource>>(
-    bundles: &HashMap<LanguageIdentifier, FluentBundle<R>>,
-    fallbacks: &HashMap<LanguageIdentifier, Vec<LanguageIdentifier>>,
-    lang: &LanguageIdentifier,
+/// Traverses the fallback chain for `langs`, finishing with the `fallback`
+/// language, and returns the first message that formats successfully.
+///
+/// If every language fails, the most descriptive error is returned: a
+/// formatting or attribute error takes precedence over a missing message, and
+/// a missing message is reported as [`LookupError::LangNotLoaded`] when no
+/// bundle matched any of `langs` at all. A missing message or attribute is
+/// wrapped in [`LookupError::Exhausted`] when more than one language was
+/// tried.
+pub fn lookup_with_fallback<'bundle, B: Bundles + ?Sized>(
+    bundles: &'bundle B,
+    fallback: &LanguageIdentifier,
+    langs: &[LanguageIdentifier],
+    text_id: &str,
+    args: Option<&FluentArgs>,
+) -> Result<Cow<'bundle, str>, LookupError> {
+    let chain = bundles.negotiate(langs);
+    let requested = langs.first().unwrap_or(fallback);
+    let fallback = Some(fallback).filter(|fallback| !chain.contains(fallback));
+    let mut error = None;
+
+    for lang in chain.iter().copied().chain(fallback) {
+        match lookup_single_language_cow(bundles, lang, text_id, args) {
+            Ok(val) => {
+                #[cfg(feature = "tracing")]
+                if langs.first() != Some(lang) {
+                    tracing::debug!(
+                        target: "fluent_templates",
+                        requested = ?langs,
+                        %lang,
+                        text_id,
+                        "message found in fallback language"
+                    );
+                }
+                return Ok(val);
+            }
+            Err(new) => {
+                if !matches!(
+                    error,
+                    Some(LookupError::FluentError(_) | LookupError::AttributeNotFound { .. })
+                ) {
+                    error = Some(new);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        target: "fluent_templates",
+        requested = ?langs,
+        text_id,
+        error = ?error,
+        "message not found in any language"
+    );
+
+    let error = match (error, langs.first()) {
+        (Some(LookupError::MessageRetrieval(_)) | None, Some(lang)) if chain.is_empty() => {
+            return Err(LookupError::LangNotLoaded(lang.clone()));
+        }
+        (Some(error), _) => error,
+        (None, _) => LookupError::MessageRetrieval(text_id.to_owned()),
+    };
+
+    let chain_tried = chain
+        .into_iter()
+        .chain(fallback)
+        .cloned()
+        .collect::<Vec<_>>();
+    match error {
+        LookupError::MessageRetrieval(_) | LookupError::AttributeNotFound { .. }
+            if chain_tried.len() > 1 =>
+        {
+            Err(LookupError::Exhausted {
+                requested: requested.clone(),
+                chain_tried,
+                last_error: Box::new(error),
+            })
+        }
+        error => Err(error),
+    }
+}
+
+/// Produces the replacement for `text_id` after it couldn't be found in the
+/// fallback chain for `langs`.
+pub fn resolve_missing<B: Bundles + ?Sized>(
+    bundles: &B,
+    fallback: &LanguageIdentifier,
+    on_missing: &OnMissing,
+    langs: &[LanguageIdentifier],
+    text_id: &str,
+) -> String {
+    let lang = langs.first().unwrap_or(fallback);
+    let fallback_attempted = fallback_chain(bundles, fallback, langs)
+        .into_iter()
+        .any(|searched| searched != lang);
+    on_missing.resolve(lang, text_id, fallback_attempted)
+}
+
+/// Checks whether `text_id` can be found in the fallback chain for `langs`,
+/// without formatting it.
+pub fn has_with_fallback<B: Bundles + ?Sized>(
+    bundles: &B,
+    fallback: &LanguageIdentifier,
+    langs: &[LanguageIdentifier],
+    text_id: &str,
+) -> bool {
+    fallback_chain(bundles, fallback, langs)
+        .into_iter()
+        .filter_map(|lang| bundles.get(lang))
+        .any(|bundle| find_pattern(bundle, text_id).is_ok())
+}
+
+/// Returns the names of the attributes of the message `text_id`, taken from
+/// the first language in the fallback chain for `langs` that contains it.
+pub fn attributes_with_fallback<B: Bundles + ?Sized>(
+    bundles: &B,
+    fallback: &LanguageIdentifier,
+    langs: &[LanguageIdentifier],
+    text_id: &str,
+) -> Vec<String> {
+    fallback_chain(bundles, fallback, langs)
+        .into_iter()
+        .filter_map(|lang| bundles.get(lang)?.get_message(text_id))
+        .map(|message| {
+            message
+                .attributes()
+                .map(|attribute| attribute.id().to_owned())
+                .collect()
+        })
+        .next()
+        .unwrap_or_default()
+}
+
+/// Formats the value and every attribute of the message `text_id` from the
+/// first language in the fallback chain for `langs` that contains it.
+pub fn message_with_fallback<B: Bundles + ?Sized>(
+    bundles: &B,
+    fallback: &LanguageIdentifier,
+    langs: &[LanguageIdentifier],
+    text_id: &str,
+    args: Option<&FluentArgs>,
+) -> Option<LocalizedMessage> {
+    let (bundle, message) = fallback_chain(bundles, fallback, langs)
+        .into_iter()
+        .filter_map(|lang| bundles.get(lang))
+        .find_map(|bundle| Some((bundle, bundle.get_message(text_id)?)))?;
+
+    let mut errors = Vec::new();
+    let mut format = |pattern| {
+        bundle
+            .format_pattern(pattern, args, &mut errors)
+            .into_owned()
+    };
+
+    Some(LocalizedMessage {
+        value: message.value().map(&mut format).unwrap_or_default(),
+        attrs: message
+            .attributes()
+            .map(|attribute| (attribute.id().to_owned(), format(attribute.value())))
+            .collect(),
+    })
+}
+
+/// Writes `text_id` from the first language in the fallback chain for `langs`
+/// that contains it into `out`, returning `None` if no language does.
+///
+/// Formatting errors don't cause a fallback to the next language, as the
+/// message has already been partially written by the time they're found.
+pub fn write_with_fallback<B: Bundles + ?Sized>(
+    bundles: &B,
+    fallback: &LanguageIdentifier,
+    mut out: &mut dyn fmt::Write,
+    langs: &[LanguageIdentifier],
+    text_id: &str,
+    args: Option<&FluentArgs>,
+) -> Option<fmt::Result> {
+    let (bundle, pattern) = fallback_chain(bundles, fallback, langs)
+        .into_iter()
+        .filter_map(|lang| bundles.get(lang))
+        .find_map(|bundle| Some((bundle, find_pattern(bundle, text_id).ok()?)))?;
+
+    let mut errors = Vec::new();
+    Some(bundle.write_pattern(&mut out, pattern, args, &mut errors))
+}
+
+/// Returns the IDs of the messages defined in `resources`.
+pub fn message_ids<'res, R: Borrow<FluentResource> + 'res>(
+    resources: impl IntoIterator<Item = &'res R>,
+) -> impl Iterator<Item = &'res str> {
+    resources
+        .into_iter()
+        .flat_map(|resource| resource.borrow().entries())
+        .filter_map(|entry| match entry {
+            ast::Entry::Message(message) => Some(message.id.name),
+            _ => None,
+        })
+}
+
+/// Returns the names of the variables referenced by the message `text_id`,
+/// taken from the first language in the fallback chain for `langs` that
+/// contains it, in the order they first appear, see [`References`].
+pub fn required_args_with_fallback<B: Bundles + ?Sized>(
+    bundles: &B,
+    fallback: &LanguageIdentifier,
+    langs: &[LanguageIdentifier],
+    text_id: &str,
+) -> Vec<String> {
+    fallback_chain(bundles, fallback, langs)
+        .into_iter()
+        .filter_map(|lang| bundles.get(lang))
+        .find_map(|bundle| {
+            let mut references = References::new(|id| find_pattern(bundle, id).ok());
+            references.message(text_id).then(|| {
+                references
+                    .variables
+                    .into_iter()
+                    .map(str::to_owned)
+                    .collect()
+            })
+        })
+        .unwrap_or_default()
+}
+
+/// The variables and terms, without their leading `-`, that patterns refer
+/// to, in the order they first appear.
+///
+/// These are what a message needs from the caller, so references to other
+/// messages and attributes are followed, once each, with `resolve`, as
+/// they're formatted with the same arguments. Terms aren't followed, as they
+/// can only access the arguments passed to them, so only the variables used
+/// in those arguments are included.
+pub(crate) struct References<'a, F> {
+    resolve: F,
+    visited: Vec<String>,
+    pub(crate) variables: Vec<&'a str>,
+    pub(crate) terms: Vec<&'a str>,
+}
+
+impl<'a, F: Fn(&str) -> Option<&'a ast::Pattern<&'a str>>> References<'a, F> {
+    /// Creates an empty set of references, where `resolve` returns the
+    /// pattern of a message ID, or of an attribute as `message.attribute`.
+    pub(crate) fn new(resolve: F) -> Self {
+        Self {
+            resolve,
+            visited: Vec::new(),
+            variables: Vec::new(),
+            terms: Vec::new(),
+        }
+    }
+
+    /// Adds the references of the message or attribute `text_id`, unless
+    /// they've already been added, and returns whether it was found.
+    pub(crate) fn message(&mut self, text_id: &str) -> bool {
+        if self.visited.iter().any(|id| id == text_id) {
+            return true;
+        }
+        let Some(pattern) = (self.resolve)(text_id) else {
+            return false;
+        };
+        self.visited.push(text_id.to_owned());
+        self.pattern(pattern);
+        true
+    }
+
+    pub(crate) fn pattern(&mut self, pattern: &ast::Pattern<&'a str>) {
+        for element in &pattern.elements {
+            if let ast::PatternElement::Placeable { expression } = element {
+                self.expression(expression);
+            }
+        }
+    }
+
+    fn expression(&mut self, expression: &ast::Expression<&'a str>) {
+        match expression {
+            ast::Expression::Select { selector, variants } => {
+                self.inline_expression(selector);
+                for variant in variants {
+                    self.pattern(&variant.value);
+                }
+            }
+            ast::Expression::Inline(expression) => self.inline_expression(expression),
+        }
+    }
+
+    fn inline_expression(&mut self, expression: &ast::InlineExpression<&'a str>) {
+        match expression {
+            ast::InlineExpression::VariableReference { id } => {
+                if !self.variables.contains(&id.name) {
+                    self.variables.push(id.name);
+                }
+            }
+            ast::InlineExpression::TermReference { id, arguments, .. } => {
+                if !self.terms.contains(&id.name) {
+                    self.terms.push(id.name);
+                }
+                if let Some(arguments) = arguments {
+                    self.call_arguments(arguments);
+                }
+            }
+            ast::InlineExpression::FunctionReference { arguments, .. } => {
+                self.call_arguments(arguments)
+            }
+            ast::InlineExpression::MessageReference { id, attribute } => {
+                match attribute {
+                    Some(attribute) => self.message(&format!("{}.{}", id.name, attribute.name)),
+                    None => self.message(id.name),
+                };
+            }
+            ast::InlineExpression::Placeable { expression } => self.expression(expression),
+            ast::InlineExpression::StringLiteral { .. }
+            | ast::InlineExpression::NumberLiteral { .. } => {}
+        }
+    }
+
+    fn call_arguments(&mut self, arguments: &ast::CallArguments<&'a str>) {
+        let named = arguments.named.iter().map(|argument| &argument.value);
+        for argument in arguments.positional.iter().chain(named) {
+            self.inline_expression(argument);
+        }
+    }
+}
+
+/// Returns the IDs of the terms defined in `resources`, without their
+/// leading `-`.
+pub fn term_ids<'res, R: Borrow<FluentResource> + 'res>(
+    resources: impl IntoIterator<Item = &'res R>,
+) -> impl Iterator<Item = &'res str> {
+    resources
+        .into_iter()
+        .flat_map(|resource| resource.borrow().entries())
+        .filter_map(|entry| match entry {
+            ast::Entry::Term(term) => Some(term.id.name),
+            _ => None,
+        })
+}
+
+/// Formats the term `term_id` from `resources` with `bundle`, which should
+/// contain the same resources.
+#[cfg(debug_assertions)]
+pub fn format_term<'res, R: Borrow<FluentResource>, T: Borrow<FluentResource> + 'res>(
+    bundle: &FluentBundle<R>,
+    resources: impl IntoIterator<Item = &'res T>,
+    term_id: &str,
+    args: Option<&FluentArgs>,
+) -> Option<String> {
+    let term_id = term_id.strip_prefix('-').unwrap_or(term_id);
+    let term = resources
+        .into_iter()
+        .flat_map(|resource| resource.borrow().entries())
+        .find_map(|entry| match entry {
+            ast::Entry::Term(term) if term.id.name == term_id => Some(term),
+            _ => None,
+        })?;
+    let mut errors = Vec::new();
+    let value = bundle.format_pattern(&term.value, args, &mut errors);
+    errors.is_empty().then(|| value.into_owned())
+}
+
+/// Returns the first message that formats successfully in the languages
+/// that `langs` negotiate to, without the default fallback language.
+pub fn lookup_no_default_fallback<B: Bundles + ?Sized>(
+    bundles: &B,
+    langs: &[LanguageIdentifier],
     text_id: &str,
-    args: Option<&HashMap<S, FluentValue>>,
+    args: Option<&FluentArgs>,
 ) -> Option<String> {
-    let fallbacks = fallbacks.get(lang)?;
-    for l in fallbacks {
-        if let Ok(val) = lookup_single_language(bundles, l, text_id, args) {
-            return Some(val);
+    bundles
+        .negotiate(langs)
+        .into_iter()
+        .find_map(|lang| lookup_single_language(bundles, lang, text_id, args).ok())
+}
+
+/// A prefix that's logically added to the ID of every message of a loader,
+/// so that its messages can't collide with those of other loaders.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Prefix {
+    prefix: String,
+    /// The prefixed IDs of the messages of each language.
+    message_ids: HashMap<LanguageIdentifier, Vec<String>>,
+}
+
+impl Prefix {
+    /// Creates `prefix` for a loader with the given message IDs.
+    pub(crate) fn new<'a, I: IntoIterator<Item = &'a str>>(
+        prefix: String,
+        message_ids: impl IntoIterator<Item = (&'a LanguageIdentifier, I)>,
+    ) -> Self {
+        let mut this = Self {
+            prefix,
+            message_ids: HashMap::new(),
+        };
+        for (lang, ids) in message_ids {
+            this.insert(lang, ids);
+        }
+        this
+    }
+
+    /// Sets the message IDs of `lang`, replacing any it had.
+    pub(crate) fn insert<'a>(
+        &mut self,
+        lang: &LanguageIdentifier,
+        ids: impl IntoIterator<Item = &'a str>,
+    ) {
+        let ids = ids
+            .into_iter()
+            .map(|id| format!("{}{id}", self.prefix))
+            .collect();
+        self.message_ids.insert(lang.clone(), ids);
+    }
+
+    /// Removes the message IDs of `lang`.
+    pub(crate) fn remove(&mut self, lang: &LanguageIdentifier) {
+        self.message_ids.remove(lang);
+    }
+
+    /// Returns `text_id` without the prefix, or `None` if it doesn't start
+    /// with it and so can't be one of the loader's messages.
+    pub(crate) fn strip<'a>(&self, text_id: &'a str) -> Option<&'a str> {
+        text_id.strip_prefix(self.prefix.as_str())
+    }
+
+    /// Returns the prefixed message IDs of `lang`, or `ids` if there's no
+    /// prefix.
+    pub(crate) fn message_ids<'a>(
+        &'a self,
+        lang: &LanguageIdentifier,
+        ids: impl Iterator<Item = &'a str> + 'a,
+    ) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+        if self.prefix.is_empty() {
+            return Box::new(ids);
         }
+        Box::new(
+            self.message_ids
+                .get(lang)
+                .into_iter()
+                .flatten()
+                .map(String::as_str),
+        )
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    None
+    #[test]
+    fn required_args_follow_message_references() {
+        let lang = unic_langid::langid!("en-US");
+        let resource = FluentResource::try_new(
+            [
+                "-brand = Firefox { $case }",
+                "greeting = Hello { $name }!",
+                "    .title = { NUMBER($count) } for { $name }",
+                "cycle = { cycle-back } { $first }",
+                "cycle-back = { cycle } { -brand(case: \"genitive\") } { -brand }",
+                "nested = { greeting } { greeting.title } { $extra }",
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+        let mut bundle = FluentBundle::new_concurrent(vec![lang.clone()]);
+        bundle.add_resource(resource).unwrap();
+        let bundles = HashMap::from([(lang.clone(), bundle)]);
+
+        let required_args = |text_id| {
+            required_args_with_fallback(&bundles, &lang, std::slice::from_ref(&lang), text_id)
+        };
+        assert_eq!(vec!["name", "count", "extra"], required_args("nested"));
+        assert_eq!(vec!["count", "name"], required_args("greeting.title"));
+        assert_eq!(vec!["first"], required_args("cycle"));
+        assert!(required_args("missing").is_empty());
+    }
 }
@@ -0,0 +1,40 @@
+use unic_langid::LanguageIdentifier;
+
+/// Somewhere that Fluent sources are stored, such as a database or an object
+/// store, for building an [`ArcLoader`] with
+/// [`ArcLoaderBuilder::from_resource_source`].
+///
+/// ```
+/// use std::collections::HashMap;
+/// use fluent_templates::{ArcLoaderBuilder, DynLoader, ResourceSource};
+/// use unic_langid::{langid, LanguageIdentifier};
+///
+/// struct Database(HashMap<String, Vec<String>>);
+///
+/// impl ResourceSource for Database {
+///     fn languages(&self) -> Result<Vec<LanguageIdentifier>, Box<dyn std::error::Error>> {
+///         Ok(self.0.keys().map(|lang| lang.parse()).collect::<Result<_, _>>()?)
+///     }
+///
+///     fn load(&self, lang: &LanguageIdentifier) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+///         Ok(self.0.get(&lang.to_string()).cloned().unwrap_or_default())
+///     }
+/// }
+///
+/// let database = Database(HashMap::from([("fr".to_owned(), vec!["hello = Bonjour".to_owned()])]));
+/// let loader = ArcLoaderBuilder::from_resource_source(database, langid!("fr"))
+///     .build()
+///     .unwrap();
+/// assert_eq!("Bonjour", loader.lookup(&langid!("fr"), "hello"));
+/// ```
+///
+/// [`ArcLoader`]: crate::ArcLoader
+/// [`ArcLoaderBuilder::from_resource_source`]: crate::ArcLoaderBuilder::from_resource_source
+pub trait ResourceSource: Send + Sync {
+    /// Returns the languages that have resources.
+    fn languages(&self) -> Result<Vec<LanguageIdentifier>, Box<dyn std::error::Error>>;
+
+    /// Returns the Fluent sources of `lang`, which are added to its bundle in
+    /// order.
+    fn load(&self, lang: &LanguageIdentifier) -> Result<Vec<String>, Box<dyn std::error::Error>>;
+}
@@ -1,19 +1,38 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Mutex, OnceLock, PoisonError};
 
-use crate::{error::LookupError, languages::negotiate_languages, FluentBundle};
-use fluent_bundle::{FluentResource, FluentValue};
+use super::shared::{Bundles, Prefix};
+use super::{LazyBundles, LoaderStats, LocalizedMessage, OnMissing};
+use crate::{
+    error::{LoaderError, LookupError},
+    FluentBundle, NegotiationStrategy,
+};
+use fluent_bundle::{FluentArgs, FluentResource};
 
 pub use unic_langid::LanguageIdentifier;
 
-/// A simple Loader implementation, with statically-loaded fluent data.
+/// A simple DynLoader implementation, with statically-loaded fluent data.
 /// Typically created with the [`static_loader!`] macro
 ///
+/// The `FluentBundle` for each language is only built, and customised, the
+/// first time a message is looked up in that language. Clones share the
+/// embedded resources, but build their own bundles.
+///
 /// [`static_loader!`]: ./macro.static_loader.html
+#[derive(Clone)]
 pub struct StaticLoader {
-    bundles: &'static HashMap<LanguageIdentifier, FluentBundle<&'static FluentResource>>,
+    resources: &'static HashMap<LanguageIdentifier, Vec<FluentResource>>,
+    core_resources: &'static [FluentResource],
+    bundles: LazyBundles,
     fallbacks: &'static HashMap<LanguageIdentifier, Vec<LanguageIdentifier>>,
     fallback: LanguageIdentifier,
+    fallback_overrides: HashMap<LanguageIdentifier, Vec<LanguageIdentifier>>,
+    aliases: HashMap<LanguageIdentifier, LanguageIdentifier>,
+    on_missing: OnMissing,
+    prefix: Prefix,
 }
 
 impl StaticLoader {
@@ -23,88 +42,575 @@ impl StaticLoader {
     /// `static_loader!` macro. it's not meant to be called directly.
     #[doc(hidden)]
     pub fn new(
-        bundles: &'static HashMap<LanguageIdentifier, FluentBundle<&'static FluentResource>>,
+        resources: &'static HashMap<LanguageIdentifier, Vec<FluentResource>>,
+        core_resources: &'static [FluentResource],
+        bundles: LazyBundles,
         fallbacks: &'static HashMap<LanguageIdentifier, Vec<LanguageIdentifier>>,
         fallback: LanguageIdentifier,
     ) -> Self {
         Self {
+            resources,
+            core_resources,
             bundles,
             fallbacks,
             fallback,
+            fallback_overrides: HashMap::new(),
+            aliases: HashMap::new(),
+            on_missing: OnMissing::default(),
+            prefix: Prefix::default(),
         }
     }
 
-    /// Convenience function to look up a string for a single language
-    pub fn lookup_single_language<S: AsRef<str>>(
+    /// Sets what the infallible lookup methods return when a message can't
+    /// be found, see [`OnMissing`]. This is usually set with the
+    /// `on_missing` option of the [`static_loader!`] macro.
+    ///
+    /// [`static_loader!`]: ./macro.static_loader.html
+    pub fn with_on_missing(mut self, on_missing: OnMissing) -> Self {
+        self.on_missing = on_missing;
+        self
+    }
+
+    /// Adds `prefix` to the ID of every message, so `hello` is looked up as
+    /// `myapp-hello` with a prefix of `myapp-`. This is usually set with the
+    /// `prefix` option of the [`static_loader!`] macro.
+    ///
+    /// This lets libraries namespace their messages so that they don't
+    /// collide with the host's when combined in a [`MultiLoader`]. Messages
+    /// still reference each other by their unprefixed IDs.
+    ///
+    /// [`static_loader!`]: ./macro.static_loader.html
+    /// [`MultiLoader`]: crate::MultiLoader
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        let message_ids = self.resources.iter().map(|(lang, resources)| {
+            let resources = self.core_resources.iter().chain(resources);
+            (lang, super::shared::message_ids(resources))
+        });
+        self.prefix = Prefix::new(prefix.into(), message_ids);
+        self
+    }
+
+    /// Sets languages to search after a requested language, before the
+    /// fallback language, e.g. to make `pt-PT` fall back to `pt-BR`. This is
+    /// usually set with the `fallback_overrides` option of the
+    /// [`static_loader!`] macro.
+    ///
+    /// The overrides of a language are searched in order, but their own
+    /// overrides aren't followed.
+    ///
+    /// ```
+    /// use fluent_templates::{static_loader, DynLoader};
+    /// use unic_langid::langid;
+    ///
+    /// static_loader! {
+    ///     static LOCALES = {
+    ///         locales: "./tests/locales",
+    ///         fallback_language: "en-US",
+    ///     };
+    /// }
+    ///
+    /// let loader = LOCALES
+    ///     .rebuilt_with(|_| ())
+    ///     .with_fallback_overrides([(langid!("nn"), vec![langid!("fr")])]);
+    /// assert_eq!("Bonjour le monde!", loader.lookup(&langid!("nn"), "hello-world"));
+    /// ```
+    ///
+    /// [`static_loader!`]: ./macro.static_loader.html
+    pub fn with_fallback_overrides(
+        mut self,
+        overrides: impl IntoIterator<Item = (LanguageIdentifier, Vec<LanguageIdentifier>)>,
+    ) -> Self {
+        self.fallback_overrides = overrides.into_iter().collect();
+        self
+    }
+
+    /// Sets languages that are looked up as another language, e.g. to look
+    /// up the legacy `no` as `nb`. This is usually set with the
+    /// `language_aliases` option of the [`static_loader!`] macro.
+    ///
+    /// An alias of a language without a region or script also applies to its
+    /// more specific forms, so `no-NO` is looked up as `nb-NO`.
+    ///
+    /// ```
+    /// use fluent_templates::{static_loader, DynLoader};
+    /// use unic_langid::langid;
+    ///
+    /// static_loader! {
+    ///     static LOCALES = {
+    ///         locales: "./tests/locales",
+    ///         fallback_language: "en-US",
+    ///     };
+    /// }
+    ///
+    /// let loader = LOCALES
+    ///     .rebuilt_with(|_| ())
+    ///     .with_language_aliases([(langid!("fr-legacy"), langid!("fr"))]);
+    /// assert_eq!("Bonjour le monde!", loader.lookup(&langid!("fr-legacy"), "hello-world"));
+    /// ```
+    ///
+    /// [`static_loader!`]: ./macro.static_loader.html
+    pub fn with_language_aliases(
+        mut self,
+        aliases: impl IntoIterator<Item = (LanguageIdentifier, LanguageIdentifier)>,
+    ) -> Self {
+        self.aliases = aliases.into_iter().collect();
+        self
+    }
+
+    /// Sets how requested languages are matched against the embedded
+    /// locales, see [`NegotiationStrategy`]. This is usually set with the
+    /// `negotiation_strategy` option of the [`static_loader!`] macro.
+    ///
+    /// [`static_loader!`]: ./macro.static_loader.html
+    pub fn with_negotiation_strategy(mut self, strategy: NegotiationStrategy) -> Self {
+        self.bundles.strategy = strategy;
+        self
+    }
+
+    /// Creates a copy of this loader from the same embedded resources, with
+    /// its bundles customised by `customizer` instead of the `customise`
+    /// option of the [`static_loader!`] macro, e.g. to change settings or add
+    /// functions based on run-time configuration.
+    ///
+    /// As with the original loader, each bundle is only built the first time
+    /// its language is used.
+    ///
+    /// ```
+    /// use fluent_templates::{static_loader, LoaderExt};
+    /// use unic_langid::langid;
+    ///
+    /// static_loader! {
+    ///     static LOCALES = {
+    ///         locales: "./tests/locales",
+    ///         fallback_language: "en-US",
+    ///     };
+    /// }
+    ///
+    /// let plain = LOCALES.rebuilt_with(|bundle| bundle.set_use_isolating(false));
+    /// assert_eq!(
+    ///     "Hello Alice!",
+    ///     plain.lookup_with_args(&langid!("en-US"), "greeting", [("name", "Alice")])
+    /// );
+    /// ```
+    ///
+    /// [`static_loader!`]: ./macro.static_loader.html
+    pub fn rebuilt_with(
         &self,
-        lang: &LanguageIdentifier,
-        text_id: &str,
-        args: Option<&HashMap<S, FluentValue>>,
-    ) -> Result<String, LookupError> {
-        super::shared::lookup_single_language(self.bundles, lang, text_id, args)
+        customizer: impl Fn(&mut FluentBundle<&'static FluentResource>) + Send + Sync + 'static,
+    ) -> Self {
+        let mut bundles = LazyBundles::new(self.resources, self.core_resources, customizer);
+        bundles.strategy = self.bundles.strategy;
+        Self {
+            resources: self.resources,
+            core_resources: self.core_resources,
+            bundles,
+            fallbacks: self.fallbacks,
+            fallback: self.fallback.clone(),
+            fallback_overrides: self.fallback_overrides.clone(),
+            aliases: self.aliases.clone(),
+            on_missing: self.on_missing.clone(),
+            prefix: self.prefix.clone(),
+        }
     }
 
-    /// Convenience function to look up a string without falling back to the
-    /// default fallback language
-    pub fn lookup_no_default_fallback<S: AsRef<str>>(
+    /// Convenience function to look up a string for a single language
+    pub fn lookup_single_language(
         &self,
         lang: &LanguageIdentifier,
         text_id: &str,
-        args: Option<&HashMap<S, FluentValue>>,
-    ) -> Option<String> {
-        super::shared::lookup_no_default_fallback(self.bundles, self.fallbacks, lang, text_id, args)
+        args: Option<&FluentArgs>,
+    ) -> Result<String, LookupError> {
+        let text_id = self
+            .prefix
+            .strip(text_id)
+            .ok_or_else(|| LookupError::MessageRetrieval(text_id.to_owned()))?;
+        super::shared::lookup_single_language(&self.bundles, lang, text_id, args)
     }
 
     /// Return the fallback language
     pub fn fallback(&self) -> &LanguageIdentifier {
         &self.fallback
     }
+
+    /// Returns the `FluentBundle` for exactly `lang`, building it if it
+    /// hasn't been used yet, for using `fluent_bundle` APIs directly.
+    pub fn get_bundle(
+        &self,
+        lang: &LanguageIdentifier,
+    ) -> Option<&FluentBundle<&'static FluentResource>> {
+        self.bundles.get(lang)
+    }
+
+    /// Returns `langs` with aliases resolved, and the fallback overrides of
+    /// each language inserted after it.
+    fn search_langs<'a>(&self, langs: &'a [LanguageIdentifier]) -> Cow<'a, [LanguageIdentifier]> {
+        if self.fallback_overrides.is_empty() && self.aliases.is_empty() {
+            return Cow::Borrowed(langs);
+        }
+
+        let mut search = Vec::new();
+        for lang in langs {
+            let lang = &*super::resolve_alias(&self.aliases, lang);
+            let overrides = self.fallback_overrides.get(lang).into_iter().flatten();
+            for lang in std::iter::once(lang).chain(overrides) {
+                if !search.contains(lang) {
+                    search.push(lang.clone());
+                }
+            }
+        }
+        Cow::Owned(search)
+    }
+
+    fn missing(&self, langs: &[LanguageIdentifier], text_id: &str) -> String {
+        super::shared::resolve_missing(
+            &self.bundles,
+            &self.fallback,
+            &self.on_missing,
+            &self.search_langs(langs),
+            text_id,
+        )
+    }
 }
 
-impl super::Loader for StaticLoader {
-    // Traverse the fallback chain,
+impl fmt::Debug for StaticLoader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticLoader")
+            .field("locales", &super::shared::locale_names(self))
+            .field("fallback", &self.fallback.to_string())
+            .field("core_resources", &self.core_resources.len())
+            .field("on_missing", &self.on_missing)
+            .finish_non_exhaustive()
+    }
+}
+
+impl super::DynLoader for StaticLoader {
     fn lookup_complete(
         &self,
         lang: &LanguageIdentifier,
         text_id: &str,
-        args: Option<&HashMap<Cow<'static, str>, FluentValue>>,
+        args: Option<&FluentArgs>,
     ) -> String {
-        for lang in negotiate_languages(&[lang], &self.bundles.keys().collect::<Vec<_>>(), None) {
-            if let Ok(val) = self.lookup_single_language(lang, text_id, args) {
-                return val;
-            }
-        }
+        self.lookup_cow(lang, text_id, args).into_owned()
+    }
 
-        if *lang != self.fallback {
-            if let Ok(val) = self.lookup_single_language(&self.fallback, text_id, args) {
-                return val;
-            }
-        }
-        format!("Unknown localization {text_id}")
+    fn try_lookup_complete(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        self.try_lookup_cow(lang, text_id, args)
+            .map(Cow::into_owned)
+    }
+
+    fn lookup_cow(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Cow<'_, str> {
+        self.try_lookup_cow(lang, text_id, args)
+            .unwrap_or_else(|| Cow::Owned(self.missing(std::slice::from_ref(lang), text_id)))
+    }
+
+    fn try_lookup_cow(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<Cow<'_, str>> {
+        let text_id = self.prefix.strip(text_id)?;
+        super::shared::lookup_with_fallback(
+            &self.bundles,
+            &self.fallback,
+            &self.search_langs(std::slice::from_ref(lang)),
+            text_id,
+            args,
+        )
+        .ok()
     }
 
     // Traverse the fallback chain,
-    fn try_lookup_complete(
+    fn lookup_complete_result(
         &self,
         lang: &LanguageIdentifier,
         text_id: &str,
-        args: Option<&HashMap<Cow<'static, str>, FluentValue>>,
+        args: Option<&FluentArgs>,
+    ) -> Result<String, LookupError> {
+        let text_id = self
+            .prefix
+            .strip(text_id)
+            .ok_or_else(|| LookupError::MessageRetrieval(text_id.to_owned()))?;
+        super::shared::lookup_with_fallback(
+            &self.bundles,
+            &self.fallback,
+            &self.search_langs(std::slice::from_ref(lang)),
+            text_id,
+            args,
+        )
+        .map(Cow::into_owned)
+    }
+
+    fn lookup_langs(
+        &self,
+        langs: &[LanguageIdentifier],
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> String {
+        self.try_lookup_langs(langs, text_id, args)
+            .unwrap_or_else(|| self.missing(langs, text_id))
+    }
+
+    fn try_lookup_langs(
+        &self,
+        langs: &[LanguageIdentifier],
+        text_id: &str,
+        args: Option<&FluentArgs>,
     ) -> Option<String> {
-        for lang in negotiate_languages(&[lang], &self.bundles.keys().collect::<Vec<_>>(), None) {
-            if let Ok(val) = self.lookup_single_language(lang, text_id, args) {
-                return Some(val);
-            }
+        let text_id = self.prefix.strip(text_id)?;
+        super::shared::lookup_with_fallback(
+            &self.bundles,
+            &self.fallback,
+            &self.search_langs(langs),
+            text_id,
+            args,
+        )
+        .ok()
+        .map(Cow::into_owned)
+    }
+
+    fn lookup_no_default_fallback(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        let text_id = self.prefix.strip(text_id)?;
+        super::shared::lookup_no_default_fallback(
+            &self.bundles,
+            &self.search_langs(std::slice::from_ref(lang)),
+            text_id,
+            args,
+        )
+    }
+
+    fn lookup_into(
+        &self,
+        out: &mut dyn fmt::Write,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> fmt::Result {
+        let written = self.prefix.strip(text_id).and_then(|id| {
+            super::shared::write_with_fallback(
+                &self.bundles,
+                &self.fallback,
+                out,
+                &self.search_langs(std::slice::from_ref(lang)),
+                id,
+                args,
+            )
+        });
+        match written {
+            Some(result) => result,
+            None => out.write_str(&self.missing(std::slice::from_ref(lang), text_id)),
         }
+    }
 
-        if *lang != self.fallback {
-            if let Ok(val) = self.lookup_single_language(&self.fallback, text_id, args) {
-                return Some(val);
-            }
+    fn has(&self, lang: &LanguageIdentifier, text_id: &str) -> bool {
+        let Some(text_id) = self.prefix.strip(text_id) else {
+            return false;
+        };
+        super::shared::has_with_fallback(
+            &self.bundles,
+            &self.fallback,
+            &self.search_langs(std::slice::from_ref(lang)),
+            text_id,
+        )
+    }
+
+    fn message_ids(&self, lang: &LanguageIdentifier) -> Box<dyn Iterator<Item = &str> + '_> {
+        match self.resources.get(lang) {
+            Some(resources) => self.prefix.message_ids(
+                lang,
+                super::shared::message_ids(self.core_resources.iter().chain(resources)),
+            ),
+            None => Box::new(std::iter::empty()),
+        }
+    }
+
+    fn required_args(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+        let Some(text_id) = self.prefix.strip(text_id) else {
+            return Vec::new();
+        };
+        super::shared::required_args_with_fallback(
+            &self.bundles,
+            &self.fallback,
+            &self.search_langs(std::slice::from_ref(lang)),
+            text_id,
+        )
+    }
+
+    fn term_ids(&self, lang: &LanguageIdentifier) -> Box<dyn Iterator<Item = &str> + '_> {
+        match self.resources.get(lang) {
+            Some(resources) => Box::new(super::shared::term_ids(
+                self.core_resources.iter().chain(resources),
+            )),
+            None => Box::new(std::iter::empty()),
         }
-        None
+    }
+
+    #[cfg(debug_assertions)]
+    fn format_term(
+        &self,
+        lang: &LanguageIdentifier,
+        term_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        super::shared::format_term(
+            self.bundles.get(lang)?,
+            self.core_resources.iter().chain(self.resources.get(lang)?),
+            term_id,
+            args,
+        )
+    }
+
+    fn stats(&self) -> LoaderStats {
+        LoaderStats::from_loader(self, |lang| {
+            self.resources.get(lang).map_or(0, Vec::len) + self.core_resources.len()
+        })
+    }
+
+    fn attributes(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+        let Some(text_id) = self.prefix.strip(text_id) else {
+            return Vec::new();
+        };
+        super::shared::attributes_with_fallback(
+            &self.bundles,
+            &self.fallback,
+            &self.search_langs(std::slice::from_ref(lang)),
+            text_id,
+        )
+    }
+
+    fn lookup_message(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<LocalizedMessage> {
+        let text_id = self.prefix.strip(text_id)?;
+        super::shared::message_with_fallback(
+            &self.bundles,
+            &self.fallback,
+            &self.search_langs(std::slice::from_ref(lang)),
+            text_id,
+            args,
+        )
+    }
+
+    fn fallback_chain(&self, lang: &LanguageIdentifier) -> Vec<LanguageIdentifier> {
+        super::shared::fallback_chain(
+            &self.bundles,
+            &self.fallback,
+            &self.search_langs(std::slice::from_ref(lang)),
+        )
+        .into_iter()
+        .cloned()
+        .collect()
     }
 
     fn locales(&self) -> Box<dyn Iterator<Item = &LanguageIdentifier> + '_> {
         Box::new(self.fallbacks.keys())
     }
 }
+
+/// A `StaticLoader` that is initialised from its embedded resources the
+/// first time it's used, which is the type of the statics created by the
+/// [`static_loader!`] macro.
+///
+/// Dereferencing it panics if a resource fails to load, use
+/// [`LazyStaticLoader::try_init`] at startup to report that as an error
+/// instead.
+///
+/// ```
+/// use fluent_templates::static_loader;
+///
+/// static_loader! {
+///     static LOCALES = {
+///         locales: "./tests/locales",
+///         fallback_language: "en-US",
+///     };
+/// }
+///
+/// if let Err(error) = LOCALES.try_init() {
+///     panic!("Couldn't load translations: {error}");
+/// }
+/// ```
+///
+/// [`static_loader!`]: ./macro.static_loader.html
+pub struct LazyStaticLoader {
+    loader: OnceLock<StaticLoader>,
+    init: fn() -> Result<StaticLoader, LoaderError>,
+    init_lock: Mutex<()>,
+}
+
+impl LazyStaticLoader {
+    /// Creates a loader that is initialised by `init` on first use.
+    ///
+    /// This is exposed as publicly so that it can be used inside the
+    /// `static_loader!` macro. it's not meant to be called directly.
+    #[doc(hidden)]
+    pub const fn new(init: fn() -> Result<StaticLoader, LoaderError>) -> Self {
+        Self {
+            loader: OnceLock::new(),
+            init,
+            init_lock: Mutex::new(()),
+        }
+    }
+
+    /// Initialises the loader if it hasn't been already, returning the error
+    /// if one of its resources couldn't be loaded. Initialisation is retried
+    /// on the next use after a failure.
+    pub fn try_init(&self) -> Result<&StaticLoader, LoaderError> {
+        if let Some(loader) = self.loader.get() {
+            return Ok(loader);
+        }
+
+        let _guard = self
+            .init_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(loader) = self.loader.get() {
+            return Ok(loader);
+        }
+        let loader = (self.init)()?;
+        Ok(self.loader.get_or_init(|| loader))
+    }
+
+    /// Initialises the loader if it hasn't been already.
+    ///
+    /// # Panics
+    /// If one of its resources couldn't be loaded.
+    pub fn force(&self) -> &StaticLoader {
+        self.try_init()
+            .unwrap_or_else(|error| panic!("Couldn't initialise static loader: {error}"))
+    }
+}
+
+impl fmt::Debug for LazyStaticLoader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.loader.get() {
+            Some(loader) => loader.fmt(f),
+            None => f.write_str("LazyStaticLoader(<uninit>)"),
+        }
+    }
+}
+
+impl Deref for LazyStaticLoader {
+    type Target = StaticLoader;
+
+    fn deref(&self) -> &StaticLoader {
+        self.force()
+    }
+}
@@ -0,0 +1,59 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use unic_langid::LanguageIdentifier;
+
+use super::DynLoader;
+
+/// Counts of the locales, resources and messages in a loader, returned by
+/// [`DynLoader::stats`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LoaderStats {
+    /// The counts for each locale.
+    pub locales: BTreeMap<LanguageIdentifier, LocaleStats>,
+}
+
+/// The counts for a single locale in [`LoaderStats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LocaleStats {
+    /// The number of Fluent resources, including any shared resources.
+    pub resources: usize,
+    /// The number of messages defined for exactly this locale.
+    pub messages: usize,
+}
+
+impl LoaderStats {
+    /// Creates the stats for `loader` from its message IDs, with the number
+    /// of resources of each locale given by `resources`.
+    pub(crate) fn from_loader(
+        loader: &(impl DynLoader + ?Sized),
+        resources: impl Fn(&LanguageIdentifier) -> usize,
+    ) -> Self {
+        let locales = loader
+            .locales_sorted()
+            .into_iter()
+            .map(|lang| {
+                let stats = LocaleStats {
+                    resources: resources(&lang),
+                    messages: loader.message_ids(&lang).collect::<BTreeSet<_>>().len(),
+                };
+                (lang, stats)
+            })
+            .collect();
+        Self { locales }
+    }
+
+    /// Returns the number of locales.
+    pub fn locale_count(&self) -> usize {
+        self.locales.len()
+    }
+
+    /// Returns the number of resources across all locales.
+    pub fn resource_count(&self) -> usize {
+        self.locales.values().map(|stats| stats.resources).sum()
+    }
+
+    /// Returns the number of messages across all locales.
+    pub fn message_count(&self) -> usize {
+        self.locales.values().map(|stats| stats.messages).sum()
+    }
+}
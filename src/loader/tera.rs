@@ -1,13 +1,16 @@
-use fluent_bundle::FluentValue;
+use fluent_bundle::{FluentArgs, FluentValue};
 use serde_json::Value as Json;
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::collections::HashMap;
 use unic_langid::LanguageIdentifier;
 
-use crate::Loader;
+use crate::{AvailableLocales, DynLoader, TextDirection};
 
 const LANG_KEY: &str = "lang";
 const FLUENT_KEY: &str = "key";
+const ATTR_KEY: &str = "attr";
+const ESCAPE_KEY: &str = "escape";
 
 #[derive(Debug, thiserror::Error)]
 enum Error {
@@ -17,10 +20,37 @@ enum Error {
     LangArgumentInvalid,
     #[error("No `id` argument provided.")]
     NoFluentArgument,
+    #[error("`attr` must be a string.")]
+    AttrArgumentInvalid,
+    #[error("`escape` must be a boolean.")]
+    EscapeArgumentInvalid,
     #[error("Couldn't convert JSON to Fluent value.")]
     JsonToFluentFail,
 }
 
+thread_local! {
+    /// The language of the template being rendered by
+    /// [`FluentLoader::render_tera`](crate::FluentLoader::render_tera) on this
+    /// thread, as Tera functions can't read the context.
+    static CONTEXT_LANG: RefCell<Option<LanguageIdentifier>> = const { RefCell::new(None) };
+}
+
+/// Restores the previous context language when a render finishes, so that
+/// templates rendered while rendering another keep their own language.
+struct ContextLang(Option<LanguageIdentifier>);
+
+impl ContextLang {
+    fn set(lang: Option<LanguageIdentifier>) -> Self {
+        Self(CONTEXT_LANG.with(|current| current.replace(lang)))
+    }
+}
+
+impl Drop for ContextLang {
+    fn drop(&mut self) {
+        CONTEXT_LANG.with(|current| *current.borrow_mut() = self.0.take());
+    }
+}
+
 impl From<Error> for tera::Error {
     fn from(error: Error) -> Self {
         tera::Error::msg(error)
@@ -44,35 +74,124 @@ fn parse_language(arg: &Json) -> crate::Result<LanguageIdentifier, Error> {
         .ok_or(Error::LangArgumentInvalid)
 }
 
-impl<L: Loader + Send + Sync> tera::Function for crate::FluentLoader<L> {
+impl<L> crate::FluentLoader<L> {
+    /// Renders the Tera template `name` like [`tera::Tera::render`], with the
+    /// language read from the context variable set by
+    /// [`with_lang_variable`](Self::with_lang_variable), `lang` by default, so
+    /// that `fluent` and `dir` don't need a `lang` argument in templates
+    /// rendered with a per-request context. The `lang` argument still takes
+    /// precedence, and the default language is used when the context doesn't
+    /// have the variable.
+    ///
+    /// Tera functions can't read the context, so the language is only known
+    /// to the functions called while rendering on this thread.
+    ///
+    /// ```
+    /// use fluent_templates::{ArcLoader, FluentLoader};
+    /// use unic_langid::langid;
+    ///
+    /// let loader = ArcLoader::builder("./tests/locales", langid!("en-US")).build().unwrap();
+    /// let loader = FluentLoader::new(loader);
+    /// let mut tera = tera::Tera::default();
+    /// tera.register_function("fluent", loader.clone());
+    /// tera.add_raw_template("title", r#"{{ fluent(key="hello-world") }}"#).unwrap();
+    ///
+    /// let mut context = tera::Context::new();
+    /// context.insert("lang", "fr");
+    /// assert_eq!("Bonjour le monde!", loader.render_tera(&tera, "title", &context).unwrap());
+    /// ```
+    pub fn render_tera(
+        &self,
+        tera: &tera::Tera,
+        name: &str,
+        context: &tera::Context,
+    ) -> Result<String, tera::Error> {
+        let lang = context
+            .get(&self.lang_variable)
+            .map(parse_language)
+            .transpose()?;
+        let _lang = ContextLang::set(lang);
+        tera.render(name, context)
+    }
+
+    /// Returns the language given by the `lang` argument, or else the
+    /// language of the context if the template is rendered with
+    /// [`render_tera`](Self::render_tera), or else the default language.
+    fn tera_lang(&self, args: &HashMap<String, Json>) -> Result<LanguageIdentifier, Error> {
+        args.get(LANG_KEY)
+            .map(parse_language)
+            .transpose()?
+            .or_else(|| CONTEXT_LANG.with(|lang| lang.borrow().clone()))
+            .or_else(|| self.default_lang.clone())
+            .ok_or(Error::NoLangArgument)
+    }
+}
+
+impl<L: DynLoader + Send + Sync> tera::Function for crate::FluentLoader<L> {
     fn call(&self, args: &HashMap<String, Json>) -> Result<Json, tera::Error> {
-        let lang_arg = args.get(LANG_KEY).map(parse_language).transpose()?;
-        let lang = lang_arg
-            .as_ref()
-            .or(self.default_lang.as_ref())
-            .ok_or(Error::NoLangArgument)?;
+        let lang = &self.tera_lang(args)?;
 
         let id = args
             .get(FLUENT_KEY)
             .and_then(Json::as_str)
             .ok_or(Error::NoFluentArgument)?;
+        let id = match args.get(ATTR_KEY) {
+            Some(attr) => {
+                let attr = attr.as_str().ok_or(Error::AttrArgumentInvalid)?;
+                Cow::Owned(format!("{id}.{attr}"))
+            }
+            None => Cow::Borrowed(id),
+        };
+        let escape = match args.get(ESCAPE_KEY) {
+            Some(escape) => escape.as_bool().ok_or(Error::EscapeArgumentInvalid)?,
+            None => self.escape.unwrap_or(false),
+        };
 
         /// Filters kwargs to exclude ones used by this function and tera.
         fn is_not_tera_key((k, _): &(&String, &Json)) -> bool {
             let k = &**k;
-            !(k == LANG_KEY || k == FLUENT_KEY || k == "__tera_one_off")
+            !(k == LANG_KEY
+                || k == FLUENT_KEY
+                || k == ATTR_KEY
+                || k == ESCAPE_KEY
+                || k == "__tera_one_off")
         }
 
-        let mut fluent_args = HashMap::new();
+        let mut fluent_args = FluentArgs::new();
 
         for (key, value) in args.iter().filter(is_not_tera_key) {
-            fluent_args.insert(
-                Cow::from(heck::ToKebabCase::to_kebab_case(&**key)),
+            fluent_args.set(
+                heck::ToKebabCase::to_kebab_case(&**key),
                 json_to_fluent(value.clone())?,
             );
         }
 
-        let response = self.loader.lookup_with_args(lang, id, &fluent_args);
-        Ok(Json::String(response))
+        let response = self
+            .lookup_for_template(lang, &id, Some(&fluent_args))
+            .map_err(tera::Error::msg)?;
+        if escape {
+            return Ok(Json::String(tera::escape_html(&response)));
+        }
+        Ok(Json::String(response.into_owned()))
+    }
+
+    /// Messages are left to Tera's autoescaping, unless escaping was set
+    /// with [`with_escape`](crate::FluentLoader::with_escape), in which case
+    /// the function escapes them itself, so they mustn't be escaped again.
+    fn is_safe(&self) -> bool {
+        self.escape.is_some()
+    }
+}
+
+impl<L: DynLoader + Send + Sync> tera::Function for TextDirection<L> {
+    fn call(&self, args: &HashMap<String, Json>) -> Result<Json, tera::Error> {
+        let lang = self.0.tera_lang(args)?;
+        Ok(Json::String(self.0.loader.dir(&lang).to_owned()))
+    }
+}
+
+impl<L: DynLoader + Send + Sync> tera::Function for AvailableLocales<L> {
+    fn call(&self, _: &HashMap<String, Json>) -> Result<Json, tera::Error> {
+        Ok(self.json())
     }
 }
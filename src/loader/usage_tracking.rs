@@ -0,0 +1,248 @@
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::Mutex;
+
+use fluent_bundle::FluentArgs;
+use unic_langid::LanguageIdentifier;
+
+use super::{DynLoader, LoaderStats, LocalizedMessage};
+use crate::error::LookupError;
+
+/// A loader that records which messages are looked up in another loader, for
+/// finding translations that are never used.
+///
+/// ```
+/// use fluent_templates::{static_loader, DynLoader, UsageTrackingLoader};
+/// use unic_langid::langid;
+///
+/// static_loader! {
+///     static LOCALES = {
+///         locales: "./tests/locales",
+///         fallback_language: "en-US",
+///     };
+/// }
+///
+/// let loader = UsageTrackingLoader::new(&*LOCALES);
+/// loader.lookup(&langid!("fr"), "hello-world");
+///
+/// assert!(loader.used().contains(&(langid!("fr"), "hello-world".to_owned())));
+/// assert!(loader.unused_ids().contains("simple"));
+/// assert!(!loader.unused_ids().contains("hello-world"));
+/// ```
+pub struct UsageTrackingLoader<L> {
+    loader: L,
+    used: Mutex<BTreeSet<(LanguageIdentifier, String)>>,
+}
+
+impl<L: DynLoader> UsageTrackingLoader<L> {
+    /// Wraps `loader`, recording every message that is looked up.
+    pub fn new(loader: L) -> Self {
+        Self {
+            loader,
+            used: Mutex::default(),
+        }
+    }
+
+    /// Returns the wrapped loader.
+    pub fn into_inner(self) -> L {
+        self.loader
+    }
+
+    /// Returns every `(lang, text_id)` pair that has been looked up, where
+    /// `lang` is the requested language.
+    pub fn used(&self) -> BTreeSet<(LanguageIdentifier, String)> {
+        self.used.lock().unwrap().clone()
+    }
+
+    /// Returns the IDs of the messages that have been looked up in any
+    /// language. Attributes are recorded as their message.
+    pub fn used_ids(&self) -> BTreeSet<String> {
+        self.used
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, text_id)| message_id(text_id).to_owned())
+            .collect()
+    }
+
+    /// Returns the IDs of the messages in [`DynLoader::all_message_ids`] that
+    /// haven't been looked up in any language.
+    pub fn unused_ids(&self) -> BTreeSet<String> {
+        let used = self.used_ids();
+        self.loader
+            .all_message_ids()
+            .into_iter()
+            .filter(|id| !used.contains(*id))
+            .map(String::from)
+            .collect()
+    }
+
+    /// Forgets every recorded lookup.
+    pub fn clear(&self) {
+        self.used.lock().unwrap().clear();
+    }
+
+    fn record(&self, lang: &LanguageIdentifier, text_id: &str) {
+        self.used
+            .lock()
+            .unwrap()
+            .insert((lang.clone(), text_id.to_owned()));
+    }
+}
+
+/// Returns the message part of a `message.attribute` ID.
+fn message_id(text_id: &str) -> &str {
+    text_id
+        .split_once('.')
+        .map_or(text_id, |(message, _)| message)
+}
+
+impl<L: DynLoader> DynLoader for UsageTrackingLoader<L> {
+    fn lookup_complete(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> String {
+        self.record(lang, text_id);
+        self.loader.lookup_complete(lang, text_id, args)
+    }
+
+    fn try_lookup_complete(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        self.record(lang, text_id);
+        self.loader.try_lookup_complete(lang, text_id, args)
+    }
+
+    fn lookup_complete_result(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Result<String, LookupError> {
+        self.record(lang, text_id);
+        self.loader.lookup_complete_result(lang, text_id, args)
+    }
+
+    fn lookup_cow(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Cow<'_, str> {
+        self.record(lang, text_id);
+        self.loader.lookup_cow(lang, text_id, args)
+    }
+
+    fn try_lookup_cow(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<Cow<'_, str>> {
+        self.record(lang, text_id);
+        self.loader.try_lookup_cow(lang, text_id, args)
+    }
+
+    fn lookup_langs(
+        &self,
+        langs: &[LanguageIdentifier],
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> String {
+        if let Some(lang) = langs.first() {
+            self.record(lang, text_id);
+        }
+        self.loader.lookup_langs(langs, text_id, args)
+    }
+
+    fn try_lookup_langs(
+        &self,
+        langs: &[LanguageIdentifier],
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        if let Some(lang) = langs.first() {
+            self.record(lang, text_id);
+        }
+        self.loader.try_lookup_langs(langs, text_id, args)
+    }
+
+    fn lookup_no_default_fallback(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        self.record(lang, text_id);
+        self.loader.lookup_no_default_fallback(lang, text_id, args)
+    }
+
+    fn lookup_into(
+        &self,
+        out: &mut dyn fmt::Write,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> fmt::Result {
+        self.record(lang, text_id);
+        self.loader.lookup_into(out, lang, text_id, args)
+    }
+
+    fn has(&self, lang: &LanguageIdentifier, text_id: &str) -> bool {
+        self.loader.has(lang, text_id)
+    }
+
+    fn attributes(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+        self.loader.attributes(lang, text_id)
+    }
+
+    fn lookup_message(
+        &self,
+        lang: &LanguageIdentifier,
+        text_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<LocalizedMessage> {
+        self.record(lang, text_id);
+        self.loader.lookup_message(lang, text_id, args)
+    }
+
+    fn fallback_chain(&self, lang: &LanguageIdentifier) -> Vec<LanguageIdentifier> {
+        self.loader.fallback_chain(lang)
+    }
+
+    fn locales(&self) -> Box<dyn Iterator<Item = &LanguageIdentifier> + '_> {
+        self.loader.locales()
+    }
+
+    fn message_ids(&self, lang: &LanguageIdentifier) -> Box<dyn Iterator<Item = &str> + '_> {
+        self.loader.message_ids(lang)
+    }
+
+    fn required_args(&self, lang: &LanguageIdentifier, text_id: &str) -> Vec<String> {
+        self.loader.required_args(lang, text_id)
+    }
+
+    fn term_ids(&self, lang: &LanguageIdentifier) -> Box<dyn Iterator<Item = &str> + '_> {
+        self.loader.term_ids(lang)
+    }
+
+    fn stats(&self) -> LoaderStats {
+        self.loader.stats()
+    }
+
+    #[cfg(debug_assertions)]
+    fn format_term(
+        &self,
+        lang: &LanguageIdentifier,
+        term_id: &str,
+        args: Option<&FluentArgs>,
+    ) -> Option<String> {
+        self.loader.format_term(lang, term_id, args)
+    }
+}
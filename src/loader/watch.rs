@@ -0,0 +1,125 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use notify::{EventKind, RecursiveMode, Watcher};
+
+use super::reloadable::CurrentLoader;
+use super::{ArcLoader, ReloadableLoader};
+use crate::error::LoaderError;
+use crate::LanguageIdentifier;
+
+/// An [`ArcLoader`] that is rebuilt when any file under its locales directory
+/// changes, so translations can be edited while the application is running.
+///
+/// The directory is watched with [`notify`], and the loader is rebuilt from a
+/// background thread, which stops when the `WatchingLoader` is dropped. The
+/// loader is only rebuilt once the files have stopped changing for an
+/// interval, so that files which are still being written aren't loaded. If
+/// rebuilding fails, e.g. because a resource has a syntax error, the previous
+/// loader is kept and a warning is logged.
+///
+/// Lookups through the loader use the latest loader, and locales and IDs are
+/// listed from the snapshot returned by [`WatchingLoader::current`], as with
+/// a [`ReloadableLoader`].
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use fluent_templates::{ArcLoader, DynLoader, WatchingLoader};
+/// use unic_langid::langid;
+///
+/// let loader = WatchingLoader::new("./locales", Duration::from_secs(1), || {
+///     ArcLoader::builder("./locales", langid!("en-US")).build()
+/// })
+/// .unwrap();
+///
+/// println!("{}", loader.lookup(&langid!("en-US"), "hello-world"));
+/// ```
+pub struct WatchingLoader {
+    location: PathBuf,
+    loader: Arc<ReloadableLoader>,
+    _watcher: notify::RecommendedWatcher,
+}
+
+impl WatchingLoader {
+    /// Builds the loader with `build` and rebuilds it whenever a file under
+    /// `location` changes, once no file has changed for `interval`.
+    ///
+    /// Resources outside of `location`, such as shared resources, aren't
+    /// watched, but are reloaded along with it.
+    pub fn new(
+        location: impl Into<PathBuf>,
+        interval: Duration,
+        build: impl Fn() -> Result<ArcLoader, Box<dyn std::error::Error>> + Send + Sync + 'static,
+    ) -> Result<Self, LoaderError> {
+        let location = location.into();
+        let loader = Arc::new(ReloadableLoader::new(build)?);
+
+        let (sender, events) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                // Reading the resources shouldn't cause another reload.
+                if !matches!(event.map(|event| event.kind), Ok(EventKind::Access(_))) {
+                    let _ = sender.send(());
+                }
+            })?;
+        watcher.watch(&location, RecursiveMode::Recursive)?;
+
+        // The sender is dropped along with the watcher, which ends the thread.
+        let shared = loader.clone();
+        let path = location.clone();
+        std::thread::spawn(move || {
+            while events.recv().is_ok() {
+                loop {
+                    match events.recv_timeout(interval) {
+                        Ok(()) => continue,
+                        Err(mpsc::RecvTimeoutError::Timeout) => break,
+                        Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+                if let Err(error) = shared.reload() {
+                    log::warn!("Couldn't reload locales from {}: {error}", path.display());
+                }
+            }
+        });
+
+        Ok(Self {
+            location,
+            loader,
+            _watcher: watcher,
+        })
+    }
+
+    /// Returns the latest loader. The returned loader isn't updated, so call
+    /// this again to see later changes.
+    pub fn current(&self) -> Arc<ArcLoader> {
+        self.loader.current()
+    }
+
+    /// Rebuilds the loader immediately, keeping the previous loader if that
+    /// fails.
+    pub fn reload(&self) -> Result<(), LoaderError> {
+        self.loader.reload()
+    }
+
+    /// Calls `on_update` with the locales that changed whenever the loader is
+    /// rebuilt, see [`ReloadableLoader::on_update`]. Rebuilds on changes are
+    /// made by the background thread, so that's where it's called from.
+    pub fn on_update(&self, on_update: impl Fn(&[LanguageIdentifier]) + Send + Sync + 'static) {
+        self.loader.on_update(on_update);
+    }
+
+    pub(crate) fn current_loader(&self) -> &CurrentLoader {
+        self.loader.current_loader()
+    }
+}
+
+impl fmt::Debug for WatchingLoader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WatchingLoader")
+            .field("location", &self.location)
+            .field("loader", &self.current())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,277 @@
+//! Checks the resources of a loader for common translation mistakes.
+//!
+//! A [`ValidationReport`] is the run-time counterpart of the
+//! `check_coverage` and `check_placeholders` options of `static_loader!`.
+//! It lists, for each locale, the messages that are missing compared to a
+//! reference language, the messages whose variables differ from the
+//! reference's, and the terms that nothing refers to.
+//!
+//! ```
+//! use fluent_templates::ArcLoader;
+//! use unic_langid::langid;
+//!
+//! let report = ArcLoader::builder("./tests/locales", langid!("en-US"))
+//!     .validate()
+//!     .unwrap();
+//! let french = &report.locales[&langid!("fr")];
+//! assert!(french.missing.contains("fallback"));
+//! assert!(!report.is_valid());
+//! ```
+
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use fluent_bundle::FluentResource;
+use fluent_syntax::ast;
+use unic_langid::LanguageIdentifier;
+
+use crate::loader::References;
+
+/// The problems found in the resources of each locale of a loader.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// The language that every other locale is compared against.
+    pub reference: LanguageIdentifier,
+    /// The problems of every locale, including the reference language.
+    pub locales: BTreeMap<LanguageIdentifier, LocaleValidation>,
+}
+
+/// The problems found in a single locale of a [`ValidationReport`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LocaleValidation {
+    /// Messages defined for the reference language but neither this locale
+    /// nor the locales it inherits from, e.g. `de` for `de-AT`.
+    pub missing: BTreeSet<String>,
+    /// Messages whose variables differ from those of the reference language,
+    /// by message ID. The variables of a message include those of its
+    /// attributes and of the messages it refers to, as with
+    /// [`DynLoader::required_args`](crate::DynLoader::required_args).
+    pub mismatched_placeholders: BTreeMap<String, PlaceholderMismatch>,
+    /// Terms defined for this locale that none of its messages or terms, or
+    /// the shared resources, refer to. The IDs don't have their leading `-`.
+    pub unreferenced_terms: BTreeSet<String>,
+}
+
+/// How the variables of a message differ from those of the reference
+/// language, in a [`LocaleValidation`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PlaceholderMismatch {
+    /// Variables used by the reference language but not this locale.
+    pub missing: BTreeSet<String>,
+    /// Variables used by this locale but not the reference language.
+    pub unexpected: BTreeSet<String>,
+}
+
+impl LocaleValidation {
+    /// Returns whether no problems were found in this locale.
+    pub fn is_valid(&self) -> bool {
+        self.missing.is_empty()
+            && self.mismatched_placeholders.is_empty()
+            && self.unreferenced_terms.is_empty()
+    }
+}
+
+impl ValidationReport {
+    /// Validates the resources of each locale against those of `reference`.
+    /// `shared` are the resources added to every locale, whose terms aren't
+    /// reported, but whose references to terms are counted.
+    pub fn new<R: Borrow<FluentResource>>(
+        reference: &LanguageIdentifier,
+        locales: &HashMap<LanguageIdentifier, Vec<R>>,
+        shared: &[R],
+    ) -> Self {
+        let expected = locales
+            .get(reference)
+            .map(|resources| messages(reference, locales, shared, resources))
+            .unwrap_or_default();
+        let shared_terms = terms(shared);
+
+        let locales = locales
+            .iter()
+            .map(|(lang, resources)| {
+                let own = messages(lang, locales, shared, resources);
+                let inherited = crate::loader::parent_locales(lang, locales)
+                    .into_iter()
+                    .flat_map(|parent| message_entries(&locales[parent]))
+                    .map(|message| message.id.name)
+                    .collect::<BTreeSet<_>>();
+                let missing = expected
+                    .keys()
+                    .filter(|id| !own.contains_key(*id) && !inherited.contains(*id))
+                    .map(|id| id.to_string())
+                    .collect();
+
+                let mismatched_placeholders = own
+                    .iter()
+                    .filter_map(|(id, vars)| {
+                        let expected = expected.get(id)?;
+                        let mismatch = PlaceholderMismatch {
+                            missing: expected
+                                .difference(vars)
+                                .map(|var| var.to_string())
+                                .collect(),
+                            unexpected: vars
+                                .difference(expected)
+                                .map(|var| var.to_string())
+                                .collect(),
+                        };
+                        let mismatched =
+                            !mismatch.missing.is_empty() || !mismatch.unexpected.is_empty();
+                        mismatched.then(|| (id.to_string(), mismatch))
+                    })
+                    .collect();
+
+                let referenced = terms(resources);
+                let unreferenced_terms = resources
+                    .iter()
+                    .flat_map(|resource| resource.borrow().entries())
+                    .filter_map(|entry| match entry {
+                        ast::Entry::Term(term) => Some(term.id.name),
+                        _ => None,
+                    })
+                    .filter(|id| !referenced.contains(id) && !shared_terms.contains(id))
+                    .map(str::to_owned)
+                    .collect();
+
+                let validation = LocaleValidation {
+                    missing,
+                    mismatched_placeholders,
+                    unreferenced_terms,
+                };
+                (lang.clone(), validation)
+            })
+            .collect();
+
+        Self {
+            reference: reference.clone(),
+            locales,
+        }
+    }
+
+    /// Returns whether no problems were found in any locale.
+    pub fn is_valid(&self) -> bool {
+        self.locales.values().all(LocaleValidation::is_valid)
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |items: &BTreeSet<String>, prefix: &str| {
+            items
+                .iter()
+                .map(|item| format!("{prefix}{item}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        for (lang, validation) in &self.locales {
+            if validation.is_valid() {
+                continue;
+            }
+            writeln!(f, "{lang}:")?;
+            for id in &validation.missing {
+                writeln!(f, "  missing: {id}")?;
+            }
+            for (id, mismatch) in &validation.mismatched_placeholders {
+                write!(f, "  mismatched placeholders: {id}")?;
+                if !mismatch.missing.is_empty() {
+                    write!(f, " (missing {})", list(&mismatch.missing, "$"))?;
+                }
+                if !mismatch.unexpected.is_empty() {
+                    write!(f, " (unexpected {})", list(&mismatch.unexpected, "$"))?;
+                }
+                writeln!(f)?;
+            }
+            for id in &validation.unreferenced_terms {
+                writeln!(f, "  unreferenced term: -{id}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the variables that the value and attributes of each message
+/// defined in `resources` need, keyed by message ID, see [`References`].
+/// References to other messages are resolved like a bundle of `lang` would,
+/// in `resources`, then the resources of its parent locales, then `shared`.
+fn messages<'a, R: Borrow<FluentResource>>(
+    lang: &LanguageIdentifier,
+    locales: &'a HashMap<LanguageIdentifier, Vec<R>>,
+    shared: &'a [R],
+    resources: &'a [R],
+) -> BTreeMap<&'a str, BTreeSet<&'a str>> {
+    let mut layers = vec![resources];
+    layers.extend(
+        crate::loader::parent_locales(lang, locales)
+            .into_iter()
+            .map(|parent| locales[parent].as_slice()),
+    );
+    layers.push(shared);
+    let resolve = |text_id: &str| {
+        let (id, attribute) = match text_id.split_once('.') {
+            Some((id, attribute)) => (id, Some(attribute)),
+            None => (text_id, None),
+        };
+        let message = layers
+            .iter()
+            .flat_map(|resources| message_entries(resources))
+            .find(|message| message.id.name == id)?;
+        match attribute {
+            Some(attribute) => message
+                .attributes
+                .iter()
+                .find(|candidate| candidate.id.name == attribute)
+                .map(|attribute| &attribute.value),
+            None => message.value.as_ref(),
+        }
+    };
+
+    message_entries(resources)
+        .map(|message| {
+            let mut references = References::new(resolve);
+            references.message(message.id.name);
+            for attribute in &message.attributes {
+                references.message(&format!("{}.{}", message.id.name, attribute.id.name));
+            }
+            let variables = references.variables.into_iter().collect();
+            (message.id.name, variables)
+        })
+        .collect()
+}
+
+/// Returns the messages defined in `resources`.
+fn message_entries<R: Borrow<FluentResource>>(
+    resources: &[R],
+) -> impl Iterator<Item = &ast::Message<&str>> {
+    resources
+        .iter()
+        .flat_map(|resource| resource.borrow().entries())
+        .filter_map(|entry| match entry {
+            ast::Entry::Message(message) => Some(message),
+            _ => None,
+        })
+}
+
+/// Returns the terms, without their leading `-`, that the messages and terms
+/// in `resources` refer to.
+fn terms<R: Borrow<FluentResource>>(resources: &[R]) -> BTreeSet<&str> {
+    // Every message is walked, so references to them needn't be followed.
+    let mut references = References::new(|_| None);
+    for entry in resources
+        .iter()
+        .flat_map(|resource| resource.borrow().entries())
+    {
+        let (value, attributes) = match entry {
+            ast::Entry::Message(message) => (message.value.as_ref(), &message.attributes),
+            ast::Entry::Term(term) => (Some(&term.value), &term.attributes),
+            _ => continue,
+        };
+        for pattern in value
+            .into_iter()
+            .chain(attributes.iter().map(|attribute| &attribute.value))
+        {
+            references.pattern(pattern);
+        }
+    }
+    references.terms.into_iter().collect()
+}
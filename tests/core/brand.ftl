@@ -0,0 +1 @@
+-brand = Fluent Templates
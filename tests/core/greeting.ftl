@@ -0,0 +1 @@
+shared-greeting = Welcome to { -brand }, { -something }!
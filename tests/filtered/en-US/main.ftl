@@ -0,0 +1 @@
+simple = filtered text
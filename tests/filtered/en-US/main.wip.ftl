@@ -0,0 +1 @@
+simple = draft text
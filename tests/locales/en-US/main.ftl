@@ -10,3 +10,8 @@ parameter2 = text one { $param } second { $multi-word-param }
 email = text with an EMAIL("example@example.org")
 
 fallback = this should fall back
+
+items = { $count ->
+    [one] One item in { $place }
+   *[other] { $count } items in { $place }
+}
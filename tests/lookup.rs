@@ -0,0 +1,1678 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use fluent_templates::coverage::CoverageReport;
+use fluent_templates::fluent_bundle::FluentResource;
+use fluent_templates::{
+    fluent_args, ArcLoader, ArcLoaderBuilder, Duplicates, DynLoader, LoaderExt, LookupError,
+    MultiLoader, NegotiationStrategy, OnMissing, OnParseError, OverlayLoader, UsageTrackingLoader,
+};
+use unic_langid::{langid, LanguageIdentifier};
+
+const US_ENGLISH: LanguageIdentifier = langid!("en-US");
+const FRENCH: LanguageIdentifier = langid!("fr");
+const JAPANESE: LanguageIdentifier = langid!("ja");
+
+fluent_templates::static_loader! {
+    static LOCALES = {
+        locales: "./tests/locales",
+        fallback_language: "en-US",
+        core_locales: "./tests/locales/core.ftl",
+        customise: |bundle| bundle.set_use_isolating(false),
+    };
+}
+
+fn arc_loader() -> ArcLoader {
+    ArcLoader::builder("./tests/locales", US_ENGLISH)
+        .shared_resources(Some(&["./tests/locales/core.ftl".into()]))
+        .customize(|bundle| bundle.set_use_isolating(false))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn lookup_cow_borrows_static_messages() {
+    let arc = arc_loader();
+    let loaders: [&dyn DynLoader; 2] = [&*LOCALES, &arc];
+
+    for loader in loaders {
+        let text = loader.lookup_cow(&FRENCH, "simple", None);
+        assert!(matches!(text, Cow::Borrowed("texte simple")));
+
+        let args = fluent_args!["param" => "PARAM"];
+        let text = loader.lookup_cow(&US_ENGLISH, "parameter", Some(&args));
+        assert!(matches!(text, Cow::Owned(_)));
+        assert_eq!("text with a PARAM", text);
+
+        assert_eq!(
+            "Unknown localization missing",
+            loader.lookup_cow(&US_ENGLISH, "missing", None)
+        );
+        assert_eq!(None, loader.try_lookup_cow(&US_ENGLISH, "missing", None));
+    }
+}
+
+#[test]
+fn lookup_result_distinguishes_failures() {
+    let arc = arc_loader();
+    let loaders: [&dyn DynLoader; 2] = [&*LOCALES, &arc];
+
+    for loader in loaders {
+        assert_eq!(
+            "texte simple",
+            loader.lookup_result(&FRENCH, "simple").unwrap()
+        );
+        assert_eq!(
+            "Hello World!",
+            loader.lookup_result(&JAPANESE, "hello-world").unwrap()
+        );
+        assert!(matches!(
+            loader.lookup_result(&US_ENGLISH, "missing"),
+            Err(LookupError::MessageRetrieval(id)) if id == "missing"
+        ));
+        assert!(matches!(
+            loader.lookup_result(&JAPANESE, "missing"),
+            Err(LookupError::LangNotLoaded(lang)) if lang == JAPANESE
+        ));
+        assert!(matches!(
+            loader.lookup_result(&FRENCH, "parameter"),
+            Err(LookupError::FluentError(_))
+        ));
+        assert!(matches!(
+            loader.lookup_result(&FRENCH, "greeting.missing"),
+            Err(LookupError::Exhausted { last_error, .. })
+                if matches!(*last_error, LookupError::AttributeNotFound { .. })
+        ));
+
+        let args = fluent_args!["param" => "PARAM"];
+        assert_eq!(
+            "texte avec une PARAM",
+            loader
+                .lookup_complete_result(&FRENCH, "parameter", Some(&args))
+                .unwrap()
+        );
+    }
+}
+
+#[test]
+fn has_checks_fallback_chain() {
+    let arc = arc_loader();
+    let loaders: [&dyn DynLoader; 2] = [&*LOCALES, &arc];
+
+    for loader in loaders {
+        assert!(loader.has(&FRENCH, "simple"));
+        assert!(loader.has(&FRENCH, "parameter"));
+        assert!(loader.has(&FRENCH, "fallback"));
+        assert!(loader.has(&JAPANESE, "hello-world"));
+        assert!(loader.has(&FRENCH, "greeting.placeholder"));
+        assert!(!loader.has(&FRENCH, "greeting.missing"));
+        assert!(!loader.has(&FRENCH, "missing"));
+    }
+}
+
+#[test]
+fn message_ids_per_locale() {
+    let arc = arc_loader();
+    let loaders: [&dyn DynLoader; 2] = [&*LOCALES, &arc];
+
+    for loader in loaders {
+        let ids = loader.message_ids(&langid!("zh-CN")).collect::<Vec<_>>();
+        assert_eq!(vec!["exists"], ids);
+
+        let mut ids = loader.message_ids(&US_ENGLISH).collect::<Vec<_>>();
+        ids.sort_unstable();
+        assert_eq!(
+            vec![
+                "email",
+                "fallback",
+                "greeting",
+                "hello-world",
+                "items",
+                "parameter",
+                "parameter2",
+                "reference",
+                "simple"
+            ],
+            ids
+        );
+
+        assert_eq!(0, loader.message_ids(&JAPANESE).count());
+
+        let all = loader.all_message_ids();
+        assert!(all.contains("fallback-zh"));
+        assert!(all.contains("hello-world"));
+        assert!(!all.contains("-something"));
+    }
+}
+
+#[test]
+fn lookup_result_reports_fallback_chain() {
+    let arc = arc_loader();
+    let loaders: [&dyn DynLoader; 2] = [&*LOCALES, &arc];
+
+    for loader in loaders {
+        let error = loader
+            .lookup_result(&langid!("de-AT"), "missing")
+            .unwrap_err();
+        assert_eq!(
+            "Couldn't retrieve message with ID `missing` (tried de, en-US)",
+            error.to_string()
+        );
+        match error {
+            LookupError::Exhausted {
+                requested,
+                chain_tried,
+                last_error,
+            } => {
+                assert_eq!(langid!("de-AT"), requested);
+                assert_eq!(vec![langid!("de"), US_ENGLISH], chain_tried);
+                assert!(matches!(*last_error, LookupError::MessageRetrieval(_)));
+            }
+            error => panic!("unexpected error: {error:?}"),
+        }
+    }
+}
+
+#[test]
+fn terms_are_listed_per_locale() {
+    let arc = arc_loader();
+    let loaders: [&dyn DynLoader; 2] = [&*LOCALES, &arc];
+
+    for loader in loaders {
+        assert!(loader.term_ids(&FRENCH).any(|id| id == "something"));
+        assert_eq!(0, loader.term_ids(&JAPANESE).count());
+
+        #[cfg(debug_assertions)]
+        {
+            assert_eq!(
+                Some("foo"),
+                loader.format_term(&FRENCH, "-something", None).as_deref()
+            );
+            assert_eq!(
+                Some("foo"),
+                loader.format_term(&FRENCH, "something", None).as_deref()
+            );
+            assert_eq!(None, loader.format_term(&FRENCH, "-missing", None));
+            assert_eq!(None, loader.format_term(&JAPANESE, "-something", None));
+        }
+    }
+}
+
+#[test]
+fn locales_sorted_is_stable() {
+    let expected = vec![
+        langid!("de"),
+        US_ENGLISH,
+        FRENCH,
+        langid!("zh"),
+        langid!("zh-CN"),
+        langid!("zh-TW"),
+    ];
+
+    let shared: std::sync::Arc<dyn DynLoader + Send + Sync> = std::sync::Arc::new(arc_loader());
+    assert_eq!(expected, shared.locales_sorted());
+    assert_eq!(expected, LOCALES.locales_sorted());
+
+    let multi = MultiLoader::from_iter([
+        Box::new(arc_loader()) as Box<dyn DynLoader>,
+        Box::new(&*LOCALES),
+    ]);
+    assert_eq!(expected, multi.locales_sorted());
+}
+
+#[test]
+fn required_args_of_message() {
+    let arc = arc_loader();
+    let loaders: [&dyn DynLoader; 2] = [&*LOCALES, &arc];
+
+    for loader in loaders {
+        assert_eq!(
+            vec!["param", "multi-word-param"],
+            loader.required_args(&US_ENGLISH, "parameter2")
+        );
+        assert_eq!(
+            vec!["count", "place"],
+            loader.required_args(&FRENCH, "items")
+        );
+        assert_eq!(vec!["name"], loader.required_args(&FRENCH, "greeting"));
+        assert!(loader.required_args(&FRENCH, "hello-world").is_empty());
+        assert!(loader.required_args(&FRENCH, "missing").is_empty());
+    }
+}
+
+#[test]
+fn static_loader_builds_bundles_lazily() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CUSTOMISED: AtomicUsize = AtomicUsize::new(0);
+
+    fluent_templates::static_loader! {
+        static LAZY_LOCALES = {
+            locales: "./tests/locales",
+            fallback_language: "en-US",
+            customise: |_bundle| {
+                CUSTOMISED.fetch_add(1, Ordering::SeqCst);
+            },
+        };
+    }
+
+    assert_eq!(6, LAZY_LOCALES.locales().count());
+    assert_eq!(0, CUSTOMISED.load(Ordering::SeqCst));
+    assert_eq!(
+        "Hello World!",
+        LAZY_LOCALES.lookup(&US_ENGLISH, "hello-world")
+    );
+    assert_eq!(1, CUSTOMISED.load(Ordering::SeqCst));
+    assert_eq!(
+        "Bonjour le monde!",
+        LAZY_LOCALES.lookup(&FRENCH, "hello-world")
+    );
+    assert_eq!(
+        "Bonjour le monde!",
+        LAZY_LOCALES.lookup(&FRENCH, "hello-world")
+    );
+    assert_eq!(2, CUSTOMISED.load(Ordering::SeqCst));
+}
+
+#[test]
+fn bundles_are_exposed() {
+    let arc = arc_loader();
+
+    let static_bundle = LOCALES.get_bundle(&FRENCH).unwrap();
+    let arc_bundle = arc.get_bundle(&FRENCH).unwrap();
+    assert!(static_bundle.has_message("hello-world"));
+    assert!(arc_bundle.has_message("hello-world"));
+
+    let message = arc_bundle.get_message("greeting").unwrap();
+    let args = fluent_args!["name" => "Alice"];
+    let mut errors = Vec::new();
+    assert_eq!(
+        "Bonjour Alice!",
+        arc_bundle.format_pattern(message.value().unwrap(), Some(&args), &mut errors)
+    );
+    assert!(errors.is_empty());
+
+    assert!(LOCALES.get_bundle(&JAPANESE).is_none());
+    assert!(arc.get_bundle(&JAPANESE).is_none());
+}
+
+#[test]
+fn overlay_loader_layers_per_language() {
+    let dir = tempfile::tempdir().unwrap();
+    for (lang, ftl) in [
+        ("en-US", "hello-world = Hi World!"),
+        ("fr", "greeting = Salut { $name }!"),
+    ] {
+        std::fs::create_dir(dir.path().join(lang)).unwrap();
+        std::fs::write(dir.path().join(lang).join("main.ftl"), ftl).unwrap();
+    }
+    let overrides = || {
+        ArcLoader::builder(dir.path(), US_ENGLISH)
+            .customize(|bundle| bundle.set_use_isolating(false))
+            .build()
+            .unwrap()
+    };
+
+    let overlay = OverlayLoader::new(&*LOCALES, overrides());
+    assert_eq!("Hi World!", overlay.lookup(&US_ENGLISH, "hello-world"));
+    assert_eq!("Bonjour le monde!", overlay.lookup(&FRENCH, "hello-world"));
+    assert_eq!(
+        "Salut Alice!",
+        overlay.lookup_with_args(&FRENCH, "greeting", [("name", "Alice")])
+    );
+    assert_eq!(
+        "Salut l'ami!",
+        overlay.lookup(&FRENCH, "greeting.placeholder")
+    );
+    assert_eq!("this should fall back", overlay.lookup(&FRENCH, "fallback"));
+    assert_eq!("Hi World!", overlay.lookup(&JAPANESE, "hello-world"));
+    assert!(overlay.lookup_result(&FRENCH, "missing").is_err());
+    assert_eq!(
+        "Unknown localization missing",
+        overlay.lookup(&FRENCH, "missing")
+    );
+
+    // A `MultiLoader` lets the override's fallback language win instead.
+    let multi = MultiLoader::from_iter([
+        Box::new(overrides()) as Box<dyn DynLoader>,
+        Box::new(&*LOCALES),
+    ]);
+    assert_eq!("Hi World!", multi.lookup(&FRENCH, "hello-world"));
+}
+
+#[test]
+fn static_loader_rebuilt_with_customizer() {
+    fn shout(text: &str) -> Cow<'_, str> {
+        Cow::Owned(text.to_uppercase())
+    }
+
+    let rebuilt = LOCALES.rebuilt_with(|bundle| {
+        bundle.set_use_isolating(true);
+        bundle.set_transform(Some(shout));
+    });
+
+    assert_eq!(
+        "HELLO \u{2068}Alice\u{2069}!",
+        rebuilt.lookup_with_args(&US_ENGLISH, "greeting", [("name", "Alice")])
+    );
+    assert_eq!("BONJOUR LE MONDE!", rebuilt.lookup(&FRENCH, "hello-world"));
+    assert_eq!(
+        "Hello Alice!",
+        LOCALES.lookup_with_args(&US_ENGLISH, "greeting", [("name", "Alice")])
+    );
+}
+
+#[test]
+fn customise_with_lang() {
+    fn shout(text: &str) -> Cow<'_, str> {
+        Cow::Owned(text.to_uppercase())
+    }
+
+    fluent_templates::static_loader! {
+        static SHOUTY_FRENCH = {
+            locales: "./tests/locales",
+            fallback_language: "en-US",
+            customise: |bundle, lang| {
+                if *lang == FRENCH {
+                    bundle.set_transform(Some(shout));
+                }
+            },
+        };
+    }
+
+    let arc = ArcLoader::builder("./tests/locales", US_ENGLISH)
+        .customize_with_lang(|bundle, lang| {
+            if *lang == FRENCH {
+                bundle.set_transform(Some(shout));
+            }
+        })
+        .build()
+        .unwrap();
+    let loaders: [&dyn DynLoader; 2] = [&*SHOUTY_FRENCH, &arc];
+
+    for loader in loaders {
+        assert_eq!("BONJOUR LE MONDE!", loader.lookup(&FRENCH, "hello-world"));
+        assert_eq!("Hello World!", loader.lookup(&US_ENGLISH, "hello-world"));
+    }
+}
+
+#[cfg(feature = "compress")]
+#[test]
+fn compressed_static_loader() {
+    fluent_templates::static_loader! {
+        static COMPRESSED = {
+            locales: "./tests/locales",
+            fallback_language: "en-US",
+            core_locales: "./tests/locales/core.ftl",
+            customise: |bundle| bundle.set_use_isolating(false),
+            compress: true,
+        };
+    }
+
+    for lang in [US_ENGLISH, FRENCH, JAPANESE] {
+        for text_id in ["simple", "reference", "fallback"] {
+            assert_eq!(
+                LOCALES.lookup(&lang, text_id),
+                COMPRESSED.lookup(&lang, text_id)
+            );
+        }
+    }
+    assert_eq!(
+        LOCALES.term_ids(&US_ENGLISH).collect::<Vec<_>>(),
+        COMPRESSED.term_ids(&US_ENGLISH).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn static_loader_try_init() {
+    let loader = LOCALES.try_init().unwrap();
+    assert!(std::ptr::eq(loader, LOCALES.force()));
+    assert_eq!("simple text", loader.lookup(&US_ENGLISH, "simple"));
+}
+
+#[test]
+fn loader_stats() {
+    let arc = arc_loader();
+    let loaders: [&dyn DynLoader; 2] = [&*LOCALES, &arc];
+
+    for loader in loaders {
+        let stats = loader.stats();
+        assert_eq!(loader.locales_sorted().len(), stats.locale_count());
+        let english = stats.locales[&US_ENGLISH];
+        assert_eq!(
+            english.messages,
+            loader.message_ids(&US_ENGLISH).collect::<Vec<_>>().len()
+        );
+        // The locale's own resources and the shared `core.ftl`.
+        assert!(english.resources >= 2);
+    }
+
+    let mut multi = MultiLoader::new();
+    multi.push_back(Box::new(arc_loader()));
+    multi.push_back(Box::new(arc_loader()));
+    let stats = multi.stats();
+    assert_eq!(
+        stats.locales[&US_ENGLISH].messages,
+        arc.stats().locales[&US_ENGLISH].messages
+    );
+    assert_eq!(2 * arc.stats().resource_count(), stats.resource_count());
+}
+
+#[test]
+fn loaders_are_debug() {
+    let debug = format!("{:?}", LOCALES.force());
+    assert!(debug.starts_with("StaticLoader {"));
+    assert!(debug.contains(r#"fallback: "en-US""#));
+    assert!(format!("{:?}", arc_loader()).contains(r#""fr""#));
+    assert!(format!("{:?}", MultiLoader::new()).contains("loaders: 0"));
+}
+
+#[test]
+fn static_loader_fallback_overrides() {
+    fluent_templates::static_loader! {
+        static OVERRIDDEN = {
+            locales: "./tests/locales",
+            fallback_language: "en-US",
+            fallback_overrides: { "de": ["fr"], "nn": ["fr"] },
+        };
+    }
+
+    let german = langid!("de");
+    let norwegian = langid!("nn");
+    assert_eq!("texte simple", OVERRIDDEN.lookup(&german, "simple"));
+    assert_eq!("Hallo Welt!", OVERRIDDEN.lookup(&german, "hello-world"));
+    assert_eq!(
+        "Bonjour le monde!",
+        OVERRIDDEN.lookup(&norwegian, "hello-world")
+    );
+    assert_eq!(
+        vec![german.clone(), FRENCH, US_ENGLISH],
+        OVERRIDDEN.fallback_chain(&german)
+    );
+    assert_eq!(
+        Some("texte simple".to_owned()),
+        OVERRIDDEN.lookup_no_default_fallback(&norwegian, "simple", None)
+    );
+    assert_eq!("simple text", LOCALES.lookup(&german, "simple"));
+}
+
+#[test]
+fn static_loader_negotiation_strategy() {
+    fluent_templates::static_loader! {
+        static MATCHING = {
+            locales: "./tests/locales",
+            fallback_language: "en-US",
+            negotiation_strategy: "matching",
+        };
+    }
+
+    let simplified = langid!("zh-CN");
+    assert_eq!(
+        vec![simplified.clone(), US_ENGLISH],
+        MATCHING.fallback_chain(&simplified)
+    );
+    assert_eq!(
+        vec![simplified.clone(), langid!("zh"), US_ENGLISH],
+        LOCALES.fallback_chain(&simplified)
+    );
+    let filtering = LOCALES
+        .rebuilt_with(|_| ())
+        .with_negotiation_strategy(NegotiationStrategy::Filtering);
+    assert_eq!(
+        vec![
+            simplified.clone(),
+            langid!("zh"),
+            langid!("zh-TW"),
+            US_ENGLISH
+        ],
+        filtering.fallback_chain(&simplified)
+    );
+
+    // Lookups without the default fallback use the same strategy, so `zh`
+    // only falls back to the more specific `zh-CN` or `zh-TW` when
+    // filtering.
+    let chinese = langid!("zh");
+    assert_eq!(
+        vec![chinese.clone(), US_ENGLISH],
+        LOCALES.fallback_chain(&chinese)
+    );
+    assert_eq!(
+        None,
+        LOCALES.lookup_no_default_fallback(&chinese, "exists", None)
+    );
+    assert!(filtering
+        .lookup_no_default_fallback(&chinese, "exists", None)
+        .is_some());
+}
+
+#[test]
+fn static_loader_language_aliases() {
+    fluent_templates::static_loader! {
+        static ALIASED = {
+            locales: "./tests/locales",
+            fallback_language: "en-US",
+            language_aliases: { "iw": "fr", "in-ID": "de" },
+        };
+    }
+
+    let hebrew = langid!("iw-IL");
+    assert_eq!("Bonjour le monde!", ALIASED.lookup(&hebrew, "hello-world"));
+    assert_eq!(
+        "Hallo Welt!",
+        ALIASED.lookup(&langid!("in-ID"), "hello-world")
+    );
+    assert_eq!(
+        "Hello World!",
+        ALIASED.lookup(&langid!("in"), "hello-world")
+    );
+    assert_eq!(
+        Some("texte simple".to_owned()),
+        ALIASED.lookup_no_default_fallback(&langid!("iw"), "simple", None)
+    );
+}
+
+// The incomplete test locales are reported as a deprecation warning.
+#[allow(deprecated)]
+#[test]
+fn static_loader_coverage_warning() {
+    fluent_templates::static_loader! {
+        static CHECKED = {
+            locales: "./tests/locales",
+            fallback_language: "en-US",
+            check_coverage: "warn",
+        };
+    }
+
+    assert_eq!("texte simple", CHECKED.lookup(&FRENCH, "simple"));
+}
+
+#[allow(deprecated)]
+#[test]
+#[should_panic(expected = "Failed to add FTL resources to the bundle.")]
+fn build_bundles_rejects_duplicates() {
+    let resource = || FluentResource::try_new("simple = text\n".to_owned()).unwrap();
+    let resources = Box::leak(Box::new(HashMap::from([(
+        US_ENGLISH,
+        vec![resource(), resource()],
+    )])));
+    fluent_templates::loader::build_bundles(resources, None, |_| {});
+}
+
+#[test]
+fn static_loader_placeholders_are_consistent() {
+    fluent_templates::static_loader! {
+        static CHECKED = {
+            locales: "./tests/locales",
+            fallback_language: "en-US",
+            customise: |bundle| bundle.set_use_isolating(false),
+            check_placeholders: "deny",
+        };
+    }
+
+    assert_eq!(
+        "Bonjour Alice!",
+        CHECKED.lookup_with_args(&FRENCH, "greeting", [("name", "Alice")])
+    );
+}
+
+#[test]
+fn static_loader_without_orphaned_messages() {
+    fluent_templates::static_loader! {
+        static CHECKED = {
+            locales: "./tests/regional",
+            fallback_language: "en-US",
+            check_orphans: "deny",
+        };
+    }
+
+    assert!(CHECKED.has(&langid!("de-AT"), "invoice-due"));
+}
+
+#[test]
+fn static_loader_keys_module() {
+    fluent_templates::static_loader! {
+        static KEYED = {
+            locales: "./tests/locales",
+            fallback_language: "en-US",
+            keys_module: keys,
+        };
+    }
+
+    assert_eq!("hello-world", keys::HELLO_WORLD);
+    assert_eq!("Hello World!", KEYED.lookup(&US_ENGLISH, keys::HELLO_WORLD));
+    assert_eq!("parameter2", keys::PARAMETER2);
+}
+
+#[test]
+fn static_loader_merges_locales_directories() {
+    fluent_templates::static_loader! {
+        static MERGED = {
+            locales: ["./tests/locales", "./tests/overrides"],
+            fallback_language: "en-US",
+            // Later directories override earlier ones even when duplicates
+            // are denied.
+            duplicates: "deny",
+        };
+    }
+
+    assert_eq!("overridden text", MERGED.lookup(&US_ENGLISH, "simple"));
+    assert_eq!(
+        "only in the overrides",
+        MERGED.lookup(&US_ENGLISH, "only-overridden")
+    );
+    assert_eq!("Hello World!", MERGED.lookup(&US_ENGLISH, "hello-world"));
+    assert_eq!("texte simple", MERGED.lookup(&FRENCH, "simple"));
+}
+
+#[test]
+fn static_loader_filters_files() {
+    fluent_templates::static_loader! {
+        static RELEASED = {
+            locales: "./tests/filtered",
+            fallback_language: "en-US",
+            exclude: "*.wip.ftl",
+        };
+    }
+    fluent_templates::static_loader! {
+        static UI = {
+            locales: "./tests/filtered",
+            fallback_language: "en-US",
+            include: ["ui/**"],
+        };
+    }
+
+    assert_eq!("filtered text", RELEASED.lookup(&US_ENGLISH, "simple"));
+    assert_eq!("OK", RELEASED.lookup(&US_ENGLISH, "ok"));
+    assert!(!UI.has(&US_ENGLISH, "simple"));
+    assert_eq!("OK", UI.lookup(&US_ENGLISH, "ok"));
+}
+
+#[test]
+fn static_loader_paths_from_env() {
+    fluent_templates::static_loader! {
+        static FROM_ENV = {
+            locales: concat!(env!("CARGO_MANIFEST_DIR"), "/tests/locales"),
+            core_locales: concat!(env!("CARGO_MANIFEST_DIR"), "/tests/locales/core.ftl"),
+            fallback_language: "en-US",
+            customise: |bundle| bundle.set_use_isolating(false),
+        };
+    }
+
+    assert_eq!(
+        "simple text with a reference: foo",
+        FROM_ENV.lookup(&US_ENGLISH, "reference")
+    );
+}
+
+#[test]
+fn static_loader_only_languages() {
+    fluent_templates::static_loader! {
+        static SUBSET = {
+            locales: "./tests/locales",
+            fallback_language: "en-US",
+            only_languages: ["en-US", "fr"],
+        };
+    }
+
+    assert_eq!(vec![US_ENGLISH, FRENCH], SUBSET.locales_sorted());
+    assert_eq!("Hello World!", SUBSET.lookup(&langid!("de"), "hello-world"));
+}
+
+#[test]
+fn loaders_with_extensions() {
+    fluent_templates::static_loader! {
+        static EXPORTED = {
+            locales: "./tests/extensions",
+            fallback_language: "en-US",
+            extensions: ["ftl.txt", "flt"],
+        };
+    }
+    let arc = ArcLoader::builder("./tests/extensions", US_ENGLISH)
+        .extensions(["ftl.txt", "flt"])
+        .build()
+        .unwrap();
+    let loaders: [&dyn DynLoader; 2] = [&*EXPORTED, &arc];
+
+    for loader in loaders {
+        assert_eq!("exported text", loader.lookup(&US_ENGLISH, "simple"));
+        assert_eq!("other text", loader.lookup(&US_ENGLISH, "other"));
+    }
+}
+
+#[test]
+fn loaders_with_shared_resource_directory() {
+    fluent_templates::static_loader! {
+        static SHARED = {
+            locales: "./tests/locales",
+            fallback_language: "en-US",
+            core_locales: ["./tests/locales/core.ftl", "./tests/core"],
+            customise: |bundle| bundle.set_use_isolating(false),
+        };
+    }
+    let shared = ["./tests/locales/core.ftl".into(), "./tests/core".into()];
+    let arc = ArcLoader::builder("./tests/locales", US_ENGLISH)
+        .shared_resources(Some(&shared))
+        .customize(|bundle| bundle.set_use_isolating(false))
+        .build()
+        .unwrap();
+    let loaders: [&dyn DynLoader; 2] = [&*SHARED, &arc];
+
+    for loader in loaders {
+        assert_eq!(
+            "Welcome to Fluent Templates, foo!",
+            loader.lookup(&FRENCH, "shared-greeting")
+        );
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn arc_loader_builds_asynchronously() {
+    let shared = ["./tests/locales/core.ftl".into(), "./tests/core".into()];
+    let build = || {
+        ArcLoader::builder("./tests/locales", US_ENGLISH)
+            .shared_resources(Some(&shared))
+            .customize(|bundle| bundle.set_use_isolating(false))
+    };
+    let sync = build().build().unwrap();
+    let async_ = build().build_async().await.unwrap();
+
+    assert_eq!(sync.stats(), async_.stats());
+    assert_eq!(
+        "Welcome to Fluent Templates, foo!",
+        async_.lookup(&FRENCH, "shared-greeting")
+    );
+}
+
+#[test]
+fn static_loader_constructor_function() {
+    fluent_templates::static_loader! {
+        fn build_locales = {
+            locales: "./tests/locales",
+            fallback_language: "en-US",
+        };
+    }
+
+    let isolated = build_locales();
+    let plain = build_locales().rebuilt_with(|bundle| bundle.set_use_isolating(false));
+    let args = [("name", "Alice")];
+    assert_eq!(
+        "Hello \u{2068}Alice\u{2069}!",
+        isolated.lookup_with_args(&US_ENGLISH, "greeting", args)
+    );
+    assert_eq!(
+        "Hello Alice!",
+        plain.lookup_with_args(&US_ENGLISH, "greeting", args)
+    );
+}
+
+#[test]
+fn regional_locales_inherit_resources() {
+    fluent_templates::static_loader! {
+        static REGIONAL = {
+            locales: "./tests/regional",
+            fallback_language: "en-US",
+        };
+    }
+    let arc = ArcLoader::builder("./tests/regional", US_ENGLISH)
+        .build()
+        .unwrap();
+    let loaders: [&dyn DynLoader; 2] = [&*REGIONAL, &arc];
+
+    for loader in loaders {
+        assert_eq!(
+            "Fällig im Jänner",
+            loader.lookup(&langid!("de-AT"), "invoice-due")
+        );
+        assert_eq!(
+            "Fällig im Januar",
+            loader.lookup(&langid!("de"), "invoice-due")
+        );
+    }
+}
+
+#[test]
+fn arc_loader_locales_change_at_runtime() {
+    let mut arc = ArcLoader::builder("./tests/regional", US_ENGLISH)
+        .build()
+        .unwrap();
+    let austrian = langid!("de-AT");
+
+    arc.update_resource(
+        langid!("de"),
+        "-january = Januar\ninvoice-due = Zahlbar im { -january }",
+    )
+    .unwrap();
+    assert_eq!("Zahlbar im Jänner", arc.lookup(&austrian, "invoice-due"));
+
+    assert!(arc
+        .update_resource(FRENCH, "bonjour = Bonjour\nbonjour = Salut")
+        .is_err());
+    assert!(!arc.locales().any(|lang| *lang == FRENCH));
+
+    let resource = FluentResource::try_new("invoice-due = Échéance".to_owned()).unwrap();
+    arc.insert_locale(FRENCH, vec![resource]).unwrap();
+    assert_eq!("Échéance", arc.lookup(&FRENCH, "invoice-due"));
+
+    assert!(arc.remove_locale(&langid!("de")));
+    assert!(!arc.remove_locale(&langid!("de")));
+    assert_eq!("Due in January", arc.lookup(&austrian, "invoice-due"));
+}
+
+#[test]
+fn arc_loader_from_parsed_resources() {
+    let parse =
+        |source: &str| std::sync::Arc::new(FluentResource::try_new(source.to_owned()).unwrap());
+    let loader =
+        ArcLoaderBuilder::from_resources([(US_ENGLISH, vec![parse("hello = Hello")])], US_ENGLISH)
+            .add_resources(FRENCH, [parse("hello = Bonjour")])
+            .add_source(FRENCH, "goodbye = Au revoir")
+            .build()
+            .unwrap();
+
+    assert_eq!("Bonjour", loader.lookup(&FRENCH, "hello"));
+    assert_eq!("Au revoir", loader.lookup(&FRENCH, "goodbye"));
+    assert_eq!("Hello", loader.lookup(&JAPANESE, "hello"));
+}
+
+#[test]
+fn loaders_with_overriding_duplicates() {
+    fluent_templates::static_loader! {
+        static PATCHED = {
+            locales: "./tests/duplicates",
+            fallback_language: "en-US",
+            duplicates: "override",
+        };
+    }
+    let arc = ArcLoader::builder("./tests/duplicates", US_ENGLISH)
+        .duplicates(Duplicates::Override)
+        .build()
+        .unwrap();
+    let loaders: [&dyn DynLoader; 2] = [&*PATCHED, &arc];
+
+    for loader in loaders {
+        assert_eq!("Hi", loader.lookup(&US_ENGLISH, "greeting"));
+        assert_eq!("Goodbye", loader.lookup(&US_ENGLISH, "farewell"));
+    }
+    assert!(ArcLoader::builder("./tests/duplicates", US_ENGLISH)
+        .build()
+        .is_err());
+}
+
+#[test]
+fn loaders_normalise_locale_directory_names() {
+    fluent_templates::static_loader! {
+        static EXPORTED = {
+            locales: "./tests/aliases",
+            fallback_language: "en-US",
+            aliases: { "deutsch": "de" },
+        };
+    }
+    let arc = ArcLoader::builder("./tests/aliases", US_ENGLISH)
+        .aliases([("deutsch", langid!("de"))])
+        .build()
+        .unwrap();
+    let loaders: [&dyn DynLoader; 2] = [&*EXPORTED, &arc];
+
+    for loader in loaders {
+        assert_eq!(
+            vec![langid!("de"), US_ENGLISH, langid!("zh-Hans-CN")],
+            loader.locales_sorted()
+        );
+        assert_eq!("Hallo", loader.lookup(&langid!("de"), "hello"));
+        assert_eq!("你好", loader.lookup(&langid!("zh-Hans-CN"), "hello"));
+        assert_eq!("Hello", loader.lookup(&FRENCH, "hello"));
+    }
+}
+
+#[test]
+fn arc_loader_maps_locale_directories() {
+    let arc = ArcLoader::builder("./tests/aliases", US_ENGLISH)
+        .map_directory(|name| match name {
+            "zh_hans_cn" => Some(langid!("zh-Hans")),
+            "deutsch" => None,
+            _ => name.parse().ok(),
+        })
+        .build()
+        .unwrap();
+
+    assert_eq!(vec![US_ENGLISH, langid!("zh-Hans")], arc.locales_sorted());
+    assert_eq!("你好", arc.lookup(&langid!("zh-Hans"), "hello"));
+}
+
+#[test]
+fn arc_loader_filters_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let locale = dir.path().join("en-US");
+    std::fs::create_dir_all(locale.join("drafts")).unwrap();
+    std::fs::create_dir_all(locale.join("pages/nested")).unwrap();
+    std::fs::write(locale.join("main.ftl"), "main = Main").unwrap();
+    std::fs::write(locale.join("drafts/draft.ftl"), "draft = Draft").unwrap();
+    std::fs::write(locale.join("pages/page.ftl"), "page = Page").unwrap();
+    std::fs::write(locale.join("pages/nested/nested.ftl"), "nested = Nested").unwrap();
+
+    let message_ids = |loader: ArcLoader| {
+        let mut ids = loader
+            .message_ids(&US_ENGLISH)
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    };
+    let filtered = ArcLoader::builder(dir.path(), US_ENGLISH)
+        .filter_files(|path| !path.starts_with("drafts"))
+        .build()
+        .unwrap();
+    assert_eq!(vec!["main", "nested", "page"], message_ids(filtered));
+
+    let shallow = ArcLoader::builder(dir.path(), US_ENGLISH)
+        .max_depth(1)
+        .build()
+        .unwrap();
+    assert_eq!(vec!["draft", "main", "page"], message_ids(shallow));
+}
+
+#[test]
+fn arc_loader_skips_unparseable_resources() {
+    let dir = tempfile::tempdir().unwrap();
+    let locale = dir.path().join("en-US");
+    std::fs::create_dir(&locale).unwrap();
+    std::fs::write(locale.join("broken.ftl"), "broken = { $").unwrap();
+    std::fs::write(locale.join("main.ftl"), "main = Main").unwrap();
+
+    assert!(ArcLoader::builder(dir.path(), US_ENGLISH).build().is_err());
+
+    let arc = ArcLoader::builder(dir.path(), US_ENGLISH)
+        .on_parse_error(OnParseError::Skip)
+        .add_source(FRENCH, "main = { ")
+        .build()
+        .unwrap();
+    assert_eq!("Main", arc.lookup(&US_ENGLISH, "main"));
+
+    let report = arc.build_report();
+    assert!(!report.is_complete());
+    let skipped = report
+        .skipped
+        .iter()
+        .map(|skipped| skipped.origin.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            locale.join("broken.ftl").display().to_string().as_str(),
+            "a source added for fr",
+        ],
+        skipped
+    );
+}
+
+#[test]
+fn arc_loader_reads_added_files() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("messages.en-US.ftl"), "greeting = Hello").unwrap();
+    std::fs::write(dir.path().join("messages.fr.ftl"), "greeting = Bonjour").unwrap();
+
+    let loader = ArcLoaderBuilder::from_sources(HashMap::new(), US_ENGLISH)
+        .with_files([
+            (US_ENGLISH, dir.path().join("messages.en-US.ftl")),
+            (FRENCH, dir.path().join("messages.fr.ftl")),
+        ])
+        .build()
+        .unwrap();
+    assert_eq!("Bonjour", loader.lookup(&FRENCH, "greeting"));
+    assert_eq!("Hello", loader.lookup(&JAPANESE, "greeting"));
+
+    let missing = ArcLoaderBuilder::from_sources(HashMap::new(), US_ENGLISH)
+        .add_file(JAPANESE, dir.path().join("messages.ja.ftl"))
+        .build();
+    assert!(missing.is_err());
+}
+
+#[test]
+fn scoped_lookups_prefer_scope_resources() {
+    let german = langid!("de");
+    let austrian = langid!("de-AT");
+    let sources = HashMap::from([
+        (
+            US_ENGLISH,
+            vec!["-brand = Acme\ngreeting = Welcome to { -brand }".to_owned()],
+        ),
+        (german.clone(), vec!["greeting = Willkommen".to_owned()]),
+        (austrian.clone(), vec!["farewell = Servus".to_owned()]),
+    ]);
+    let mut loader = ArcLoaderBuilder::from_sources(sources, US_ENGLISH)
+        .customize(|bundle| bundle.set_use_isolating(false))
+        .build()
+        .unwrap();
+    let resource =
+        |src: &str| std::sync::Arc::new(FluentResource::try_new(src.to_owned()).unwrap());
+    loader
+        .add_scope(
+            "globex",
+            [
+                (US_ENGLISH, vec![resource("-brand = Globex")]),
+                (
+                    german.clone(),
+                    vec![resource("greeting = Willkommen bei Globex")],
+                ),
+            ],
+        )
+        .unwrap();
+
+    let globex = loader.scoped("globex");
+    assert_eq!("Welcome to Globex", globex.lookup(&US_ENGLISH, "greeting"));
+    assert_eq!(
+        "Willkommen bei Globex",
+        globex.lookup(&austrian, "greeting")
+    );
+    assert_eq!("Servus", globex.lookup(&austrian, "farewell"));
+    assert_eq!("Welcome to Acme", loader.lookup(&US_ENGLISH, "greeting"));
+    assert_eq!("Willkommen", loader.lookup(&austrian, "greeting"));
+    assert_eq!(
+        "Welcome to Acme",
+        loader.scoped("initech").lookup(&US_ENGLISH, "greeting")
+    );
+
+    loader
+        .update_resource(austrian.clone(), "farewell = Baba")
+        .unwrap();
+    let globex = loader.scoped("globex");
+    assert_eq!("Baba", globex.lookup(&austrian, "farewell"));
+    assert_eq!(
+        "Willkommen bei Globex",
+        globex.lookup(&austrian, "greeting")
+    );
+
+    assert!(loader.remove_scope("globex"));
+    assert_eq!(
+        "Welcome to Acme",
+        loader.scoped("globex").lookup(&US_ENGLISH, "greeting")
+    );
+}
+
+#[test]
+fn arc_loader_language_aliases() {
+    let bokmal = langid!("nb");
+    let sources = HashMap::from([
+        (US_ENGLISH, vec!["greeting = Hello".to_owned()]),
+        (bokmal.clone(), vec!["greeting = Hei".to_owned()]),
+    ]);
+    let loader = ArcLoaderBuilder::from_sources(sources, US_ENGLISH)
+        .alias(langid!("no"), bokmal.clone())
+        .build()
+        .unwrap();
+
+    let norwegian = langid!("no-NO");
+    assert_eq!("Hei", loader.lookup(&langid!("no"), "greeting"));
+    assert_eq!("Hei", loader.lookup(&norwegian, "greeting"));
+    assert_eq!(
+        "Hei",
+        loader.lookup_langs(&[langid!("no"), US_ENGLISH], "greeting", None)
+    );
+    assert_eq!(vec![bokmal, US_ENGLISH], loader.fallback_chain(&norwegian));
+    assert_eq!(
+        Some("Hei".to_owned()),
+        loader.lookup_no_default_fallback(&langid!("no"), "greeting", None)
+    );
+}
+
+#[test]
+fn arc_loader_clones_are_independent() {
+    fn assert_shareable<T: Clone + Send + Sync + 'static>(_: &T) {}
+
+    let mut loader = arc_loader();
+    loader
+        .add_scope(
+            "acme",
+            [(
+                US_ENGLISH,
+                vec![std::sync::Arc::new(
+                    FluentResource::try_new("hello-world = Hello from Acme!".to_owned()).unwrap(),
+                )],
+            )],
+        )
+        .unwrap();
+    let clone = loader.clone();
+    assert_shareable(&clone);
+    assert_eq!("Bonjour le monde!", clone.lookup(&FRENCH, "hello-world"));
+
+    loader
+        .update_resource(FRENCH, "hello-world = Salut le monde !")
+        .unwrap();
+    assert!(loader.remove_scope("acme"));
+    assert_eq!("Salut le monde !", loader.lookup(&FRENCH, "hello-world"));
+    assert_eq!("Bonjour le monde!", clone.lookup(&FRENCH, "hello-world"));
+    assert_eq!(
+        "Hello from Acme!",
+        clone.scoped("acme").lookup(&US_ENGLISH, "hello-world")
+    );
+}
+
+#[test]
+fn arc_loader_negotiation_strategy() {
+    let simplified = langid!("zh-CN");
+    let traditional = langid!("zh-TW");
+    let sources = HashMap::from([
+        (US_ENGLISH, vec!["greeting = Hello".to_owned()]),
+        (traditional.clone(), vec!["greeting = 你好".to_owned()]),
+    ]);
+    let build = |strategy| {
+        ArcLoaderBuilder::from_sources(sources.clone(), US_ENGLISH)
+            .negotiation_strategy(strategy)
+            .build()
+            .unwrap()
+    };
+
+    let strict = build(NegotiationStrategy::default());
+    assert_eq!("Hello", strict.lookup(&simplified, "greeting"));
+    assert_eq!(vec![US_ENGLISH], strict.fallback_chain(&simplified));
+
+    let lenient = build(NegotiationStrategy::Filtering);
+    assert_eq!("你好", lenient.lookup(&simplified, "greeting"));
+    assert_eq!(
+        vec![traditional, US_ENGLISH],
+        lenient.fallback_chain(&simplified)
+    );
+}
+
+#[test]
+fn reloadable_loader_looks_up_the_latest_resources() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("en-US")).unwrap();
+    std::fs::write(dir.path().join("en-US/main.ftl"), "greeting = Hello").unwrap();
+
+    let location = dir.path().to_owned();
+    let loader = fluent_templates::ReloadableLoader::new(move || {
+        ArcLoader::builder(&location, US_ENGLISH).build()
+    })
+    .unwrap();
+    let shared: &dyn DynLoader = &loader;
+    assert_eq!("Hello", shared.lookup(&US_ENGLISH, "greeting"));
+
+    std::fs::write(
+        dir.path().join("en-US/main.ftl"),
+        "greeting = Hello again\nfarewell = Bye",
+    )
+    .unwrap();
+    std::fs::create_dir(dir.path().join("fr")).unwrap();
+    std::fs::write(dir.path().join("fr/main.ftl"), "greeting = Bonjour").unwrap();
+    loader.reload().unwrap();
+
+    assert_eq!("Hello again", shared.lookup(&US_ENGLISH, "greeting"));
+    assert_eq!("Bonjour", shared.lookup(&FRENCH, "greeting"));
+    assert!(shared.has(&US_ENGLISH, "farewell"));
+    assert_eq!(vec![US_ENGLISH, FRENCH], shared.locales_sorted());
+    // IDs are borrowed, so they're listed from a snapshot.
+    assert_eq!(0, shared.all_message_ids().len());
+    assert_eq!(
+        ["farewell", "greeting"]
+            .into_iter()
+            .collect::<std::collections::BTreeSet<_>>(),
+        loader.current().all_message_ids()
+    );
+
+    std::fs::write(dir.path().join("fr/main.ftl"), "greeting = {").unwrap();
+    assert!(matches!(
+        loader.reload(),
+        Err(fluent_templates::LoaderError::Fluent { .. })
+    ));
+    assert_eq!("Bonjour", shared.lookup(&FRENCH, "greeting"));
+}
+
+#[test]
+fn reloadable_loader_reports_changed_locales() {
+    use std::sync::{Arc, Mutex};
+
+    let dir = tempfile::tempdir().unwrap();
+    for (lang, src) in [("en-US", "greeting = Hello"), ("fr", "greeting = Bonjour")] {
+        std::fs::create_dir(dir.path().join(lang)).unwrap();
+        std::fs::write(dir.path().join(lang).join("main.ftl"), src).unwrap();
+    }
+    let shared = [dir.path().join("shared.ftl")];
+    std::fs::write(&shared[0], "brand = Acme").unwrap();
+
+    let location = dir.path().to_owned();
+    let loader = fluent_templates::ReloadableLoader::new(move || {
+        ArcLoader::builder(&location, US_ENGLISH)
+            .shared_resources(Some(&shared))
+            .build()
+    })
+    .unwrap();
+    let updates = Arc::new(Mutex::new(Vec::new()));
+    let log = updates.clone();
+    loader.on_update(move |changed| log.lock().unwrap().push(changed.to_vec()));
+
+    loader.reload().unwrap();
+    std::fs::write(dir.path().join("fr/main.ftl"), "greeting = Salut").unwrap();
+    loader.reload().unwrap();
+    std::fs::create_dir(dir.path().join("ja")).unwrap();
+    std::fs::write(dir.path().join("ja/main.ftl"), "greeting = こんにちは").unwrap();
+    loader.reload().unwrap();
+    std::fs::write(dir.path().join("shared.ftl"), "brand = Acme Inc.").unwrap();
+    loader.reload().unwrap();
+
+    assert_eq!(
+        vec![
+            vec![FRENCH],
+            vec![JAPANESE],
+            vec![US_ENGLISH, FRENCH, JAPANESE]
+        ],
+        *updates.lock().unwrap()
+    );
+}
+
+#[cfg(feature = "watch")]
+#[test]
+fn watching_loader_reloads_changes() {
+    use std::time::{Duration, Instant};
+
+    let dir = tempfile::tempdir().unwrap();
+    let locale = dir.path().join("en-US");
+    std::fs::create_dir(&locale).unwrap();
+    std::fs::write(locale.join("main.ftl"), "greeting = Hello").unwrap();
+
+    let location = dir.path().to_owned();
+    let loader =
+        fluent_templates::WatchingLoader::new(dir.path(), Duration::from_millis(10), move || {
+            ArcLoader::builder(&location, US_ENGLISH).build()
+        })
+        .unwrap();
+    assert_eq!("Hello", loader.lookup(&US_ENGLISH, "greeting"));
+
+    std::fs::write(locale.join("main.ftl"), "greeting = Hello again").unwrap();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while loader.lookup(&US_ENGLISH, "greeting") != "Hello again" {
+        assert!(Instant::now() < deadline, "the loader wasn't reloaded");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    // A broken resource keeps the previous loader. It's renamed into place
+    // so that the watcher can't see it half written.
+    std::fs::write(dir.path().join("broken.txt"), "greeting = {").unwrap();
+    std::fs::rename(dir.path().join("broken.txt"), locale.join("main.ftl")).unwrap();
+    assert!(loader.reload().is_err());
+    assert_eq!("Hello again", loader.lookup(&US_ENGLISH, "greeting"));
+}
+
+#[test]
+fn lookup_with_args_accepts_pairs() {
+    let arc = arc_loader();
+
+    assert_eq!(
+        "texte avec une PARAM",
+        LOCALES.lookup_with_args(&FRENCH, "parameter", [("param", "PARAM")])
+    );
+    assert_eq!(
+        "text one 1 second two",
+        arc.lookup_with_args(
+            &US_ENGLISH,
+            "parameter2",
+            fluent_args!["param" => 1, "multi-word-param" => "two"]
+        )
+    );
+
+    let args = HashMap::from([(String::from("name"), String::from("Alice"))]);
+    assert_eq!(
+        Some("Bonjour Alice!".to_owned()),
+        LOCALES.try_lookup_with_args(&FRENCH, "greeting", &args)
+    );
+    // The argument maps taken by `lookup_complete` before `FluentArgs`.
+    let args = HashMap::from([(
+        std::borrow::Cow::Borrowed("name"),
+        fluent_templates::fluent_bundle::FluentValue::from("Alice"),
+    )]);
+    assert_eq!(
+        "Bonjour Alice!",
+        LOCALES.lookup_with_args(&FRENCH, "greeting", &args)
+    );
+    assert!(arc
+        .lookup_with_args_result(&FRENCH, "greeting", Vec::<(&str, i32)>::new())
+        .is_err());
+}
+
+#[test]
+fn lookup_into_writes_to_buffer() {
+    let arc = arc_loader();
+    let loaders: [&dyn DynLoader; 2] = [&*LOCALES, &arc];
+
+    for loader in loaders {
+        let mut out = String::from("<p>");
+        let args = fluent_args!["name" => "Alice"];
+        loader
+            .lookup_into(&mut out, &FRENCH, "greeting", Some(&args))
+            .unwrap();
+        loader
+            .lookup_into(&mut out, &FRENCH, "fallback", None)
+            .unwrap();
+        loader
+            .lookup_into(&mut out, &FRENCH, "missing", None)
+            .unwrap();
+        assert_eq!(
+            "<p>Bonjour Alice!this should fall backUnknown localization missing",
+            out
+        );
+    }
+}
+
+#[test]
+fn attributes_of_message() {
+    let arc = arc_loader();
+    let loaders: [&dyn DynLoader; 2] = [&*LOCALES, &arc];
+
+    for loader in loaders {
+        assert_eq!(vec!["placeholder"], loader.attributes(&FRENCH, "greeting"));
+        assert_eq!(
+            vec!["placeholder"],
+            loader.attributes(&JAPANESE, "greeting")
+        );
+        assert!(loader.attributes(&FRENCH, "simple").is_empty());
+        assert!(loader.attributes(&FRENCH, "missing").is_empty());
+    }
+}
+
+#[test]
+fn lookup_message_with_attributes() {
+    let arc = arc_loader();
+    let loaders: [&dyn DynLoader; 2] = [&*LOCALES, &arc];
+
+    for loader in loaders {
+        let args = fluent_args!["name" => "Alice"];
+        let message = loader
+            .lookup_message(&FRENCH, "greeting", Some(&args))
+            .unwrap();
+        assert_eq!("Bonjour Alice!", message.value);
+        assert_eq!(
+            HashMap::from([("placeholder".to_owned(), "Salut l'ami!".to_owned())]),
+            message.attrs
+        );
+
+        let message = loader.lookup_message(&FRENCH, "simple", None).unwrap();
+        assert_eq!("texte simple", message.value);
+        assert!(message.attrs.is_empty());
+
+        assert_eq!(None, loader.lookup_message(&FRENCH, "missing", None));
+    }
+}
+
+#[test]
+fn fallback_chain_for_language() {
+    let arc = arc_loader();
+    let loaders: [&dyn DynLoader; 2] = [&*LOCALES, &arc];
+
+    for loader in loaders {
+        assert_eq!(
+            vec![langid!("zh-TW"), langid!("zh"), US_ENGLISH],
+            loader.fallback_chain(&langid!("zh-TW"))
+        );
+        assert_eq!(vec![FRENCH, US_ENGLISH], loader.fallback_chain(&FRENCH));
+        assert_eq!(vec![US_ENGLISH], loader.fallback_chain(&US_ENGLISH));
+        assert_eq!(vec![US_ENGLISH], loader.fallback_chain(&JAPANESE));
+    }
+}
+
+#[test]
+fn lookup_with_several_requested_languages() {
+    let arc = arc_loader();
+    let loaders: [&dyn DynLoader; 2] = [&*LOCALES, &arc];
+
+    for loader in loaders {
+        let langs = [JAPANESE, langid!("de-AT"), FRENCH];
+        assert_eq!(
+            "Hallo Welt!",
+            loader.lookup_langs(&langs, "hello-world", None)
+        );
+        // Missing in German, but present in French before the fallback.
+        assert_eq!("texte simple", loader.lookup_langs(&langs, "simple", None));
+        assert_eq!(
+            "this should fall back",
+            loader.lookup_langs(&langs, "fallback", None)
+        );
+        assert_eq!(None, loader.try_lookup_langs(&langs, "missing", None));
+        assert_eq!(
+            "Unknown localization missing",
+            loader.lookup_langs(&[], "missing", None)
+        );
+    }
+}
+
+#[test]
+fn lookup_count_selects_plural() {
+    let args = fluent_args!["place" => "the cart"];
+    assert_eq!(
+        "One item in the cart",
+        LOCALES.lookup_count(&US_ENGLISH, "items", 1, Some(&args))
+    );
+    assert_eq!(
+        "3 items in the cart",
+        arc_loader().lookup_count(&FRENCH, "items", 3u64, Some(&args))
+    );
+    assert_eq!(
+        "2.5 items in the cart",
+        LOCALES.lookup_count(&US_ENGLISH, "items", 2.5, Some(&args))
+    );
+}
+
+fluent_templates::static_loader! {
+    static STRICT_LOCALES = {
+        locales: "./tests/locales",
+        fallback_language: "en-US",
+        on_missing: fluent_templates::OnMissing::Panic,
+    };
+}
+
+fn missing_marker(lang: &LanguageIdentifier, text_id: &str, fallback_attempted: bool) -> String {
+    if fallback_attempted {
+        format!("[{lang}+:{text_id}]")
+    } else {
+        format!("[{lang}:{text_id}]")
+    }
+}
+
+#[test]
+fn on_missing_handler() {
+    let arc = ArcLoader::builder("./tests/locales", US_ENGLISH)
+        .on_missing(OnMissing::handler(missing_marker))
+        .build()
+        .unwrap();
+    let multi = MultiLoader::from_iter([Box::new(arc_loader()) as Box<dyn DynLoader>])
+        .with_on_missing(OnMissing::handler(missing_marker));
+    let loaders: [&dyn DynLoader; 2] = [&arc, &multi];
+
+    for loader in loaders {
+        assert_eq!("[fr+:missing]", loader.lookup(&FRENCH, "missing"));
+        assert_eq!("[en-US:missing]", loader.lookup(&US_ENGLISH, "missing"));
+        assert_eq!(
+            "[ja+:missing]",
+            loader.lookup_langs(&[JAPANESE, FRENCH], "missing", None)
+        );
+        let mut out = String::new();
+        loader
+            .lookup_into(&mut out, &FRENCH, "missing", None)
+            .unwrap();
+        assert_eq!("[fr+:missing]", out);
+        assert_eq!("Bonjour le monde!", loader.lookup(&FRENCH, "hello-world"));
+    }
+}
+
+#[test]
+#[should_panic(expected = "Unknown localization missing for language fr")]
+fn on_missing_panic() {
+    STRICT_LOCALES.lookup(&FRENCH, "missing");
+}
+
+#[test]
+fn on_missing_does_not_affect_fallible_lookups() {
+    assert_eq!(None, STRICT_LOCALES.try_lookup(&FRENCH, "missing"));
+    assert_eq!(
+        "this should fall back",
+        STRICT_LOCALES.lookup(&FRENCH, "fallback")
+    );
+}
+
+#[test]
+fn lookup_no_default_fallback_through_trait() {
+    let arc = arc_loader();
+    let multi = MultiLoader::from_iter([Box::new(arc_loader()) as Box<dyn DynLoader>]);
+    let loaders: [&dyn DynLoader; 3] = [&*LOCALES, &arc, &multi];
+
+    for loader in loaders {
+        assert_eq!(
+            Some("Bonjour le monde!".to_owned()),
+            loader.lookup_no_default_fallback(&FRENCH, "hello-world", None)
+        );
+        assert_eq!(
+            Some("气".to_owned()),
+            loader.lookup_no_default_fallback(&langid!("zh-TW"), "fallback-zh", None)
+        );
+        assert_eq!(
+            None,
+            loader.lookup_no_default_fallback(&FRENCH, "fallback", None)
+        );
+        assert_eq!(
+            None,
+            loader.lookup_no_default_fallback(&JAPANESE, "hello-world", None)
+        );
+    }
+}
+
+#[test]
+fn language_bound_loader() {
+    let arc = arc_loader();
+    let german = arc.for_lang(langid!("de"));
+    assert_eq!(&langid!("de"), german.lang());
+    assert_eq!("Hallo Welt!", german.get("hello-world"));
+    assert_eq!(
+        "Hallo Alice!",
+        german.get_with_args("greeting", [("name", "Alice")])
+    );
+    assert_eq!(None, german.try_get("missing"));
+    assert!(german.has("fallback"));
+
+    let dynamic: &dyn DynLoader = &*LOCALES;
+    let french = fluent_templates::LanguageBoundLoader::new(dynamic, FRENCH);
+    assert_eq!(
+        Some("Bonjour Bob!".to_owned()),
+        french.try_get_with_args("greeting", [("name", "Bob")])
+    );
+}
+
+#[test]
+fn map_output_transforms_lookups() {
+    let upper = arc_loader().map_output(|text| text.to_uppercase());
+    let multi = MultiLoader::from_iter([Box::new(upper) as Box<dyn DynLoader>]);
+
+    assert_eq!("BONJOUR LE MONDE!", multi.lookup(&FRENCH, "hello-world"));
+    assert_eq!(
+        Some("HELLO ALICE!".to_owned()),
+        multi.try_lookup_with_args(&US_ENGLISH, "greeting", [("name", "Alice")])
+    );
+    let mut out = String::new();
+    multi
+        .lookup_into(&mut out, &US_ENGLISH, "simple", None)
+        .unwrap();
+    assert_eq!("SIMPLE TEXT", out);
+    let message = multi.lookup_message(&FRENCH, "greeting", None).unwrap();
+    assert_eq!("SALUT L'AMI!", message.attrs["placeholder"]);
+}
+
+#[test]
+fn coverage_report() {
+    let from_builder = ArcLoader::builder("./tests/locales", US_ENGLISH)
+        .coverage()
+        .unwrap();
+    let from_loader = CoverageReport::from_loader(&arc_loader(), &US_ENGLISH);
+
+    for report in [from_builder, from_loader] {
+        assert!(!report.is_complete());
+        assert!(!report.locales.contains_key(&US_ENGLISH));
+
+        let french = &report.locales[&FRENCH];
+        assert_eq!(
+            ["fallback", "items"].as_slice(),
+            french.missing.iter().collect::<Vec<_>>()
+        );
+        assert!(french.extra.is_empty());
+        assert_eq!(report.total - 2, french.translated);
+
+        let chinese = &report.locales[&langid!("zh")];
+        assert!(chinese.extra.contains("fallback-zh"));
+        assert!(chinese.percentage() < french.percentage());
+    }
+}
+
+#[test]
+fn validation_report() {
+    let german = langid!("de");
+    let austrian = langid!("de-AT");
+    let sources = HashMap::from([
+        (
+            US_ENGLISH,
+            vec![
+                "-brand = Acme\ngreeting = Hello { $name }\nitems = { $count } items\nfarewell = Bye\nwelcome = { greeting }!"
+                    .to_owned(),
+            ],
+        ),
+        (
+            german.clone(),
+            vec![
+                "-old-brand = Acme\ngreeting = Hallo { $user }\nitems = { $count } Dinge\nwelcome = Willkommen { $name }!"
+                    .to_owned(),
+            ],
+        ),
+        (austrian.clone(), vec!["farewell = Servus".to_owned()]),
+    ]);
+    let report = ArcLoaderBuilder::from_sources(sources, US_ENGLISH)
+        .add_source(US_ENGLISH, "title = { -brand }")
+        .validate()
+        .unwrap();
+    assert!(!report.is_valid());
+    assert!(report.locales[&US_ENGLISH].is_valid());
+
+    let german = &report.locales[&german];
+    assert_eq!(
+        ["farewell", "title"].as_slice(),
+        german.missing.iter().collect::<Vec<_>>()
+    );
+    let greeting = &german.mismatched_placeholders["greeting"];
+    assert!(greeting.missing.contains("name"));
+    assert!(greeting.unexpected.contains("user"));
+    assert!(!german.mismatched_placeholders.contains_key("items"));
+    // The variables of referenced messages are included, as with
+    // `required_args`.
+    assert!(!german.mismatched_placeholders.contains_key("welcome"));
+    assert!(german.unreferenced_terms.contains("old-brand"));
+
+    // `de-AT` inherits the rest of its messages from `de`.
+    let austrian = &report.locales[&austrian];
+    assert_eq!(
+        ["title"].as_slice(),
+        austrian.missing.iter().collect::<Vec<_>>()
+    );
+    assert!(report.to_string().contains("unreferenced term: -old-brand"));
+}
+
+#[test]
+fn usage_tracking_records_lookups() {
+    let tracking = UsageTrackingLoader::new(arc_loader());
+    tracking.lookup(&FRENCH, "hello-world");
+    tracking.try_lookup_with_args(&US_ENGLISH, "greeting", [("name", "Alice")]);
+    tracking.lookup(&US_ENGLISH, "greeting.placeholder");
+    assert!(tracking.has(&US_ENGLISH, "simple"));
+
+    assert_eq!(
+        [
+            (US_ENGLISH, "greeting".to_owned()),
+            (US_ENGLISH, "greeting.placeholder".to_owned()),
+            (FRENCH, "hello-world".to_owned()),
+        ]
+        .into_iter()
+        .collect::<std::collections::BTreeSet<_>>(),
+        tracking.used()
+    );
+    let unused = tracking.unused_ids();
+    assert!(unused.contains("simple"));
+    assert!(!unused.contains("greeting"));
+    assert!(!unused.contains("hello-world"));
+
+    tracking.clear();
+    assert!(tracking.used().is_empty());
+}
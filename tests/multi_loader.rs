@@ -1,6 +1,6 @@
 use std::ops::Deref;
 
-use fluent_templates::{ArcLoader, Loader, MultiLoader};
+use fluent_templates::{ArcLoader, DynLoader, MultiLoader};
 use unic_langid::{langid, LanguageIdentifier};
 
 fluent_templates::static_loader! {
@@ -30,9 +30,9 @@ fn check_if_multiloader_works() {
         .unwrap();
 
     let multiloader = MultiLoader::from_iter([
-        Box::new(LOCALES.deref()) as Box<dyn Loader>,
-        Box::new(en_loader) as Box<dyn Loader>,
-        Box::new(cn_loader) as Box<dyn Loader>,
+        Box::new(LOCALES.deref()) as Box<dyn DynLoader>,
+        Box::new(en_loader) as Box<dyn DynLoader>,
+        Box::new(cn_loader) as Box<dyn DynLoader>,
     ]);
 
     assert_eq!(
@@ -41,3 +41,362 @@ fn check_if_multiloader_works() {
     );
     assert_eq!("儿", multiloader.lookup(&CHINESE, "exists"));
 }
+
+#[test]
+fn multiloader_with_prefixed_loaders() {
+    const US_ENGLISH: LanguageIdentifier = langid!("en-US");
+
+    fluent_templates::static_loader! {
+        static LIBRARY = {
+            locales: "./tests/locales",
+            fallback_language: "en-US",
+            prefix: "library-",
+            keys_module: library_keys,
+        };
+    }
+    let app = ArcLoader::builder("./tests/locales", US_ENGLISH)
+        .prefix("app-")
+        .build()
+        .unwrap();
+    let multiloader = MultiLoader::from_iter([
+        Box::new(LIBRARY.deref()) as Box<dyn DynLoader>,
+        Box::new(app) as Box<dyn DynLoader>,
+    ]);
+
+    assert_eq!("library-hello-world", library_keys::HELLO_WORLD);
+    assert_eq!(
+        "Hello World!",
+        multiloader.lookup(&US_ENGLISH, library_keys::HELLO_WORLD)
+    );
+    assert_eq!(
+        "Hello World!",
+        multiloader.lookup(&US_ENGLISH, "app-hello-world")
+    );
+    assert!(!multiloader.has(&US_ENGLISH, "hello-world"));
+    assert!(LIBRARY
+        .message_ids(&US_ENGLISH)
+        .all(|id| id.starts_with("library-")));
+}
+
+#[test]
+fn multiloader_of_a_single_loader_type_is_thread_safe() {
+    const US_ENGLISH: LanguageIdentifier = langid!("en-US");
+    const CHINESE: LanguageIdentifier = langid!("zh-CN");
+    static MULTILOADER: std::sync::OnceLock<MultiLoader<ArcLoader>> = std::sync::OnceLock::new();
+
+    let multiloader = MULTILOADER.get_or_init(|| {
+        let mut multiloader =
+            MultiLoader::from_iter([ArcLoader::builder("./tests/locales", CHINESE)
+                .build()
+                .unwrap()]);
+        multiloader.push_front(
+            ArcLoader::builder("./tests/locales", US_ENGLISH)
+                .build()
+                .unwrap(),
+        );
+        multiloader
+    });
+
+    std::thread::spawn(move || {
+        assert_eq!(
+            "Hello World!",
+            multiloader.lookup(&US_ENGLISH, "hello-world")
+        );
+        assert_eq!("儿", multiloader.lookup(&CHINESE, "exists"));
+    })
+    .join()
+    .unwrap();
+
+    let mut boxed = MultiLoader::new();
+    boxed.push_back(Box::new(LOCALES.deref()));
+    boxed.push_back(Box::new(multiloader));
+    std::thread::spawn(move || assert_eq!("儿", boxed.lookup(&CHINESE, "exists")))
+        .join()
+        .unwrap();
+}
+
+#[test]
+fn multiloader_routes_namespaced_ids() {
+    const US_ENGLISH: LanguageIdentifier = langid!("en-US");
+
+    let build = |source: &str| {
+        ArcLoader::builder("./tests/locales", US_ENGLISH)
+            .add_source(US_ENGLISH, source)
+            .duplicates(fluent_templates::Duplicates::Override)
+            .build()
+            .unwrap()
+    };
+    let mut multiloader = MultiLoader::from_iter([build("title = App")]);
+    multiloader.push_namespaced(
+        "payments",
+        build("title = Invoice\n    .tooltip = Your invoice"),
+    );
+
+    assert_eq!("App", multiloader.lookup(&US_ENGLISH, "title"));
+    assert_eq!(
+        "Invoice",
+        multiloader.lookup(&US_ENGLISH, "payments::title")
+    );
+    assert_eq!(
+        "Your invoice",
+        multiloader.lookup(&US_ENGLISH, "payments::title.tooltip")
+    );
+    assert_eq!(
+        vec!["tooltip".to_owned()],
+        multiloader.attributes(&US_ENGLISH, "payments::title")
+    );
+    assert!(!multiloader.has(&US_ENGLISH, "title.tooltip"));
+    assert!(!multiloader.has(&US_ENGLISH, "billing::title"));
+    assert!(multiloader
+        .message_ids(&US_ENGLISH)
+        .all(|id| !id.contains("::")));
+
+    assert!(multiloader.remove_namespaced("payments").is_some());
+    assert!(!multiloader.has(&US_ENGLISH, "payments::title"));
+}
+
+#[test]
+fn multiloader_layers_can_be_reordered_and_traced() {
+    const US_ENGLISH: LanguageIdentifier = langid!("en-US");
+
+    let build = |source: &str| {
+        ArcLoader::builder("./tests/locales", US_ENGLISH)
+            .add_source(US_ENGLISH, source)
+            .duplicates(fluent_templates::Duplicates::Override)
+            .build()
+            .unwrap()
+    };
+    let mut multiloader = MultiLoader::from_iter([build("title = Base")]);
+    multiloader.set_name(0, "base");
+    multiloader.insert(0, build("title = Tenant"));
+    assert!(multiloader.set_name(0, "tenant"));
+    assert!(!multiloader.set_name(2, "missing"));
+    multiloader.push_namespaced("payments", build("title = Invoice"));
+
+    assert_eq!(2, multiloader.len());
+    assert_eq!(
+        vec![Some("tenant"), Some("base")],
+        multiloader.iter().map(|(name, _)| name).collect::<Vec<_>>()
+    );
+    let traced = multiloader
+        .lookup_traced(&US_ENGLISH, "title", None)
+        .unwrap();
+    assert_eq!(
+        ("Tenant", Some(0), Some("tenant")),
+        (&*traced.value, traced.index, traced.name)
+    );
+    let traced = multiloader
+        .lookup_traced(&US_ENGLISH, "payments::title", None)
+        .unwrap();
+    assert_eq!(
+        ("Invoice", None, Some("payments")),
+        (&*traced.value, traced.index, traced.name)
+    );
+    assert_eq!(None, multiloader.lookup_traced(&US_ENGLISH, "nope", None));
+
+    let base = multiloader.position("base").unwrap();
+    multiloader.reorder(base, 0);
+    assert_eq!("Base", multiloader.lookup(&US_ENGLISH, "title"));
+    assert_eq!(Some(1), multiloader.position("tenant"));
+    assert!(multiloader.remove(1).is_some());
+    assert_eq!(1, multiloader.len());
+    assert!(multiloader.get(1).is_none());
+}
+
+#[test]
+fn multiloader_merges_locales() {
+    const US_ENGLISH: LanguageIdentifier = langid!("en-US");
+
+    let build = |langs: &[LanguageIdentifier]| {
+        let sources = langs
+            .iter()
+            .map(|lang| (lang.clone(), vec!["title = Title".to_owned()]))
+            .collect();
+        fluent_templates::ArcLoaderBuilder::from_sources(sources, US_ENGLISH)
+            .build()
+            .unwrap()
+    };
+    let mut multiloader = MultiLoader::from_iter([
+        build(&[US_ENGLISH, langid!("fr"), langid!("de")]),
+        build(&[US_ENGLISH, langid!("de")]),
+    ]);
+    multiloader.push_namespaced("payments", build(&[US_ENGLISH, langid!("fr")]));
+
+    assert_eq!(3, multiloader.locales().count());
+    assert_eq!(
+        vec![langid!("de"), US_ENGLISH, langid!("fr")],
+        multiloader.locales_sorted()
+    );
+
+    let multiloader = multiloader.with_locale_merge(fluent_templates::LocaleMerge::Intersection);
+    assert_eq!(vec![US_ENGLISH], multiloader.locales_sorted());
+}
+
+#[test]
+fn multiloader_routes_languages() {
+    const US_ENGLISH: LanguageIdentifier = langid!("en-US");
+    const JAPANESE: LanguageIdentifier = langid!("ja");
+
+    let build = |lang: LanguageIdentifier, source: &str| {
+        let sources = [(lang.clone(), vec![source.to_owned()])].into();
+        fluent_templates::ArcLoaderBuilder::from_sources(sources, lang)
+            .build()
+            .unwrap()
+    };
+    let mut multiloader = MultiLoader::new();
+    multiloader.push_for_langs(
+        &[JAPANESE, langid!("ko")],
+        Box::new(build(JAPANESE, "title = タイトル\nextra = 追加")),
+    );
+    multiloader.push_back(Box::new(build(US_ENGLISH, "title = Title")));
+
+    assert_eq!("Title", multiloader.lookup(&US_ENGLISH, "title"));
+    assert_eq!("タイトル", multiloader.lookup(&JAPANESE, "title"));
+    assert_eq!("タイトル", multiloader.lookup(&langid!("ja-JP"), "title"));
+    assert_eq!(None, multiloader.try_lookup(&US_ENGLISH, "extra"));
+    assert!(multiloader.message_ids(&US_ENGLISH).all(|id| id != "extra"));
+    assert_eq!(vec![US_ENGLISH, JAPANESE], multiloader.locales_sorted());
+}
+
+#[test]
+fn multiloader_reports_conflicts() {
+    const US_ENGLISH: LanguageIdentifier = langid!("en-US");
+    const FRENCH: LanguageIdentifier = langid!("fr");
+
+    let build = |sources: &[(LanguageIdentifier, &str)]| {
+        let sources = sources
+            .iter()
+            .map(|(lang, source)| (lang.clone(), vec![source.to_string()]))
+            .collect();
+        fluent_templates::ArcLoaderBuilder::from_sources(sources, US_ENGLISH)
+            .build()
+            .unwrap()
+    };
+    let mut multiloader = MultiLoader::from_iter([
+        build(&[(US_ENGLISH, "title = A\nbody = A"), (FRENCH, "title = A")]),
+        build(&[(US_ENGLISH, "title = B"), (FRENCH, "body = B")]),
+    ]);
+    multiloader.push_namespaced("payments", build(&[(US_ENGLISH, "title = C")]));
+    multiloader.push_for_langs(&[FRENCH], build(&[(US_ENGLISH, "body = D")]));
+
+    let conflicts = multiloader.check_conflicts();
+    assert_eq!(1, conflicts.len());
+    assert_eq!(
+        Some(&vec![0, 1]),
+        conflicts[&US_ENGLISH].get("title"),
+        "{conflicts:?}"
+    );
+    assert_eq!(1, conflicts[&US_ENGLISH].len());
+
+    multiloader.remove(1);
+    assert!(multiloader.check_conflicts().is_empty());
+}
+
+#[test]
+fn multiloader_replaces_loaders_in_place() {
+    const US_ENGLISH: LanguageIdentifier = langid!("en-US");
+
+    let build = |source: &str| {
+        let sources = [(US_ENGLISH, vec![source.to_owned()])].into();
+        fluent_templates::ArcLoaderBuilder::from_sources(sources, US_ENGLISH)
+            .build()
+            .unwrap()
+    };
+    let mut multiloader = MultiLoader::from_iter([build("title = Old"), build("body = Body")]);
+    multiloader.set_name(0, "app");
+
+    let previous = multiloader
+        .replace_named("app", build("title = New"))
+        .unwrap();
+    assert_eq!("Old", previous.lookup(&US_ENGLISH, "title"));
+    assert_eq!("New", multiloader.lookup(&US_ENGLISH, "title"));
+    assert_eq!(Some(0), multiloader.position("app"));
+
+    assert!(multiloader.replace(1, build("body = New body")).is_some());
+    assert_eq!("New body", multiloader.lookup(&US_ENGLISH, "body"));
+    assert!(multiloader.replace(2, build("")).is_none());
+    assert!(multiloader.replace_named("nope", build("")).is_none());
+    assert_eq!(2, multiloader.len());
+}
+
+#[test]
+fn multiloader_builder_boxes_loaders() {
+    const US_ENGLISH: LanguageIdentifier = langid!("en-US");
+
+    let overrides = fluent_templates::ArcLoaderBuilder::from_sources(
+        [(US_ENGLISH, vec!["hello-world = Hi!".to_owned()])].into(),
+        US_ENGLISH,
+    )
+    .build()
+    .unwrap();
+    let multiloader = MultiLoader::builder()
+        .with(overrides)
+        .with(&*LOCALES)
+        .with_for_langs(
+            &[langid!("zh")],
+            ArcLoader::builder("tests/locales", langid!("zh"))
+                .build()
+                .unwrap(),
+        )
+        .with_namespaced("base", &*LOCALES)
+        .build();
+
+    assert_eq!(3, multiloader.len());
+    assert_eq!("Hi!", multiloader.lookup(&US_ENGLISH, "hello-world"));
+    assert_eq!(
+        "Hello World!",
+        multiloader.lookup(&US_ENGLISH, "base::hello-world")
+    );
+}
+
+#[test]
+fn multiloader_reports_why_each_loader_failed() {
+    use fluent_templates::LookupError;
+
+    const US_ENGLISH: LanguageIdentifier = langid!("en-US");
+    const FRENCH: LanguageIdentifier = langid!("fr");
+
+    let build = |lang: LanguageIdentifier, source: &str| {
+        let sources = [(lang.clone(), vec![source.to_owned()])].into();
+        fluent_templates::ArcLoaderBuilder::from_sources(sources, lang)
+            .build()
+            .unwrap()
+    };
+    let mut multiloader = MultiLoader::builder()
+        .with(build(US_ENGLISH, "title = Title"))
+        .with(build(FRENCH, "title = Titre\nbody = { $name }"))
+        .with_namespaced("payments", build(US_ENGLISH, "title = Title"))
+        .build();
+    multiloader.set_name(1, "french");
+
+    let Err(LookupError::AllLoadersFailed(failures)) = multiloader.lookup_result(&FRENCH, "body")
+    else {
+        panic!("expected every loader to fail");
+    };
+    assert_eq!(2, failures.len());
+    assert_eq!(
+        (Some(0), None),
+        (failures[0].index, failures[0].name.as_deref())
+    );
+    assert!(matches!(failures[0].error, LookupError::LangNotLoaded(_)));
+    assert_eq!(Some("french"), failures[1].name.as_deref());
+    assert!(matches!(failures[1].error, LookupError::FluentError(_)));
+    let error = multiloader.lookup_result(&FRENCH, "body").unwrap_err();
+    assert!(error.to_string().contains("loader 1 (`french`)"), "{error}");
+
+    let Err(LookupError::AllLoadersFailed(failures)) =
+        multiloader.lookup_result(&US_ENGLISH, "payments::body")
+    else {
+        panic!("expected the namespaced loader to fail");
+    };
+    assert_eq!(Some("payments"), failures[0].name.as_deref());
+    assert!(matches!(
+        failures[0].error,
+        LookupError::MessageRetrieval(_) | LookupError::Exhausted { .. }
+    ));
+
+    assert!(matches!(
+        MultiLoader::new().lookup_result(&US_ENGLISH, "title"),
+        Err(LookupError::LangNotLoaded(_))
+    ));
+}
@@ -0,0 +1,2 @@
+simple = overridden text
+only-overridden = only in the overrides
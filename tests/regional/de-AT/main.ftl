@@ -0,0 +1 @@
+-january = Jänner
@@ -0,0 +1,2 @@
+-january = Januar
+invoice-due = Fällig im { -january }
@@ -0,0 +1,2 @@
+-january = January
+invoice-due = Due in { -january }
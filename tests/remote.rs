@@ -0,0 +1,106 @@
+#![cfg(feature = "remote")]
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+use fluent_templates::{DynLoader, RemoteLoader};
+use unic_langid::langid;
+
+type Files = Arc<Mutex<HashMap<String, (u32, String)>>>;
+
+/// Serves `files` over HTTP, using their versions as their `ETag`s, and
+/// returns the base URL.
+fn serve(files: Files) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut lines = BufReader::new(&stream).lines().map(Result::unwrap);
+            let path = lines.next().unwrap().split(' ').nth(1).unwrap().to_owned();
+            let etag = lines
+                .take_while(|line| !line.is_empty())
+                .find_map(|line| line.strip_prefix("If-None-Match: ").map(str::to_owned));
+
+            let response = match files.lock().unwrap().get(&path) {
+                Some((version, _)) if etag == Some(format!("\"{version}\"")) => {
+                    "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n".to_owned()
+                }
+                Some((version, body)) => format!(
+                    "HTTP/1.1 200 OK\r\nETag: \"{version}\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                ),
+                None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_owned(),
+            };
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+    url
+}
+
+#[test]
+fn remote_loader_refreshes_changed_resources() {
+    let files = Files::default();
+    files.lock().unwrap().extend([
+        (
+            "/manifest.json".to_owned(),
+            (
+                1,
+                r#"{ "en-US": ["en-US.ftl"], "fr": ["fr.ftl"] }"#.to_owned(),
+            ),
+        ),
+        ("/en-US.ftl".to_owned(), (1, "hello = Hello".to_owned())),
+        ("/fr.ftl".to_owned(), (1, "hello = Bonjour".to_owned())),
+    ]);
+    let loader = RemoteLoader::builder(serve(files.clone()), langid!("en-US"))
+        .build()
+        .unwrap();
+    assert_eq!("Bonjour", loader.lookup(&langid!("fr"), "hello"));
+    let shared: &dyn DynLoader = &loader;
+    assert!(shared.has(&langid!("en-US"), "hello"));
+    assert_eq!(
+        vec![langid!("en-US"), langid!("fr")],
+        shared.locales_sorted()
+    );
+    let updates = Arc::new(Mutex::new(Vec::new()));
+    let log = updates.clone();
+    loader.on_update(move |changed| log.lock().unwrap().push(changed.to_vec()));
+
+    assert!(!loader.refresh().unwrap());
+
+    files
+        .lock()
+        .unwrap()
+        .insert("/fr.ftl".to_owned(), (2, "hello = Salut".to_owned()));
+    assert!(loader.refresh().unwrap());
+    assert_eq!("Salut", shared.lookup(&langid!("fr"), "hello"));
+    assert_eq!("Hello", loader.lookup(&langid!("en-US"), "hello"));
+
+    // A refresh that fails after downloading a change still rebuilds the
+    // loader on the next refresh, even though nothing else has changed.
+    let french = files.lock().unwrap().remove("/fr.ftl").unwrap();
+    files
+        .lock()
+        .unwrap()
+        .insert("/en-US.ftl".to_owned(), (2, "hello = Hi".to_owned()));
+    assert!(loader.refresh().is_err());
+    assert_eq!("Hello", loader.lookup(&langid!("en-US"), "hello"));
+    files.lock().unwrap().insert("/fr.ftl".to_owned(), french);
+    assert!(loader.refresh().unwrap());
+    assert_eq!("Hi", loader.lookup(&langid!("en-US"), "hello"));
+    assert!(!loader.refresh().unwrap());
+
+    files
+        .lock()
+        .unwrap()
+        .insert("/fr.ftl".to_owned(), (3, "hello = { $".to_owned()));
+    assert!(loader.refresh().is_err());
+    assert_eq!("Salut", loader.lookup(&langid!("fr"), "hello"));
+    assert_eq!(
+        vec![vec![langid!("fr")], vec![langid!("en-US")]],
+        *updates.lock().unwrap()
+    );
+}
@@ -28,6 +28,24 @@ macro_rules! make_loaders {
     }};
 }
 
+/// Builds a loader with a right-to-left locale.
+fn rtl_loader() -> fluent_templates::ArcLoader {
+    let dir = tempfile::tempdir().unwrap();
+    for (lang, ftl) in [
+        (
+            "ar",
+            "hello-world = مرحبا بالعالم!\nlanguage-name = العربية",
+        ),
+        ("en-US", "hello-world = Hello World!"),
+    ] {
+        std::fs::create_dir(dir.path().join(lang)).unwrap();
+        std::fs::write(dir.path().join(lang).join("main.ftl"), ftl).unwrap();
+    }
+    fluent_templates::ArcLoader::builder(dir.path(), unic_langid::langid!("en-US"))
+        .build()
+        .unwrap()
+}
+
 /// Generates tests for each loader in different locales.
 macro_rules! generate_tests {
     ($(fn $locale_test_fn:ident ($template_engine:ident, $locale:expr) {
@@ -64,6 +82,27 @@ macro_rules! generate_tests {
                     )*
                 }
 
+                #[cfg(feature = "minijinja")]
+                if stringify!($template_engine) == "minijinja" {
+                    let (static_loader, arc_loader) = make_loaders!();
+                    let mut static_env = minijinja::Environment::new();
+                    static_loader.add_to_environment(&mut static_env);
+                    let mut arc_env = minijinja::Environment::new();
+                    arc_loader.add_to_environment(&mut arc_env);
+
+                    $(
+                        $assert_macro ! (
+                            static_env.render_str($lhs, &data).unwrap(),
+                            $rhs
+                        );
+
+                        $assert_macro ! (
+                            arc_env.render_str($lhs, &data).unwrap(),
+                            $rhs
+                        );
+                    )*
+                }
+
                 #[cfg(feature = "tera")]
                 if stringify!($template_engine) == "tera" {
                     let (static_loader, arc_loader) = make_loaders!();
@@ -106,6 +145,7 @@ mod handlebars {
             assert_eq!(r#"{{#fluent "parameter"}}{{#fluentparam "param"}}blah blah{{/fluentparam}}{{/fluent}}"#, "text with a blah blah");
             assert_eq!(r#"{{#fluent "parameter2"}}{{#fluentparam "param"}}foo{{/fluentparam}}{{#fluentparam "multi-word-param"}}bar{{/fluentparam}}{{/fluent}}"#, "text one foo second bar");
             assert_eq!(r#"{{fluent "fallback"}}"#, "this should fall back");
+            assert_eq!(r#"{{fluent "greeting" attr="placeholder"}}"#, "Hello Friend!");
         }
 
         fn french(handlebars, "fr") {
@@ -125,6 +165,248 @@ mod handlebars {
             assert_eq!(r#"{{fluent "fallback"}}"#, "this should fall back");
         }
     }
+
+    #[test]
+    fn text_direction() {
+        let mut handlebars = handlebars::Handlebars::new();
+        let loader = FluentLoader::new(super::rtl_loader()).text_direction();
+        handlebars.register_helper("dir", Box::new(loader));
+
+        let render = |template, lang| {
+            handlebars
+                .render_template(template, &serde_json::json!({ "lang": lang }))
+                .unwrap()
+        };
+        assert_eq!(render("{{dir}}", "ar"), "rtl");
+        assert_eq!(render("{{dir}}", "ar-EG"), "rtl");
+        assert_eq!(render("{{dir}}", "en-US"), "ltr");
+        assert_eq!(render(r#"{{dir "ar"}}"#, "en-US"), "rtl");
+        // Hebrew isn't loaded, so the text falls back to English.
+        assert_eq!(render("{{dir}}", "he"), "ltr");
+    }
+
+    #[test]
+    fn lang_parameter() {
+        let (loader, _) = make_loaders!();
+        let mut handlebars = handlebars::Handlebars::new();
+        handlebars.register_helper("fluent", Box::new(loader));
+        let render =
+            |template| handlebars.render_template(template, &serde_json::json!({"lang": "en-US"}));
+
+        assert_eq!(
+            render(r#"{{fluent "hello-world"}} / {{fluent "hello-world" lang="fr"}}"#).unwrap(),
+            "Hello World! / Bonjour le monde!"
+        );
+        assert_eq!(
+            render(r#"{{fluent "greeting" lang="de" name="Alice"}}"#).unwrap(),
+            "Hallo Alice!"
+        );
+        assert!(render(r#"{{fluent "hello-world" lang="not a language"}}"#).is_err());
+    }
+
+    #[test]
+    fn json_args() {
+        let sources = [(
+            unic_langid::langid!("en-US"),
+            vec!["status = { $online ->\n    [true] Online\n   *[false] Offline\n}\ncount = { $count } items\nemails = { $count ->\n    [one] One email\n   *[other] Emails\n}".to_owned()],
+        )]
+        .into();
+        let loader = ArcLoaderBuilder::from_sources(sources, unic_langid::langid!("en-US"))
+            .customize(|bundle| bundle.set_use_isolating(false))
+            .build()
+            .unwrap();
+        let mut handlebars = handlebars::Handlebars::new();
+        handlebars.register_helper("fluent", Box::new(FluentLoader::new(loader)));
+        let render = |template| {
+            let data = serde_json::json!({"lang": "en-US", "user": {"online": true}, "items": [1]});
+            handlebars.render_template(template, &data)
+        };
+
+        assert_eq!(
+            render(r#"{{fluent "status" online=user.online}}"#).unwrap(),
+            "Online"
+        );
+        assert_eq!(
+            render(r#"{{fluent "status" online=false}}"#).unwrap(),
+            "Offline"
+        );
+        assert_eq!(render(r#"{{fluent "count" count=3}}"#).unwrap(), "3 items");
+        assert!(render(r#"{{fluent "count" count=items}}"#).is_err());
+        assert_eq!(
+            render(r#"{{#fluent "count"}}{{#fluentparam "count" type="number"}} 1.50 {{/fluentparam}}{{/fluent}}"#)
+                .unwrap(),
+            "1.5 items"
+        );
+        assert_eq!(
+            render(
+                r#"{{#fluent "count"}}{{#fluentparam "count"}} 1.50 {{/fluentparam}}{{/fluent}}"#
+            )
+            .unwrap(),
+            " 1.50  items"
+        );
+        assert_eq!(
+            render(r#"{{#fluent "emails"}}{{#fluentparam "count" type="number"}}1{{/fluentparam}}{{/fluent}}"#)
+                .unwrap(),
+            "One email"
+        );
+        assert_eq!(
+            render(r#"{{#fluent "emails"}}{{#fluentparam "count"}}1{{/fluentparam}}{{/fluent}}"#)
+                .unwrap(),
+            "Emails"
+        );
+        assert!(render(r#"{{#fluent "count"}}{{#fluentparam "count" type="number"}}many{{/fluentparam}}{{/fluent}}"#).is_err());
+        assert!(render(
+            r#"{{#fluent "count"}}{{#fluentparam "count" type="date"}}1{{/fluentparam}}{{/fluent}}"#
+        )
+        .is_err());
+        assert!(render(r#"{{fluent "count" count=user}}"#).is_err());
+    }
+
+    #[test]
+    fn else_block() {
+        let (loader, _) = make_loaders!();
+        let mut handlebars = handlebars::Handlebars::new();
+        handlebars.register_helper(
+            "fluent",
+            Box::new(loader.with_missing_key(MissingKey::Error)),
+        );
+        let render = |template| {
+            handlebars
+                .render_template(
+                    template,
+                    &serde_json::json!({"lang": "fr", "name": "Alice"}),
+                )
+                .unwrap()
+        };
+
+        assert_eq!(
+            render(r#"{{#fluent "missing"}}{{else}}<b>{{name}}</b>{{/fluent}}"#),
+            "<b>Alice</b>"
+        );
+        assert_eq!(
+            render(r#"{{#fluent "simple"}}{{else}}fallback{{/fluent}}"#),
+            "texte simple"
+        );
+        assert_eq!(
+            render(
+                r#"{{#fluent "parameter"}}{{#fluentparam "param"}}{{name}}{{/fluentparam}}{{else}}fallback{{/fluent}}"#
+            ),
+            "texte avec une Alice"
+        );
+    }
+
+    #[test]
+    fn register_handlebars() {
+        let mut handlebars = handlebars::Handlebars::new();
+        FluentLoader::new(super::rtl_loader()).register_handlebars(&mut handlebars);
+        let render = |template| {
+            handlebars
+                .render_template(template, &serde_json::json!({"lang": "ar"}))
+                .unwrap()
+        };
+
+        assert_eq!(render("{{fluent-dir}}"), "rtl");
+        assert_eq!(
+            render(r#"{{#each (fluent-locales)}}{{id}};{{/each}}"#),
+            "ar;en-US;"
+        );
+        assert_eq!(
+            render(r#"{{fluent "hello-world" lang="en-US"}}"#),
+            "Hello World!"
+        );
+    }
+
+    #[test]
+    fn escape() {
+        let (loader, _) = make_loaders!();
+        let mut handlebars = handlebars::Handlebars::new();
+        handlebars.register_helper("fluent", Box::new(loader.clone()));
+        handlebars.register_helper("escaped", Box::new(loader.with_escape(true)));
+        let render = |template| {
+            handlebars
+                .render_template(template, &serde_json::json!({"lang": "en-US"}))
+                .unwrap()
+        };
+
+        assert_eq!(render(r#"{{fluent "greeting" name="<b>"}}"#), "Hello <b>!");
+        assert_eq!(
+            render(r#"{{fluent "greeting" name="<b>" escape=true}}"#),
+            "Hello &lt;b&gt;!"
+        );
+        assert_eq!(
+            render(r#"{{escaped "greeting" name="<b>"}}"#),
+            "Hello &lt;b&gt;!"
+        );
+        assert_eq!(
+            render(r#"{{escaped "greeting" name="<b>" escape=false}}"#),
+            "Hello <b>!"
+        );
+    }
+
+    /// The default language is used when the context has no language
+    #[test]
+    fn use_default_lang() {
+        let loader = FluentLoader::new(&*super::LOCALES).with_default_lang("de".parse().unwrap());
+        let mut handlebars = handlebars::Handlebars::new();
+        handlebars.register_helper("fluent", Box::new(loader));
+        let render = |data| handlebars.render_template(r#"{{fluent "hello-world"}}"#, &data);
+
+        assert_eq!(render(serde_json::json!({})).unwrap(), "Hallo Welt!");
+        assert_eq!(
+            render(serde_json::json!({"lang": "fr"})).unwrap(),
+            "Bonjour le monde!"
+        );
+        assert!(render(serde_json::json!({"lang": 1})).is_err());
+    }
+
+    /// Rendering fails when there's no language in the context and no default
+    #[test]
+    fn no_default_and_no_context_lang_error() {
+        let mut handlebars = handlebars::Handlebars::new();
+        handlebars.register_helper("fluent", Box::new(FluentLoader::new(&*super::LOCALES)));
+        let dir = FluentLoader::new(&*super::LOCALES).text_direction();
+        handlebars.register_helper("dir", Box::new(dir));
+
+        let data = serde_json::json!({});
+        assert!(handlebars
+            .render_template(r#"{{fluent "hello-world"}}"#, &data)
+            .is_err());
+        assert!(handlebars.render_template("{{dir}}", &data).is_err());
+    }
+
+    #[test]
+    fn missing_key() {
+        let render = |missing_key| {
+            let mut handlebars = handlebars::Handlebars::new();
+            let loader = FluentLoader::new(&*super::LOCALES).with_missing_key(missing_key);
+            handlebars.register_helper("fluent", Box::new(loader));
+            handlebars.render_template(
+                r#"{{fluent "missing"}}"#,
+                &serde_json::json!({"lang": "fr"}),
+            )
+        };
+        assert_eq!(
+            render(MissingKey::Fallback).unwrap(),
+            "Unknown localization missing"
+        );
+        assert_eq!(render(MissingKey::Mark).unwrap(), "[missing: missing]");
+        assert!(render(MissingKey::Error).is_err());
+    }
+
+    #[test]
+    fn available_locales() {
+        let mut handlebars = handlebars::Handlebars::new();
+        let loader = FluentLoader::new(super::rtl_loader()).available_locales();
+        handlebars.register_helper("locales", Box::new(loader));
+
+        let template = r#"{{#each (locales)}}{{id}}:{{name}}:{{rtl}};{{/each}}"#;
+        assert_eq!(
+            handlebars
+                .render_template(template, &serde_json::json!({}))
+                .unwrap(),
+            "ar:العربية:true;en-US:en-US:false;"
+        );
+    }
 }
 
 #[cfg(feature = "tera")]
@@ -136,6 +418,7 @@ mod tera {
             assert_eq!(r#"{{ fluent(key="parameter", lang="{lang}", param="PARAM") }}"#, "text with a PARAM");
             assert_eq!(r#"{{ fluent(key="parameter2", lang="{lang}", param="P1", multi_word_param="P2") }}"#, "text one P1 second P2");
             assert_eq!(r#"{{ fluent(key="fallback", lang="{lang}") }}"#, "this should fall back");
+            assert_eq!(r#"{{ fluent(key="greeting", attr="placeholder", lang="{lang}") }}"#, "Hello Friend!");
         }
 
         fn french(tera, "fr") {
@@ -173,6 +456,162 @@ mod tera {
         );
     }
 
+    /// The macro's template loader defaults to the fallback language
+    #[test]
+    fn static_template_loader() {
+        fluent_templates::static_loader! {
+            static GERMAN_LOCALES = {
+                locales: "./tests/locales",
+                fallback_language: "de",
+                template_loader: GERMAN_TPL,
+            };
+        }
+        let mut tera = tera::Tera::default();
+        tera.register_function("fluent", GERMAN_TPL.clone());
+        let context = tera::Context::new();
+        assert_eq!(
+            tera.render_str(r#"{{ fluent(key="hello-world") }}"#, &context)
+                .unwrap(),
+            "Hallo Welt!"
+        );
+    }
+
+    #[test]
+    fn text_direction() {
+        let loader = FluentLoader::new(super::rtl_loader())
+            .with_default_lang("ar".parse().unwrap())
+            .text_direction();
+        let mut tera = tera::Tera::default();
+        tera.register_function("dir", loader);
+        let context = tera::Context::new();
+        assert_eq!(tera.render_str("{{ dir() }}", &context).unwrap(), "rtl");
+        assert_eq!(
+            tera.render_str(r#"{{ dir(lang="en-US") }}"#, &context)
+                .unwrap(),
+            "ltr"
+        );
+    }
+
+    /// `render_tera` takes the language from the context
+    #[test]
+    fn context_lang() {
+        let loader = FluentLoader::new(&*super::LOCALES)
+            .with_default_lang("de".parse().unwrap())
+            .with_lang_variable("locale");
+        let mut tera = tera::Tera::default();
+        tera.register_function("fluent", loader.clone());
+        tera.add_raw_template("title", r#"{{ fluent(key="hello-world") }}"#)
+            .unwrap();
+        tera.add_raw_template(
+            "english",
+            r#"{{ fluent(key="hello-world", lang="en-US") }}"#,
+        )
+        .unwrap();
+
+        let mut context = tera::Context::new();
+        context.insert("locale", "fr");
+        assert_eq!(
+            loader.render_tera(&tera, "title", &context).unwrap(),
+            "Bonjour le monde!"
+        );
+        assert_eq!(
+            loader.render_tera(&tera, "english", &context).unwrap(),
+            "Hello World!"
+        );
+        // The language only applies while rendering with `render_tera`.
+        assert_eq!(tera.render("title", &context).unwrap(), "Hallo Welt!");
+        assert_eq!(
+            loader
+                .render_tera(&tera, "title", &tera::Context::new())
+                .unwrap(),
+            "Hallo Welt!"
+        );
+        context.insert("locale", "not a language");
+        assert!(loader.render_tera(&tera, "title", &context).is_err());
+    }
+
+    #[test]
+    fn escape() {
+        let (loader, _) = make_loaders!();
+        let mut tera = tera::Tera::default();
+        tera.register_function("fluent", loader.clone());
+        tera.register_function("escaped", loader.with_escape(true));
+        tera.add_raw_templates([
+            (
+                "raw.html",
+                r#"{{ fluent(key="greeting", lang="en-US", name="<b>") }}"#,
+            ),
+            (
+                "escaped.html",
+                r#"{{ escaped(key="greeting", lang="en-US", name="<b>") }}"#,
+            ),
+            (
+                "unescaped.html",
+                r#"{{ escaped(key="greeting", lang="en-US", name="<b>", escape=false) }}"#,
+            ),
+            (
+                "escaped.txt",
+                r#"{{ fluent(key="greeting", lang="en-US", name="<b>", escape=true) }}"#,
+            ),
+            (
+                "escape_arg.html",
+                r#"{{ escaped(key="greeting", lang="en-US", name="<b>", escape=true) }}"#,
+            ),
+            (
+                "raw.txt",
+                r#"{{ fluent(key="greeting", lang="en-US", name="<b>") }}"#,
+            ),
+            (
+                "unescaped.txt",
+                r#"{{ fluent(key="greeting", lang="en-US", name="<b>", escape=false) }}"#,
+            ),
+        ])
+        .unwrap();
+        let render = |name| tera.render(name, &tera::Context::new()).unwrap();
+
+        // Without an escape setting Tera's autoescaping decides, and with
+        // one the function escapes in its place, so messages are only
+        // escaped once.
+        assert_eq!(render("raw.html"), "Hello &lt;b&gt;!");
+        assert_eq!(render("raw.txt"), "Hello <b>!");
+        assert_eq!(render("escaped.html"), "Hello &lt;b&gt;!");
+        assert_eq!(render("unescaped.html"), "Hello <b>!");
+        assert_eq!(render("escaped.txt"), "Hello &lt;b&gt;!");
+        assert_eq!(render("escape_arg.html"), "Hello &lt;b&gt;!");
+        assert_eq!(render("unescaped.txt"), "Hello <b>!");
+    }
+
+    #[test]
+    fn missing_key() {
+        let render = |missing_key| {
+            let loader = FluentLoader::new(&*super::LOCALES).with_missing_key(missing_key);
+            let mut tera = tera::Tera::default();
+            tera.register_function("fluent", loader);
+            tera.render_str(
+                r#"{{ fluent(key="missing", lang="fr") }}"#,
+                &tera::Context::new(),
+            )
+        };
+        assert_eq!(
+            render(MissingKey::Fallback).unwrap(),
+            "Unknown localization missing"
+        );
+        assert_eq!(render(MissingKey::Mark).unwrap(), "[missing: missing]");
+        assert!(render(MissingKey::Error).is_err());
+    }
+
+    #[test]
+    fn available_locales() {
+        let loader = FluentLoader::new(super::rtl_loader()).available_locales();
+        let mut tera = tera::Tera::default();
+        tera.register_function("locales", loader);
+        let template = "{% for locale in locales() %}{{ locale.id }}:{{ locale.name }}:{{ locale.rtl }};{% endfor %}";
+        assert_eq!(
+            tera.render_str(template, &tera::Context::new()).unwrap(),
+            "ar:العربية:true;en-US:en-US:false;"
+        );
+    }
+
     /// Rendering fails when no default and no explicit lang argument is provided
     #[test]
     fn no_default_and_no_argument_error() {
@@ -185,3 +624,141 @@ mod tera {
             .is_err());
     }
 }
+
+#[cfg(feature = "minijinja")]
+mod minijinja {
+    generate_tests! {
+        fn english(minijinja, "en-US") {
+            assert_eq!(r#"{{ fluent("simple") }}"#, "simple text");
+            assert_eq!(r#"{{ fluent("reference") }}"#, "simple text with a reference: foo");
+            assert_eq!(r#"{{ fluent("parameter", param="PARAM") }}"#, "text with a PARAM");
+            assert_eq!(r#"{{ fluent("parameter2", param="P1", multi_word_param="P2") }}"#, "text one P1 second P2");
+            assert_eq!(r#"{{ "fallback" | fluent }}"#, "this should fall back");
+            assert_eq!(r#"{{ "greeting" | fluent(attr="placeholder") }}"#, "Hello Friend!");
+        }
+
+        fn french(minijinja, "fr") {
+            assert_eq!(r#"{{ fluent("simple") }}"#, "texte simple");
+            assert_eq!(r#"{{ "parameter" | fluent(param="PARAM") }}"#, "texte avec une PARAM");
+            assert_eq!(r#"{{ fluent("simple", lang="en-US") }}"#, "simple text");
+        }
+
+        fn chinese(minijinja, "zh-TW") {
+            assert_eq!(r#"{{ fluent("exists") }}"#, "兒");
+            assert_eq!(r#"{{ fluent("fallback-zh") }}"#, "气");
+        }
+    }
+
+    #[test]
+    fn translated() {
+        let mut env = minijinja::Environment::new();
+        FluentLoader::new(&*super::LOCALES).add_to_environment(&mut env);
+        let render = |template| {
+            env.render_str(template, minijinja::context! { lang => "fr" })
+                .unwrap()
+        };
+        assert_eq!(render(r#"{{ "simple" is translated }}"#), "True");
+        assert_eq!(render(r#"{{ "missing" is translated }}"#), "False");
+        assert_eq!(
+            render(r#"{{ "greeting.placeholder" is translated("en-US") }}"#),
+            "True"
+        );
+    }
+
+    #[test]
+    fn use_default_lang() {
+        let loader = FluentLoader::new(&*super::LOCALES).with_default_lang("de".parse().unwrap());
+        let mut env = minijinja::Environment::new();
+        loader
+            .with_lang_variable("locale")
+            .add_to_environment(&mut env);
+        let render = |context| env.render_str(r#"{{ fluent("hello-world") }}"#, context);
+        assert_eq!(render(minijinja::context! {}).unwrap(), "Hallo Welt!");
+        assert_eq!(
+            render(minijinja::context! { locale => "fr" }).unwrap(),
+            "Bonjour le monde!"
+        );
+        assert!(render(minijinja::context! { locale => "not a language" }).is_err());
+    }
+
+    #[test]
+    fn add_to_environment_with_names() {
+        let names = MinijinjaNames {
+            function: "t",
+            filter: "t",
+            test: "has_translation",
+            dir: "dir",
+            locales: "locales",
+        };
+        let mut env = minijinja::Environment::new();
+        FluentLoader::new(super::rtl_loader())
+            .with_default_lang("ar".parse().unwrap())
+            .add_to_environment_with_names(&mut env, &names);
+        let template = r#"{{ dir() }} {{ dir(lang="en-US") }} {% if "hello-world" is has_translation %}{{ t("hello-world") }}{% endif %}"#;
+        assert_eq!(
+            env.render_str(template, minijinja::context! {}).unwrap(),
+            "rtl ltr مرحبا بالعالم!"
+        );
+        let template = "{% for locale in locales() %}{{ locale.id }}:{{ locale.name }}:{{ locale.rtl }};{% endfor %}";
+        assert_eq!(
+            env.render_str(template, minijinja::context! {}).unwrap(),
+            "ar:العربية:True;en-US:en-US:False;"
+        );
+    }
+
+    #[test]
+    fn escape() {
+        let (loader, _) = make_loaders!();
+        let mut env = minijinja::Environment::new();
+        loader.clone().add_to_environment(&mut env);
+        env.add_function("escaped", loader.with_escape(true).into_minijinja_fn());
+        let render = |name, template| {
+            env.template_from_named_str(name, template)
+                .unwrap()
+                .render(minijinja::context! { lang => "en-US" })
+                .unwrap()
+        };
+
+        // Without an escape setting MiniJinja's autoescaping decides, and
+        // with one the message is marked as safe, so messages are only
+        // escaped once.
+        let raw = r#"{{ fluent("greeting", name="<b>") }}"#;
+        assert_eq!(render("raw.html", raw), "Hello &lt;b&gt;!");
+        assert_eq!(render("raw.txt", raw), "Hello <b>!");
+        let escaped = r#"{{ escaped("greeting", name="<b>") }}"#;
+        assert_eq!(render("escaped.html", escaped), "Hello &lt;b&gt;!");
+        assert_eq!(render("escaped.txt", escaped), "Hello &lt;b&gt;!");
+        let unescaped = r#"{{ escaped("greeting", name="<b>", escape=false) }}"#;
+        assert_eq!(render("unescaped.html", unescaped), "Hello <b>!");
+    }
+
+    #[test]
+    fn missing_key() {
+        let render = |missing_key| {
+            let mut env = minijinja::Environment::new();
+            FluentLoader::new(&*super::LOCALES)
+                .with_missing_key(missing_key)
+                .add_to_environment(&mut env);
+            env.render_str(
+                r#"{{ fluent("missing", lang="fr") }}"#,
+                minijinja::context! {},
+            )
+        };
+        assert_eq!(
+            render(MissingKey::Fallback).unwrap(),
+            "Unknown localization missing"
+        );
+        assert_eq!(render(MissingKey::Mark).unwrap(), "[missing: missing]");
+        assert!(render(MissingKey::Error).is_err());
+    }
+
+    /// Rendering fails when there's no default, context or argument language
+    #[test]
+    fn no_default_and_no_context_lang_error() {
+        let mut env = minijinja::Environment::new();
+        FluentLoader::new(&*super::LOCALES).add_to_environment(&mut env);
+        assert!(env
+            .render_str(r#"{{ fluent("hello-world") }}"#, minijinja::context! {})
+            .is_err());
+    }
+}